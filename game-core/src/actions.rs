@@ -0,0 +1,41 @@
+use bevy_ecs::prelude::*;
+use bracket_geometry::prelude::Point;
+
+use crate::RunState;
+
+/// Something the player has chosen to do, in the game's terms rather than the keyboard's.
+#[derive(PartialEq, Copy, Clone)]
+pub enum PlayerAction {
+    /// Step one tile, or attack whatever is standing there.
+    Move(i32, i32),
+    Wait,
+    Pickup,
+    Descend,
+    /// Use an item from the backpack, aimed at a tile if it's ranged.
+    UseItem(Entity, Option<Point>),
+    Drop(Entity),
+    Remove(Entity),
+    ShowInventory,
+    ShowDropItem,
+    ShowRemoveItem,
+    ShowHelp,
+    SaveAndQuit,
+}
+
+/// The player's next action, left for the player action system to carry out as soon as the
+/// game is waiting on the player.
+#[derive(Resource, Default)]
+pub struct PendingAction(pub Option<PlayerAction>);
+
+/// Whether the game is waiting on the player in this state.
+pub fn awaiting_player(state: RunState) -> bool {
+    matches!(
+        state,
+        RunState::AwaitingInput
+            | RunState::ShowInventory
+            | RunState::ShowDropItem
+            | RunState::ShowRemoveItem
+            | RunState::ShowHelp
+            | RunState::ShowTargeting { .. }
+    )
+}
@@ -0,0 +1,186 @@
+use bracket_random::prelude::RandomNumberGenerator;
+
+use crate::components::{EquipmentSlot, Rarity};
+
+/// A modifier that can be rolled onto a piece of equipment, either before its name (a prefix)
+/// or after it (a suffix).
+pub struct Affix {
+    pub name: &'static str,
+    pub slot: Option<EquipmentSlot>,
+    pub power: i32,
+    pub defense: i32,
+    pub life_steal: i32,
+    pub thorns: i32,
+}
+
+const fn affix(name: &'static str, slot: Option<EquipmentSlot>) -> Affix {
+    Affix {
+        name,
+        slot,
+        power: 0,
+        defense: 0,
+        life_steal: 0,
+        thorns: 0,
+    }
+}
+
+const PREFIXES: [Affix; 6] = [
+    Affix {
+        power: 1,
+        ..affix("Sharp", Some(EquipmentSlot::Melee))
+    },
+    Affix {
+        power: 2,
+        ..affix("Keen", Some(EquipmentSlot::Melee))
+    },
+    Affix {
+        defense: 1,
+        ..affix("Sturdy", Some(EquipmentSlot::Shield))
+    },
+    Affix {
+        defense: 2,
+        ..affix("Reinforced", Some(EquipmentSlot::Shield))
+    },
+    Affix {
+        power: 1,
+        defense: 1,
+        ..affix("Balanced", None)
+    },
+    Affix {
+        thorns: 1,
+        ..affix("Spiked", None)
+    },
+];
+
+const SUFFIXES: [Affix; 5] = [
+    Affix {
+        power: 1,
+        ..affix("of Might", None)
+    },
+    Affix {
+        defense: 1,
+        ..affix("of Warding", None)
+    },
+    Affix {
+        life_steal: 1,
+        ..affix("of the Leech", Some(EquipmentSlot::Melee))
+    },
+    Affix {
+        life_steal: 2,
+        ..affix("of Vampirism", Some(EquipmentSlot::Melee))
+    },
+    Affix {
+        thorns: 2,
+        ..affix("of Brambles", Some(EquipmentSlot::Shield))
+    },
+];
+
+/// Cursed items always carry one of these, on top of whatever else they rolled.
+const CURSES: [Affix; 2] = [
+    Affix {
+        power: -2,
+        ..affix("of Weakness", None)
+    },
+    Affix {
+        defense: -2,
+        ..affix("of Frailty", None)
+    },
+];
+
+/// Everything that was rolled for a single piece of equipment.
+pub struct MagicRoll {
+    pub rarity: Rarity,
+    pub name: String,
+    pub power: i32,
+    pub defense: i32,
+    pub life_steal: i32,
+    pub thorns: i32,
+    pub cursed: bool,
+}
+
+impl MagicRoll {
+    fn apply(&mut self, affix: &Affix) {
+        self.power += affix.power;
+        self.defense += affix.defense;
+        self.life_steal += affix.life_steal;
+        self.thorns += affix.thorns;
+    }
+}
+
+fn pick<'a>(
+    rng: &mut RandomNumberGenerator,
+    affixes: &'a [Affix],
+    slot: EquipmentSlot,
+) -> &'a Affix {
+    let possible: Vec<&Affix> = affixes
+        .iter()
+        .filter(|a| a.slot.is_none() || a.slot == Some(slot))
+        .collect();
+    possible[(rng.roll_dice(1, possible.len() as i32) - 1) as usize]
+}
+
+/// Rolls a rarity class for a new piece of equipment; deeper levels find better gear.
+pub fn roll_rarity(rng: &mut RandomNumberGenerator, depth: i32) -> Rarity {
+    let roll = rng.roll_dice(1, 100) + depth;
+    if roll > 95 {
+        Rarity::Rare
+    } else if roll > 70 {
+        Rarity::Magic
+    } else {
+        Rarity::Common
+    }
+}
+
+/// Decorates a base item with random affixes according to its rarity. Magic items get a prefix
+/// or a suffix, rare items get both; either can turn out to be cursed.
+pub fn roll_magic(
+    rng: &mut RandomNumberGenerator,
+    depth: i32,
+    base_name: &str,
+    slot: EquipmentSlot,
+    power: i32,
+    defense: i32,
+) -> MagicRoll {
+    let mut result = MagicRoll {
+        rarity: roll_rarity(rng, depth),
+        name: base_name.to_string(),
+        power,
+        defense,
+        life_steal: 0,
+        thorns: 0,
+        cursed: false,
+    };
+
+    let (prefix, suffix) = match result.rarity {
+        Rarity::Common => (None, None),
+        Rarity::Magic => {
+            if rng.roll_dice(1, 2) == 1 {
+                (Some(pick(rng, &PREFIXES, slot)), None)
+            } else {
+                (None, Some(pick(rng, &SUFFIXES, slot)))
+            }
+        }
+        Rarity::Rare => (
+            Some(pick(rng, &PREFIXES, slot)),
+            Some(pick(rng, &SUFFIXES, slot)),
+        ),
+    };
+
+    if let Some(prefix) = prefix {
+        result.apply(prefix);
+        result.name = format!("{} {}", prefix.name, result.name);
+    }
+
+    if result.rarity != Rarity::Common && rng.roll_dice(1, 8) == 1 {
+        // A curse takes the place of the suffix
+        let curse = pick(rng, &CURSES, slot);
+        result.apply(curse);
+        result.name = format!("{} {}", result.name, curse.name);
+        result.cursed = true;
+    } else if let Some(suffix) = suffix {
+        result.apply(suffix);
+        result.name = format!("{} {}", result.name, suffix.name);
+    }
+
+    result
+}
@@ -0,0 +1,156 @@
+use bevy_ecs::prelude::*;
+use bracket_color::prelude::{BLACK, MAGENTA, SKY_BLUE};
+use bracket_pathfinding::prelude::{a_star_search, DistanceAlg, Point};
+use bracket_random::prelude::RandomNumberGenerator;
+
+use crate::{
+    components::{
+        AsPoint, Confused, EntityMoved, HeardNoise, HungerClock, HungerState, Item, Monster, Name,
+        Perception, Player, Position, ProvidesFood, Stealth, Unaware, Viewshed, WantsToMelee,
+        WantsToUseItem,
+    },
+    cp437::to_cp437,
+    gamelog::GameLog,
+    map::Map,
+    particle::ParticleBuilder,
+    stealth::perception_check,
+    RunState,
+};
+
+pub fn monster_ai_system(
+    mut commands: Commands,
+    mut monsters: Query<
+        (
+            Entity,
+            &mut Viewshed,
+            &mut Position,
+            Option<&mut Confused>,
+            Option<&HungerClock>,
+            Option<&HeardNoise>,
+            &Name,
+            Option<&Perception>,
+            Option<&Unaware>,
+        ),
+        (With<Monster>, Without<Player>),
+    >,
+    players: Query<(Entity, &Position, Option<&Stealth>), With<Player>>,
+    food: Query<(Entity, &Position), (With<ProvidesFood>, With<Item>, Without<Monster>)>,
+    mut map: ResMut<Map>,
+    state: Res<RunState>,
+    mut particle: ResMut<ParticleBuilder>,
+    mut log: ResMut<GameLog>,
+    mut rng: NonSendMut<RandomNumberGenerator>,
+) {
+    if *state != RunState::MonsterTurn {
+        return;
+    }
+
+    let (player, player_pos, stealth) = players.single();
+    let player_point = player_pos.as_point();
+    let player_lit = map.is_lit(map.xy_idx(player_point.x, player_point.y));
+
+    for (monster, mut viewshed, mut pos, confused, hunger, noise, name, perception, unaware) in
+        monsters.iter_mut()
+    {
+        if let Some(unaware) = unaware {
+            // Sleeping monsters can't see anything, and nobody can see the player in the dark
+            let noticed = !unaware.asleep
+                && player_lit
+                && viewshed.visible_tiles.contains(&player_point)
+                && perception_check(&mut rng, perception, unaware, stealth, 0);
+            let idx = map.xy_idx(pos.x, pos.y);
+            let seen = map.visible_tiles[idx] && map.is_lit(idx);
+            if noticed {
+                commands.entity(monster).remove::<Unaware>();
+                if seen {
+                    log.entries.push(format!("{} notices you!", name.name));
+                }
+            } else {
+                if unaware.asleep && seen {
+                    particle.request(pos.x, pos.y, SKY_BLUE, BLACK, to_cp437('z'), 200.0);
+                }
+                continue;
+            }
+        }
+
+        let mut can_act = true;
+        if let Some(mut confused) = confused {
+            confused.turns -= 1;
+            if confused.turns < 1 {
+                // TODO warn player?
+                commands.entity(monster).remove::<Confused>();
+            }
+            can_act = false;
+
+            particle.request(pos.x, pos.y, MAGENTA, BLACK, to_cp437('?'), 200.0);
+        }
+
+        if can_act {
+            let distance =
+                DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), player_point);
+            if distance < 1.5 {
+                // Attack goes here
+                commands
+                    .entity(monster)
+                    .insert(WantsToMelee { target: player });
+            } else {
+                let mut goal = None;
+                if viewshed.visible_tiles.contains(&player_point) {
+                    goal = Some(player_point);
+                    // No need to go looking for what it can already see
+                    if noise.is_some() {
+                        commands.entity(monster).remove::<HeardNoise>();
+                    }
+                } else if let Some(noise) = noise {
+                    // Go and find out what that was
+                    let noise_point = Point::new(noise.x, noise.y);
+                    if noise_point == pos.as_point() {
+                        commands.entity(monster).remove::<HeardNoise>();
+                    } else {
+                        goal = Some(noise_point);
+                    }
+                } else if hunger
+                    .is_some_and(|h| matches!(h.state, HungerState::Hungry | HungerState::Starving))
+                {
+                    // Nobody to fight, so go looking for something to eat
+                    let here = pos.as_point();
+                    let meal = food
+                        .iter()
+                        .filter(|(_, food_pos)| {
+                            viewshed.visible_tiles.contains(&food_pos.as_point())
+                        })
+                        .min_by(|(_, a), (_, b)| {
+                            let da = DistanceAlg::Pythagoras.distance2d(here, a.as_point());
+                            let db = DistanceAlg::Pythagoras.distance2d(here, b.as_point());
+                            da.total_cmp(&db)
+                        });
+                    if let Some((item, food_pos)) = meal {
+                        if food_pos.as_point() == here {
+                            commands
+                                .entity(monster)
+                                .insert(WantsToUseItem { item, target: None });
+                        } else {
+                            goal = Some(food_pos.as_point());
+                        }
+                    }
+                }
+
+                if let Some(goal) = goal {
+                    let start_idx = map.xy_idx(pos.x, pos.y);
+
+                    let path = a_star_search(start_idx, map.xy_idx(goal.x, goal.y), &*map);
+                    if path.success && path.steps.len() > 1 {
+                        let next_idx = path.steps[1];
+                        pos.x = next_idx as i32 % map.width;
+                        pos.y = next_idx as i32 / map.width;
+                        commands.entity(monster).insert(EntityMoved {});
+                        viewshed.dirty = true;
+
+                        map.blocked[start_idx] = false;
+                        map.blocked[next_idx] = true;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use bevy_ecs::prelude::*;
+use bracket_color::prelude::{BLACK, ORANGE};
+
+use crate::{
+    components::{
+        CombatStats, DamageCause, DefenseBonus, Equipped, HungerClock, HungerState, LifeSteal,
+        MeleePowerBonus, Name, Position, Thorns, Unaware, WantsToMelee,
+    },
+    cp437::to_cp437,
+    damage::DamageEvent,
+    gamelog::GameLog,
+    noise::{NoiseEvent, COMBAT_NOISE},
+    particle::ParticleBuilder,
+    stealth::sneak_attack_multiplier,
+};
+
+pub fn melee_combat_system(
+    mut commands: Commands,
+    mut attackers: Query<(Entity, &WantsToMelee, &Name, Option<&HungerClock>)>,
+    mut combatants: Query<&mut CombatStats>,
+    power_bonuses: Query<(&MeleePowerBonus, &Equipped)>,
+    defense_bonuses: Query<(&DefenseBonus, &Equipped)>,
+    life_stealers: Query<(&LifeSteal, &Equipped)>,
+    thorns: Query<(&Thorns, &Equipped)>,
+    names: Query<&Name>,
+    positions: Query<&Position>,
+    unaware_targets: Query<&Unaware>,
+    mut log: ResMut<GameLog>,
+    mut particle: ResMut<ParticleBuilder>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut noise_writer: EventWriter<NoiseEvent>,
+) {
+    for (attacker, wants_melee, name, hunger) in attackers.iter_mut() {
+        let victim = wants_melee.target;
+        let stats = combatants.get(attacker).unwrap().clone();
+        let target_stats = combatants.get(victim).unwrap().clone();
+        if target_stats.hp > 0 {
+            let target_name = names.get(victim).unwrap();
+
+            let mut power = stats.power;
+            for (bonus, equipped) in power_bonuses.iter() {
+                if equipped.owner == attacker {
+                    power += bonus.power;
+                }
+            }
+
+            if let Some(hunger) = hunger {
+                if hunger.state == HungerState::WellFed {
+                    power += 1;
+                }
+            }
+
+            let mut defense = target_stats.defense;
+            for (bonus, equipped) in defense_bonuses.iter() {
+                if equipped.owner == victim {
+                    defense += bonus.defense;
+                }
+            }
+
+            let pos = positions.get(victim);
+            if let Ok(pos) = pos {
+                particle.request(pos.x, pos.y, ORANGE, BLACK, to_cp437('‼'), 200.0);
+                noise_writer.send(NoiseEvent {
+                    x: pos.x,
+                    y: pos.y,
+                    loudness: COMBAT_NOISE,
+                });
+            }
+
+            let mut damage = power - defense;
+            if let Ok(unaware) = unaware_targets.get(victim) {
+                // Nobody sees the first blow coming, least of all in their sleep
+                if damage > 0 {
+                    damage *= sneak_attack_multiplier(unaware);
+                    log.entries.push(format!(
+                        "{} catches {} unawares!",
+                        &name.name, &target_name.name
+                    ));
+                }
+                commands.entity(victim).remove::<Unaware>();
+            }
+
+            if damage <= 0 {
+                log.entries.push(format!(
+                    "{} is unable to hurt {}",
+                    &name.name, &target_name.name
+                ));
+            } else {
+                log.entries.push(format!(
+                    "{} hits {}, for {} hp.",
+                    &name.name, &target_name.name, damage
+                ));
+
+                damage_writer.send(DamageEvent {
+                    who: victim,
+                    value: damage,
+                    cause: DamageCause::new(Some(attacker), &name.name),
+                });
+
+                let stolen: i32 = life_stealers
+                    .iter()
+                    .filter(|(_, equipped)| equipped.owner == attacker)
+                    .map(|(steal, _)| steal.amount)
+                    .sum();
+                if stolen > 0 {
+                    let mut attacker_stats = combatants.get_mut(attacker).unwrap();
+                    attacker_stats.hp = i32::min(attacker_stats.max_hp, attacker_stats.hp + stolen);
+                    log.entries
+                        .push(format!("{} drains {} hp.", &name.name, stolen));
+                }
+
+                let spikes: i32 = thorns
+                    .iter()
+                    .filter(|(_, equipped)| equipped.owner == victim)
+                    .map(|(thorns, _)| thorns.damage)
+                    .sum();
+                if spikes > 0 {
+                    log.entries.push(format!(
+                        "{} is pricked by {}'s thorns, for {} hp.",
+                        &name.name, &target_name.name, spikes
+                    ));
+                    damage_writer.send(DamageEvent {
+                        who: attacker,
+                        value: spikes,
+                        cause: DamageCause::new(
+                            Some(victim),
+                            format!("{}'s thorns", &target_name.name),
+                        ),
+                    });
+                }
+            }
+        }
+
+        commands.entity(attacker).remove::<WantsToMelee>();
+    }
+}
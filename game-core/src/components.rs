@@ -1,6 +1,9 @@
 use bevy_ecs::prelude::*;
-use rltk::{FontCharType, Point};
+use bracket_geometry::prelude::Point;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::cp437::FontCharType;
 
 #[derive(Clone, Component, Default, Serialize, Deserialize)]
 pub struct Position {
@@ -8,7 +11,7 @@ pub struct Position {
     pub y: i32,
 }
 
-pub(crate) trait AsPoint {
+pub trait AsPoint {
     fn as_point(&self) -> Point;
 }
 
@@ -32,6 +35,13 @@ pub struct Renderable {
 #[derive(Debug, Clone, Component, Default, Serialize, Deserialize)]
 pub struct Player {}
 
+/// Casts light of the given colour, fading out to nothing at `range`.
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct LightSource {
+    pub color: (u8, u8, u8),
+    pub range: i32,
+}
+
 #[derive(Clone, Component, Default, Serialize, Deserialize)]
 pub struct Viewshed {
     pub visible_tiles: Vec<Point>,
@@ -58,14 +68,34 @@ pub struct CombatStats {
     pub power: i32,
 }
 
-#[derive(Component)]
+#[derive(Clone, Component, Serialize, Deserialize)]
 pub struct WantsToMelee {
     pub target: Entity,
 }
 
-#[derive(Component)]
+#[derive(Clone, Component, Serialize, Deserialize)]
 pub struct SufferDamage {
     pub amount: Vec<i32>,
+    /// Whatever dealt the most recent of those hits.
+    pub final_blow: DamageCause,
+}
+
+/// Who or what dealt some damage, so the right entity gets the credit (or the blame).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DamageCause {
+    /// The entity responsible, if anyone is.
+    pub source: Option<Entity>,
+    /// What to call it in the morgue file, such as `Orc` or `starvation`.
+    pub description: String,
+}
+
+impl DamageCause {
+    pub fn new<S: ToString>(source: Option<Entity>, description: S) -> DamageCause {
+        DamageCause {
+            source,
+            description: description.to_string(),
+        }
+    }
 }
 
 #[derive(Clone, Component, Default, Serialize, Deserialize)]
@@ -81,19 +111,19 @@ pub struct InBackpack {
     pub owner: Entity,
 }
 
-#[derive(Component)]
+#[derive(Clone, Component, Serialize, Deserialize)]
 pub struct WantsToPickupItem {
     pub collected_by: Entity,
     pub item: Entity,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct WantsToUseItem {
     pub item: Entity,
     pub target: Option<Point>,
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct WantsToDropItem {
     pub item: Entity,
 }
@@ -133,6 +163,7 @@ pub struct Waiting {}
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Ring,
 }
 
 #[derive(Clone, Component, Serialize, Deserialize)]
@@ -168,6 +199,7 @@ pub struct ParticleLifetime {
 
 #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
 pub enum HungerState {
+    Bloated,
     WellFed,
     Normal,
     Hungry,
@@ -177,15 +209,50 @@ pub enum HungerState {
 #[derive(Clone, Component, Serialize, Deserialize)]
 pub struct HungerClock {
     pub state: HungerState,
-    pub duration: i32,
+    pub nutrition: i32,
 }
 
 #[derive(Clone, Component, Serialize, Deserialize)]
-pub struct ProvidesFood {}
+pub struct ProvidesFood {
+    pub nutrition: i32,
+}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct Corpse {
+    pub turns_left: i32,
+}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct Poisoned {
+    pub turns: i32,
+}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct SlowsDigestion {
+    pub percent: i32,
+}
 
 #[derive(Clone, Component, Serialize, Deserialize)]
 pub struct MagicMapper {}
 
+/// Everything the morgue file wants to know about the player's run so far.
+#[derive(Clone, Component, Default, Serialize, Deserialize)]
+pub struct RunStats {
+    pub turns: i32,
+    /// Monsters the player has killed, by name.
+    pub kills: BTreeMap<String, i32>,
+    /// Items the player has used up, by name.
+    pub items_used: BTreeMap<String, i32>,
+    pub killed_by: Option<String>,
+    /// Hit points lost on each depth.
+    #[serde(default)]
+    pub damage_taken: BTreeMap<i32, i32>,
+}
+
+/// Picking this up ends the run in victory.
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct WinsGame {}
+
 #[derive(Clone, Component, Serialize, Deserialize)]
 pub struct Hidden {}
 
@@ -195,5 +262,63 @@ pub struct EntryTrigger {}
 #[derive(Clone, Component, Serialize, Deserialize)]
 pub struct EntityMoved {}
 
+/// Where a monster last heard something it wants to investigate.
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct HeardNoise {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Muffles the noise an entity makes as it moves about, and makes it harder to spot.
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct Stealth {
+    pub value: i32,
+}
+
+/// How good a monster is at noticing things.
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct Perception {
+    pub value: i32,
+}
+
+/// A monster that hasn't noticed the player yet. Sleeping ones are harder to rouse.
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct Unaware {
+    pub asleep: bool,
+}
+
 #[derive(Clone, Component, Serialize, Deserialize)]
 pub struct SingleActivation {}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct LootTable {
+    pub table: String,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+pub enum Rarity {
+    Common,
+    Magic,
+    Rare,
+}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct MagicItem {
+    pub rarity: Rarity,
+}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct Cursed {}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct RemovesCurse {}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct LifeSteal {
+    pub amount: i32,
+}
+
+#[derive(Clone, Component, Serialize, Deserialize)]
+pub struct Thorns {
+    pub damage: i32,
+}
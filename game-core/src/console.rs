@@ -0,0 +1,5 @@
+/// Notes for whoever is running the game rather than for the player, such as a save that
+/// couldn't be read. They go to the terminal the game was started from.
+pub fn log<S: ToString>(message: S) {
+    println!("{}", message.to_string());
+}
@@ -0,0 +1,30 @@
+/// A glyph's place in the font. The game's fonts are laid out as in code page 437.
+pub type FontCharType = u16;
+
+/// Every character in code page 437, from 1 to 254. 0 and 255 have no character of their own.
+const CP437: &str = concat!(
+    "☺☻♥♦♣♠•◘○◙♂♀♪♫☼",
+    "►◄↕‼¶§▬↨↑↓→←∟↔▲▼",
+    " !\"#$%&'()*+,-./",
+    "0123456789:;<=>?",
+    "@ABCDEFGHIJKLMNO",
+    "PQRSTUVWXYZ[\\]^_",
+    "`abcdefghijklmno",
+    "pqrstuvwxyz{|}~⌂",
+    "ÇüéâäàåçêëèïîìÄÅ",
+    "ÉæÆôöòûùÿÖÜ¢£¥₧ƒ",
+    "áíóúñÑªº¿⌐¬½¼¡«»",
+    "░▒▓│┤╡╢╖╕╣║╗╝╜╛┐",
+    "└┴┬├─┼╞╟╚╔╩╦╠═╬╧",
+    "╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀",
+    "αßΓπΣσµτΦΘΩδ∞φε∩",
+    "≡±≥≤⌠⌡÷≈°∙·√ⁿ²■",
+);
+
+/// The code page 437 glyph for a character, or 0 if it doesn't have one.
+pub fn to_cp437(c: char) -> FontCharType {
+    CP437
+        .chars()
+        .position(|glyph| glyph == c)
+        .map_or(0, |i| i as FontCharType + 1)
+}
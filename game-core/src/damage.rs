@@ -0,0 +1,117 @@
+use bevy_ecs::prelude::*;
+use bracket_random::prelude::RandomNumberGenerator;
+
+use crate::{
+    components::{
+        CombatStats, DamageCause, LootTable, Name, Player, Position, RunStats, SufferDamage,
+    },
+    gamelog::GameLog,
+    loot::LOOT_TABLES,
+    map::Map,
+    spawner, RunState,
+};
+
+#[derive(Event)]
+pub struct DamageEvent {
+    pub who: Entity,
+    pub value: i32,
+    pub cause: DamageCause,
+}
+
+pub fn damage_event_reader(
+    mut commands: Commands,
+    mut reader: EventReader<DamageEvent>,
+    mut sufferers: Query<&mut SufferDamage>,
+) {
+    // Newly hurt entities don't get their SufferDamage until the commands are applied, so a
+    // second hit in the same frame has to be added to the one waiting to go in. They go in in
+    // the order they were hit, so that the same run always plays out the same way
+    let mut newly_hurt: Vec<(Entity, SufferDamage)> = Vec::new();
+    for event in reader.read() {
+        if let Ok(mut suffer) = sufferers.get_mut(event.who) {
+            suffer.amount.push(event.value);
+            suffer.final_blow = event.cause.clone();
+        } else if let Some((_, suffer)) = newly_hurt.iter_mut().find(|(who, _)| *who == event.who) {
+            suffer.amount.push(event.value);
+            suffer.final_blow = event.cause.clone();
+        } else {
+            newly_hurt.push((
+                event.who,
+                SufferDamage {
+                    amount: vec![event.value],
+                    final_blow: event.cause.clone(),
+                },
+            ));
+        }
+    }
+
+    for (victim, suffer) in newly_hurt {
+        commands.entity(victim).insert(suffer);
+    }
+}
+
+pub fn damage_system(
+    mut commands: Commands,
+    mut victims: Query<(
+        Entity,
+        &mut CombatStats,
+        &Name,
+        &SufferDamage,
+        &Position,
+        Option<&Player>,
+        Option<&LootTable>,
+    )>,
+    mut run_stats: Query<(Entity, &mut RunStats), With<Player>>,
+    mut log: ResMut<GameLog>,
+    mut state: ResMut<RunState>,
+    mut map: ResMut<Map>,
+    mut rng: NonSendMut<RandomNumberGenerator>,
+) {
+    for (victim, mut stats, name, damage, pos, player, loot) in victims.iter_mut() {
+        let amount = damage.amount.iter().sum::<i32>();
+        stats.hp -= amount;
+        if let Ok((_, mut run)) = run_stats.get_mut(victim) {
+            *run.damage_taken.entry(map.depth).or_insert(0) += amount;
+        }
+
+        let idx = map.xy_idx(pos.x, pos.y);
+        map.bloodstains.insert(idx);
+
+        if stats.hp < 1 {
+            match player {
+                None => {
+                    commands.entity(victim).despawn();
+                    log.entries.push(format!("{} dies horribly!", &name.name));
+
+                    for (player, mut stats) in run_stats.iter_mut() {
+                        if damage.final_blow.source == Some(player) {
+                            *stats.kills.entry(name.name.clone()).or_insert(0) += 1;
+                        }
+                    }
+
+                    let corpse_name = name.name.clone();
+                    let (x, y) = (pos.x, pos.y);
+                    commands.add(move |world: &mut World| {
+                        spawner::corpse(world, x, y, &corpse_name);
+                    });
+
+                    if let Some(loot) = loot {
+                        if let Some(drop) = LOOT_TABLES.roll(&loot.table, map.depth, &mut rng) {
+                            commands.add(move |world: &mut World| {
+                                spawner::spawn_entity(world, &(&idx, &drop));
+                            });
+                        }
+                    }
+                }
+                Some(_) => {
+                    if let Ok((_, mut stats)) = run_stats.get_mut(victim) {
+                        stats.killed_by = Some(damage.final_blow.description.clone());
+                    }
+                    *state = RunState::GameOver;
+                }
+            }
+        }
+
+        commands.entity(victim).remove::<SufferDamage>();
+    }
+}
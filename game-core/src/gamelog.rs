@@ -0,0 +1,7 @@
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct GameLog {
+    pub entries: Vec<String>,
+}
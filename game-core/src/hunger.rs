@@ -0,0 +1,151 @@
+use bevy_ecs::prelude::*;
+
+use crate::{
+    components::{
+        Corpse, DamageCause, Equipped, HungerClock, HungerState, Name, Player, Poisoned,
+        SlowsDigestion,
+    },
+    damage::DamageEvent,
+    gamelog::GameLog,
+    RunState,
+};
+
+/// Nutrition burned per turn before any equipment modifiers.
+const BASE_BURN: i32 = 10;
+
+/// Nobody can stuff themselves beyond this much nutrition.
+pub const SATIATION_CAP: i32 = 5000;
+
+/// Corpses turn rotten (and more likely to poison whoever eats them) at this many turns left.
+pub const CORPSE_ROTTEN_AT: i32 = 60;
+
+impl HungerState {
+    pub fn from_nutrition(nutrition: i32) -> HungerState {
+        match nutrition {
+            n if n > 4500 => HungerState::Bloated,
+            n if n > 4000 => HungerState::WellFed,
+            n if n > 2000 => HungerState::Normal,
+            n if n > 0 => HungerState::Hungry,
+            _ => HungerState::Starving,
+        }
+    }
+}
+
+impl HungerClock {
+    pub fn new(nutrition: i32) -> HungerClock {
+        HungerClock {
+            state: HungerState::from_nutrition(nutrition),
+            nutrition,
+        }
+    }
+
+    /// Adds nutrition (capped at `SATIATION_CAP`) and updates the state to match.
+    pub fn feed(&mut self, nutrition: i32) {
+        self.nutrition = i32::min(SATIATION_CAP, self.nutrition + nutrition);
+        self.state = HungerState::from_nutrition(self.nutrition);
+    }
+}
+
+pub fn hunger_system(
+    mut hungry: Query<(
+        Entity,
+        &mut HungerClock,
+        Option<&mut Poisoned>,
+        Option<&Player>,
+    )>,
+    digestion: Query<(&SlowsDigestion, &Equipped)>,
+    state: Res<RunState>,
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    for (entity, mut clock, poison, player) in hungry.iter_mut() {
+        let proceed = match *state {
+            RunState::PlayerTurn => player.is_some(),
+            RunState::MonsterTurn => player.is_none(),
+            _ => false,
+        };
+
+        if !proceed {
+            continue;
+        }
+
+        let slowed: i32 = digestion
+            .iter()
+            .filter(|(_, equipped)| equipped.owner == entity)
+            .map(|(slow, _)| slow.percent)
+            .sum();
+        let burn = i32::max(1, BASE_BURN * (100 - i32::min(slowed, 90)) / 100);
+        // Monsters only eat what they happen to see, so they bottom out at hungry rather than
+        // starving to death somewhere out of sight
+        let floor = if player.is_some() { 0 } else { 1 };
+        clock.nutrition = i32::max(floor, clock.nutrition - burn);
+
+        let new_state = HungerState::from_nutrition(clock.nutrition);
+        if new_state != clock.state && player.is_some() {
+            match new_state {
+                HungerState::Bloated => {}
+                HungerState::WellFed => log.entries.push("You no longer feel bloated.".to_string()),
+                HungerState::Normal => log.entries.push("You are no longer well fed.".to_string()),
+                HungerState::Hungry => log.entries.push("You are hungry.".to_string()),
+                HungerState::Starving => log.entries.push("You are starving!".to_string()),
+            }
+        }
+        clock.state = new_state;
+
+        if clock.state == HungerState::Starving {
+            // Inflict damage from hunger
+            if player.is_some() {
+                log.entries.push(
+                    "Your hunger pangs are getting painful! You suffer 1 hp damage.".to_string(),
+                )
+            }
+            damage_writer.send(DamageEvent {
+                who: entity,
+                value: 1,
+                cause: DamageCause::new(None, "starvation"),
+            });
+        }
+
+        if let Some(mut poison) = poison {
+            if player.is_some() {
+                log.entries
+                    .push("You feel sick to your stomach. You suffer 1 hp damage.".to_string());
+            }
+            damage_writer.send(DamageEvent {
+                who: entity,
+                value: 1,
+                cause: DamageCause::new(None, "poison"),
+            });
+
+            poison.turns -= 1;
+            if poison.turns < 1 {
+                commands.entity(entity).remove::<Poisoned>();
+                if player.is_some() {
+                    log.entries.push("You feel better.".to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Corpses go off over time, and eventually rot away entirely.
+pub fn corpse_rot_system(
+    mut commands: Commands,
+    mut corpses: Query<(Entity, &mut Corpse, &mut Name)>,
+    state: Res<RunState>,
+) {
+    if *state != RunState::MonsterTurn {
+        return;
+    }
+
+    for (entity, mut corpse, mut name) in corpses.iter_mut() {
+        corpse.turns_left -= 1;
+        if corpse.turns_left == CORPSE_ROTTEN_AT {
+            name.name = format!("rotten {}", name.name);
+        }
+        if corpse.turns_left < 1 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
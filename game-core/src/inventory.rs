@@ -1,16 +1,21 @@
 use bevy_ecs::prelude::*;
-use rltk::{to_cp437, BLACK, GREEN, MAGENTA, ORANGE, RED};
+use bracket_color::prelude::{BLACK, GREEN, MAGENTA, ORANGE, RED};
+use bracket_pathfinding::prelude::field_of_view;
+use bracket_random::prelude::RandomNumberGenerator;
 
 use crate::{
     components::{
-        AreaOfEffect, CombatStats, Confused, Confusion, Consumable, Equippable, Equipped,
-        HungerClock, HungerState, InBackpack, InflictsDamage, Item, MagicMapper, Name, Player,
-        Position, ProvidesFood, ProvidesHealing, WantsToDropItem, WantsToPickupItem,
-        WantsToRemoveItem, WantsToUseItem,
+        AreaOfEffect, CombatStats, Confused, Confusion, Consumable, Corpse, Cursed, DamageCause,
+        Equippable, Equipped, HungerClock, HungerState, InBackpack, InflictsDamage, Item,
+        MagicMapper, Name, Player, Poisoned, Position, ProvidesFood, ProvidesHealing, RemovesCurse,
+        RunStats, WantsToDropItem, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem, WinsGame,
     },
+    cp437::to_cp437,
     damage::DamageEvent,
     gamelog::GameLog,
+    hunger::CORPSE_ROTTEN_AT,
     map::Map,
+    noise::{NoiseEvent, EXPLOSION_NOISE},
     particle::ParticleBuilder,
     RunState,
 };
@@ -18,8 +23,9 @@ use crate::{
 pub fn item_collection_system(
     mut commands: Commands,
     wants_pickup: Query<(Entity, &WantsToPickupItem, Option<&Player>)>,
-    items: Query<&Name, With<Item>>,
+    items: Query<(&Name, Option<&WinsGame>), With<Item>>,
     mut log: ResMut<GameLog>,
+    mut state: ResMut<RunState>,
 ) {
     for (entity, pickup, player) in wants_pickup.iter() {
         commands.entity(pickup.item).remove::<Position>();
@@ -28,10 +34,14 @@ pub fn item_collection_system(
         });
 
         if player.is_some() {
-            let entity_name = items.get(pickup.item).unwrap();
+            let (entity_name, wins_game) = items.get(pickup.item).unwrap();
 
             log.entries
                 .push(format!("You pick up the {}.", entity_name.name));
+
+            if wins_game.is_some() {
+                *state = RunState::Victory;
+            }
         }
 
         commands.entity(entity).remove::<WantsToPickupItem>();
@@ -45,6 +55,7 @@ pub fn item_use_system(
         &WantsToUseItem,
         Option<&mut HungerClock>,
         Option<&Player>,
+        Option<&mut RunStats>,
     )>,
     mut combatants: Query<(&mut CombatStats, Option<&Position>)>,
     mut mobs: Query<(&Name, Option<&Position>)>,
@@ -57,18 +68,33 @@ pub fn item_use_system(
         Option<&ProvidesFood>,
         Option<&MagicMapper>,
         Option<&AreaOfEffect>,
+        Option<&RemovesCurse>,
+        Option<&Corpse>,
     )>,
     equippables: Query<(&Name, &Equippable)>,
-    equipped_items: Query<(Entity, &Equipped, &Name)>,
+    equipped_items: Query<(Entity, &Equipped, &Name, Option<&Cursed>)>,
+    cursed_items: Query<(Entity, Option<&Equipped>, Option<&InBackpack>), With<Cursed>>,
     mut log: ResMut<GameLog>,
     map: Res<Map>,
     mut particle: ResMut<ParticleBuilder>,
     mut state: ResMut<RunState>,
     mut damage_writer: EventWriter<DamageEvent>,
+    mut noise_writer: EventWriter<NoiseEvent>,
+    mut rng: NonSendMut<RandomNumberGenerator>,
 ) {
-    for (user, use_item, hunger, player) in users.iter_mut() {
-        if let Ok((item_name, consumable, healing, inflict, confusion, edible, mapping, aoe)) =
-            consumables.get(use_item.item)
+    for (user, use_item, hunger, player, mut run_stats) in users.iter_mut() {
+        if let Ok((
+            item_name,
+            consumable,
+            healing,
+            inflict,
+            confusion,
+            edible,
+            mapping,
+            aoe,
+            remove_curse,
+            corpse,
+        )) = consumables.get(use_item.item)
         {
             // Targeting
             let mut targets: Vec<Entity> = Vec::new();
@@ -90,7 +116,7 @@ pub fn item_use_system(
                         }
                         Some(aoe) => {
                             // AoE
-                            let mut blast_tiles = rltk::field_of_view(target, aoe.radius, &*map);
+                            let mut blast_tiles = field_of_view(target, aoe.radius, &*map);
                             blast_tiles.retain(|p| {
                                 p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1
                             });
@@ -112,6 +138,13 @@ pub fn item_use_system(
                                     200.0,
                                 );
                             }
+                            if inflict.is_some() {
+                                noise_writer.send(NoiseEvent {
+                                    x: target.x,
+                                    y: target.y,
+                                    loudness: EXPLOSION_NOISE,
+                                });
+                            }
                         }
                     }
                 }
@@ -123,13 +156,15 @@ pub fn item_use_system(
                 for target in targets.iter() {
                     if let Ok((mut stats, pos)) = combatants.get_mut(*target) {
                         // TODO don't use if full hp!
-                        stats.hp = i32::min(stats.max_hp, stats.hp + healing.heal_amount);
+                        let healed =
+                            i32::min(stats.max_hp, stats.hp + healing.heal_amount) - stats.hp;
+                        stats.hp += healed;
                         used_up = true;
 
                         if player.is_some() {
                             log.entries.push(format!(
                                 "You drink the {}, healing {} hp.",
-                                item_name.name, healing.heal_amount
+                                item_name.name, healed
                             ));
                         }
 
@@ -148,6 +183,7 @@ pub fn item_use_system(
                         damage_writer.send(DamageEvent {
                             who: *target,
                             value: inflict.damage,
+                            cause: DamageCause::new(Some(user), &item_name.name),
                         });
 
                         if player.is_some() {
@@ -187,15 +223,38 @@ pub fn item_use_system(
                 }
             }
 
-            if edible.is_some() {
+            if let Some(food) = edible {
                 if let Some(mut hunger) = hunger {
-                    used_up = true;
+                    if hunger.state == HungerState::Bloated {
+                        if player.is_some() {
+                            log.entries
+                                .push(format!("You are too full to eat the {}.", item_name.name));
+                        }
+                    } else {
+                        used_up = true;
+                        hunger.feed(food.nutrition);
 
-                    hunger.state = HungerState::WellFed;
-                    hunger.duration = 20;
+                        if player.is_some() {
+                            log.entries.push(format!("You eat the {}.", item_name.name));
+                            if hunger.state == HungerState::Bloated {
+                                log.entries.push("You feel bloated.".to_string());
+                            }
+                        }
 
-                    if player.is_some() {
-                        log.entries.push(format!("You eat the {}.", item_name.name));
+                        // Old meat is a gamble
+                        if let Some(corpse) = corpse {
+                            let chance = if corpse.turns_left > CORPSE_ROTTEN_AT {
+                                10
+                            } else {
+                                50
+                            };
+                            if rng.roll_dice(1, 100) <= chance {
+                                commands.entity(user).insert(Poisoned { turns: 5 });
+                                if player.is_some() {
+                                    log.entries.push("Ugh, that tasted foul!".to_string());
+                                }
+                            }
+                        }
                     }
                 }
             }
@@ -206,17 +265,54 @@ pub fn item_use_system(
                 *state = RunState::MagicMapReveal { row: 0 };
             }
 
-            if used_up && consumable.is_some() {
-                commands.entity(use_item.item).despawn();
+            if remove_curse.is_some() {
+                used_up = true;
+                for (cursed, equipped, backpack) in cursed_items.iter() {
+                    let owned = equipped.is_some_and(|e| e.owner == user)
+                        || backpack.is_some_and(|b| b.owner == user);
+                    if owned {
+                        commands.entity(cursed).remove::<Cursed>();
+                    }
+                }
+                if player.is_some() {
+                    log.entries
+                        .push("You feel a malevolent presence lift.".to_string());
+                }
+            }
+
+            if used_up {
+                if let Some(stats) = run_stats.as_mut() {
+                    *stats.items_used.entry(item_name.name.clone()).or_insert(0) += 1;
+                }
+                if consumable.is_some() {
+                    commands.entity(use_item.item).despawn();
+                }
             }
         }
 
         if let Ok((item_name, can_equip)) = equippables.get(use_item.item) {
             let target_slot = can_equip.slot;
 
+            // A cursed item in the same slot won't budge
+            let stuck = equipped_items
+                .iter()
+                .find(|(_, already_equipped, _, cursed)| {
+                    already_equipped.owner == user
+                        && already_equipped.slot == target_slot
+                        && cursed.is_some()
+                });
+            if let Some((_, _, name, _)) = stuck {
+                if player.is_some() {
+                    log.entries
+                        .push(format!("You cannot remove the cursed {}!", name.name));
+                }
+                commands.entity(user).remove::<WantsToUseItem>();
+                continue;
+            }
+
             // Remove any items in the same slot
             let mut to_unequip: Vec<Entity> = Vec::new();
-            for (item_entity, already_equipped, name) in equipped_items.iter() {
+            for (item_entity, already_equipped, name, _) in equipped_items.iter() {
                 if already_equipped.owner == user && already_equipped.slot == target_slot {
                     to_unequip.push(item_entity);
                     if player.is_some() {
@@ -273,9 +369,21 @@ pub fn item_remove_system(
     mut commands: Commands,
     removers: Query<(Entity, &WantsToRemoveItem, Option<&Player>)>,
     names: Query<&Name>,
+    cursed: Query<&Cursed>,
     mut log: ResMut<GameLog>,
 ) {
     for (entity, intent, player) in removers.iter() {
+        if cursed.contains(intent.item) {
+            if player.is_some() {
+                log.entries.push(format!(
+                    "You cannot remove the cursed {}!",
+                    names.get(intent.item).unwrap().name,
+                ));
+            }
+            commands.entity(entity).remove::<WantsToRemoveItem>();
+            continue;
+        }
+
         commands
             .entity(intent.item)
             .remove::<Equipped>()
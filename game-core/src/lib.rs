@@ -0,0 +1,347 @@
+//! The game without a window: its components, maps, systems and saves. Drawing it and reading
+//! the keyboard are up to whichever frontend is running it.
+
+pub mod actions;
+mod affixes;
+pub mod ai;
+pub mod combat;
+pub mod components;
+pub mod console;
+pub mod cp437;
+pub mod damage;
+pub mod gamelog;
+pub mod hunger;
+pub mod inventory;
+pub mod lighting;
+pub mod loot;
+pub mod map;
+pub mod map_builders;
+pub mod morgue;
+pub mod noise;
+pub mod particle;
+pub mod player;
+pub mod progression;
+mod random_table;
+mod rect;
+pub mod rex_assets;
+pub mod save_format;
+mod save_registry;
+pub mod saveload;
+pub mod simulate;
+pub mod spawner;
+mod stealth;
+pub mod theme;
+pub mod triggers;
+pub mod visibility;
+
+use actions::{PendingAction, PlayerAction};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::RunSystemOnce;
+use bracket_random::prelude::RandomNumberGenerator;
+use components::{CombatStats, Equipped, InBackpack, Player, Position, Viewshed};
+use damage::DamageEvent;
+use gamelog::GameLog;
+use map::Map;
+use noise::NoiseEvent;
+use progression::PROGRESSION;
+use rex_assets::RexAssets;
+use serde::{Deserialize, Serialize};
+
+/// Whether map builders keep a snapshot of each step, so the building can be watched.
+pub const SHOW_MAPGEN_VISUALIZER: bool = true;
+
+/// A world, the schedule that runs it, and everything needed to move it from one level to the
+/// next.
+pub struct Game {
+    pub world: World,
+    pub schedule: Schedule,
+    /// The current level as it was being built, one step at a time, for anyone who wants to
+    /// watch.
+    pub mapgen_history: Vec<Map>,
+}
+
+impl Game {
+    /// A world ready for a run to start in, and the schedule that runs it.
+    pub fn new() -> Game {
+        let mut world = World::new();
+
+        let seed = RandomNumberGenerator::new().next_u64();
+        world.insert_resource(Seed(seed));
+        world.insert_non_send_resource(RandomNumberGenerator::seeded(seed));
+
+        world.insert_resource(RunState::MainMenu {
+            menu_selection: MainMenuSelection::NewGame,
+        });
+        world.insert_resource(particle::ParticleBuilder::new());
+        world.insert_resource(RexAssets::new());
+        world.insert_resource(PendingAction::default());
+
+        world.insert_resource(Events::<DamageEvent>::default());
+        world.insert_resource(Events::<NoiseEvent>::default());
+
+        let mut game = Game {
+            world,
+            schedule: Schedule::default(),
+            mapgen_history: Vec::new(),
+        };
+
+        game.schedule.add_systems(
+            (
+                (
+                    inventory::item_use_system,
+                    inventory::item_drop_system,
+                    inventory::item_remove_system,
+                    hunger::hunger_system,
+                    hunger::corpse_rot_system,
+                    player::turn_counter_system,
+                    player::player_action_system,
+                    inventory::item_collection_system,
+                    visibility::visibility_system,
+                    player::waiting_system,
+                    ai::monster_ai_system,
+                )
+                    .chain(),
+                (
+                    noise::footstep_system,
+                    triggers::trigger_system,
+                    combat::melee_combat_system,
+                    damage::damage_event_reader,
+                    damage::damage_system,
+                    noise::noise_system,
+                    map::map_indexing_system,
+                    lighting::lighting_system,
+                    particle::cull_dead_particles_system,
+                    particle::spawn_particles_system,
+                )
+                    .chain(),
+            )
+                .chain(),
+        );
+
+        game
+    }
+
+    fn entities_to_remove_on_level_change(&mut self) -> Vec<Entity> {
+        let mut entities = self.world.query::<Entity>();
+        let player_entity = self
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .single(&self.world);
+        let mut backpack = self.world.query::<&InBackpack>();
+        let mut equipped = self.world.query::<&Equipped>();
+
+        let mut to_delete: Vec<Entity> = Vec::new();
+        for entity in entities.iter(&self.world) {
+            let mut should_delete = true;
+
+            // Don't delete the player
+            if entity == player_entity {
+                should_delete = false;
+            }
+
+            // Don't delete the player's equipment
+            let bp = backpack.get(&self.world, entity);
+            if let Ok(bp) = bp {
+                if bp.owner == player_entity {
+                    should_delete = false;
+                }
+            }
+
+            let eq = equipped.get(&self.world, entity);
+            if let Ok(eq) = eq {
+                if eq.owner == player_entity {
+                    should_delete = false;
+                }
+            }
+
+            if should_delete {
+                to_delete.push(entity);
+            }
+        }
+
+        to_delete
+    }
+
+    /// Sets up a new run. The same seed always gives the same run, given the same inputs.
+    pub fn start_run(&mut self, seed: u64) {
+        self.world.insert_resource(Seed(seed));
+        self.world
+            .insert_non_send_resource(RandomNumberGenerator::seeded(seed));
+        self.world.insert_resource(GameLog {
+            entries: vec!["Welcome to Rusty Roguelike".to_string()],
+        });
+        self.init_game();
+    }
+
+    fn init_game(&mut self) {
+        let start = self.create_map(0);
+        spawner::player(&mut self.world, start.x, start.y);
+    }
+
+    fn create_map(&mut self, new_depth: i32) -> Position {
+        self.mapgen_history.clear();
+
+        // Each level gets a generator of its own, seeded from the run's, so that the same seed
+        // always builds the same levels
+        let level_seed = self
+            .world
+            .non_send_resource_mut::<RandomNumberGenerator>()
+            .next_u64();
+        let mut rng = RandomNumberGenerator::seeded(level_seed);
+        let mut builder = map_builders::level_builder(new_depth, &mut rng);
+        builder.build_map(&mut rng);
+        self.mapgen_history = builder.get_snapshot_history();
+        self.world.insert_resource(builder.get_map());
+
+        builder.spawn_entities(&mut self.world);
+
+        builder.get_starting_position()
+    }
+
+    pub fn goto_next_level(&mut self) {
+        // Delete entities that aren't the player or his/her equipment
+        let to_delete = self.entities_to_remove_on_level_change();
+        for target in to_delete {
+            self.world.despawn(target);
+        }
+
+        // Build a new map and place the player
+        let old_map = self.world.resource::<Map>();
+        let current_depth = old_map.depth;
+        let start = self.create_map(current_depth + 1);
+
+        // Find the player
+        let (mut player_position, mut player_viewshed, mut player_stats) = self
+            .world
+            .query_filtered::<(&mut Position, &mut Viewshed, &mut CombatStats), With<Player>>()
+            .single_mut(&mut self.world);
+
+        // Place the player and update resources
+        player_position.x = start.x;
+        player_position.y = start.y;
+
+        // Mark the player's visibility as dirty
+        player_viewshed.dirty = true;
+
+        // Let them recover a bit
+        player_stats.hp = i32::max(player_stats.hp, player_stats.max_hp / 2);
+
+        // Notify the player
+        let mut log = self.world.resource_mut::<GameLog>();
+        log.entries
+            .push("You descend to the next level, and take a moment to heal.".to_string());
+        if let Some(message) = PROGRESSION
+            .fixed_level(current_depth + 1)
+            .and_then(|level| level.message.as_ref())
+        {
+            log.entries.push(message.clone());
+        }
+
+        // Replays and simulated games have no slot of their own, and mustn't touch anyone else's
+        if !self.world.contains_resource::<saveload::SaveSlot>() {
+            return;
+        }
+        if let Err(e) = saveload::save_game(&mut self.world, saveload::SaveKind::Autosave) {
+            self.world
+                .resource_mut::<GameLog>()
+                .entries
+                .push(format!("Unable to autosave: {}", e));
+        }
+    }
+
+    /// Carries out an action the player chose from one of the menus, straight away.
+    pub fn perform(&mut self, action: PlayerAction) {
+        self.world.resource_mut::<PendingAction>().0 = Some(action);
+        self.world.run_system_once(player::player_action_system);
+    }
+
+    pub fn game_over_cleanup(&mut self) {
+        self.world.clear_entities();
+        self.world.remove_resource::<morgue::RunSummary>();
+    }
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game::new()
+    }
+}
+
+/// How long the last frame took, in milliseconds.
+pub type FrameTime = f32;
+
+/// The seed the game's random number generator was started from.
+#[derive(Copy, Clone, Resource, Serialize, Deserialize)]
+pub struct Seed(pub u64);
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum MainMenuSelection {
+    NewGame,
+    LoadGame,
+    HighScores,
+    Quit,
+}
+
+/// Why the player is picking a save slot.
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum SlotPurpose {
+    NewGame,
+    LoadGame,
+}
+
+#[derive(PartialEq, Copy, Clone, Resource, Serialize, Deserialize)]
+pub enum RunState {
+    AwaitingInput,
+    PlayerTurn,
+    MonsterTurn,
+    ShowInventory,
+    ShowDropItem,
+    ShowTargeting {
+        range: i32,
+        item: Entity,
+    },
+    MainMenu {
+        menu_selection: MainMenuSelection,
+    },
+    SaveSlots {
+        purpose: SlotPurpose,
+        selection: usize,
+    },
+    SaveGame,
+    NextLevel,
+    ShowRemoveItem,
+    ShowHelp,
+    GameOver,
+    Victory,
+    HighScores,
+    MagicMapReveal {
+        row: i32,
+    },
+    MapGeneration,
+}
+
+impl RunState {
+    /// Where the game goes after a frame in this state, unless something moves it on first.
+    pub fn next(self) -> RunState {
+        match self {
+            RunState::PlayerTurn => RunState::MonsterTurn,
+            RunState::ShowInventory => self,
+            RunState::ShowDropItem => self,
+            RunState::ShowTargeting { .. } => self,
+            RunState::MainMenu { .. } => self,
+            RunState::SaveSlots { .. } => self,
+            RunState::SaveGame => RunState::MainMenu {
+                menu_selection: MainMenuSelection::LoadGame,
+            },
+            RunState::ShowRemoveItem => self,
+            RunState::ShowHelp => self,
+            RunState::GameOver => self,
+            RunState::Victory => self,
+            RunState::HighScores => self,
+            RunState::MapGeneration => self,
+            RunState::NextLevel => RunState::MapGeneration,
+            RunState::MagicMapReveal { row } => RunState::MagicMapReveal { row: row + 1 },
+            _ => RunState::AwaitingInput,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use bevy_ecs::prelude::*;
+use bracket_color::prelude::RGB;
+use bracket_pathfinding::prelude::{field_of_view, DistanceAlg};
+
+use crate::{
+    components::{AsPoint, LightSource, Position},
+    map::Map,
+    progression::PROGRESSION,
+};
+
+/// Works out how much light falls on every tile: the level's ambient light, plus whatever the
+/// light sources can reach, fading out with distance.
+pub fn lighting_system(lights: Query<(&Position, &LightSource)>, mut map: ResMut<Map>) {
+    let ambient = PROGRESSION.ambient_light(map.depth);
+    let mut light = vec![RGB::from_f32(ambient, ambient, ambient); map.tiles.len()];
+
+    for (pos, source) in lights.iter() {
+        let color = RGB::named(source.color);
+        let origin = pos.as_point();
+        for tile in field_of_view(origin, source.range, &*map) {
+            if !map.contains_point(tile) {
+                continue;
+            }
+            let distance = DistanceAlg::Pythagoras.distance2d(origin, tile);
+            let intensity = 1.0 - distance / source.range as f32;
+            if intensity > 0.0 {
+                let idx = map.xy_idx(tile.x, tile.y);
+                light[idx] = light[idx] + color * intensity;
+            }
+        }
+    }
+
+    for tile in light.iter_mut() {
+        *tile = RGB::from_f32(tile.r.min(1.0), tile.g.min(1.0), tile.b.min(1.0));
+    }
+    map.light = light;
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use bracket_random::prelude::RandomNumberGenerator;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::{
+    random_table::{RandomRoll, RandomTable},
+    spawner,
+};
+
+const LOOT_TABLES_JSON: &str = include_str!("../../resources/loot_tables.json");
+
+/// How many nested table references we follow before giving up; guards against cycles.
+const MAX_NESTING: i32 = 8;
+
+lazy_static! {
+    pub static ref LOOT_TABLES: LootTables =
+        LootTables::from_json(LOOT_TABLES_JSON).expect("Invalid loot tables");
+}
+
+#[derive(Deserialize)]
+struct LootTableFile {
+    tables: Vec<LootTableDef>,
+}
+
+#[derive(Deserialize)]
+struct LootTableDef {
+    name: String,
+    entries: Vec<LootEntryDef>,
+}
+
+#[derive(Deserialize)]
+struct LootEntryDef {
+    item: Option<String>,
+    table: Option<String>,
+    #[serde(default)]
+    nothing: bool,
+    weight: i32,
+    #[serde(default)]
+    per_depth: i32,
+}
+
+/// Named, data-driven tables of things that can be found lying around or dropped by monsters.
+/// Entries may name an item, refer to another table, or explicitly roll nothing; weights scale
+/// with depth.
+pub struct LootTables {
+    tables: HashMap<String, Vec<LootEntryDef>>,
+}
+
+impl LootTables {
+    pub fn from_json(json: &str) -> Result<LootTables, String> {
+        let file: LootTableFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut tables = HashMap::new();
+        for table in file.tables {
+            if tables.contains_key(&table.name) {
+                return Err(format!("Loot table {} is defined twice", table.name));
+            }
+            tables.insert(table.name, table.entries);
+        }
+
+        for (name, entries) in tables.iter() {
+            for entry in entries.iter() {
+                let kinds = entry.item.is_some() as i32
+                    + entry.table.is_some() as i32
+                    + entry.nothing as i32;
+                if kinds != 1 {
+                    return Err(format!(
+                        "Loot table {} has an entry that isn't exactly one of item, table or nothing",
+                        name
+                    ));
+                }
+                if let Some(item) = &entry.item {
+                    if !spawner::is_spawnable(item) {
+                        return Err(format!("Loot table {} has unknown item {}", name, item));
+                    }
+                }
+                if let Some(table) = &entry.table {
+                    if !tables.contains_key(table) {
+                        return Err(format!(
+                            "Loot table {} refers to unknown table {}",
+                            name, table
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(LootTables { tables })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tables.contains_key(name)
+    }
+
+    /// Builds the weighted table for the given depth; unknown names produce an empty table.
+    pub fn table(&self, name: &str, depth: i32) -> RandomTable {
+        let mut table = RandomTable::new();
+        if let Some(entries) = self.tables.get(name) {
+            for entry in entries.iter() {
+                let weight = entry.weight + entry.per_depth * depth;
+                table = if let Some(item) = &entry.item {
+                    table.add(item, weight)
+                } else if let Some(nested) = &entry.table {
+                    table.add_table(nested, weight)
+                } else {
+                    table.add_nothing(weight)
+                };
+            }
+        }
+        table
+    }
+
+    /// Rolls the named table, following nested tables until we land on something to spawn.
+    pub fn roll(&self, name: &str, depth: i32, rng: &mut RandomNumberGenerator) -> Option<String> {
+        self.resolve(RandomRoll::Table(name.to_string()), depth, rng)
+    }
+
+    /// Turns the result of rolling any `RandomTable` into the name of something to spawn.
+    pub fn resolve(
+        &self,
+        roll: RandomRoll,
+        depth: i32,
+        rng: &mut RandomNumberGenerator,
+    ) -> Option<String> {
+        let mut roll = roll;
+        for _ in 0..MAX_NESTING {
+            match roll {
+                RandomRoll::Spawn(name) => return Some(name),
+                RandomRoll::Nothing => return None,
+                RandomRoll::Table(name) => roll = self.table(&name, depth).roll(rng),
+            }
+        }
+
+        crate::console::log("Loot tables nest too deeply, giving up");
+        None
+    }
+}
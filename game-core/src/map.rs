@@ -1,15 +1,22 @@
 use bevy_ecs::prelude::*;
-use rltk::{to_cp437, Algorithm2D, BaseMap, DistanceAlg, Point, Rltk, SmallVec, RGB};
+use bracket_color::prelude::RGB;
+use bracket_pathfinding::prelude::{Algorithm2D, BaseMap, DistanceAlg, Point, SmallVec};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-use crate::components::{BlocksTile, Position};
+use crate::{
+    components::{BlocksTile, Position},
+    theme::Theme,
+};
 
 #[derive(PartialEq, Eq, Hash, Copy, Serialize, Deserialize, Clone)]
 pub enum TileType {
     Wall,
     Floor,
     DownStairs,
+    Road,
+    Grass,
+    WoodFloor,
 }
 
 #[derive(Resource, Default, Serialize, Deserialize, Clone)]
@@ -22,16 +29,28 @@ pub struct Map {
     pub blocked: Vec<bool>,
     pub depth: i32,
     pub bloodstains: HashSet<usize>,
+    #[serde(default)]
+    pub theme: Theme,
 
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
     pub tile_content: Vec<Vec<Entity>>,
+
+    /// How much light falls on each tile, worked out afresh by the lighting system. Until it has
+    /// run (or for maps still being built) this is empty, and everything counts as fully lit.
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    pub light: Vec<RGB>,
 }
 
 pub const MAPWIDTH: usize = 80;
 pub const MAPHEIGHT: usize = 43;
 pub const MAPCOUNT: usize = MAPHEIGHT * MAPWIDTH;
 
+/// How bright the brightest channel of a tile's light has to be before anything standing there
+/// can be made out.
+const LIT_THRESHOLD: f32 = 0.2;
+
 impl Map {
     pub fn xy_idx(&self, x: i32, y: i32) -> usize {
         (y as usize * self.width as usize) + x as usize
@@ -41,6 +60,19 @@ impl Map {
         p.x >= 0 && p.x < self.width && p.y >= 0 && p.y < self.height
     }
 
+    pub fn light_at(&self, idx: usize) -> RGB {
+        self.light
+            .get(idx)
+            .copied()
+            .unwrap_or_else(|| RGB::from_f32(1.0, 1.0, 1.0))
+    }
+
+    /// Whether there's enough light on a tile to see what's on it, rather than just the tile.
+    pub fn is_lit(&self, idx: usize) -> bool {
+        let light = self.light_at(idx);
+        light.r.max(light.g).max(light.b) >= LIT_THRESHOLD
+    }
+
     pub fn populate_blocked(&mut self) {
         for (i, tile) in self.tiles.iter_mut().enumerate() {
             self.blocked[i] = *tile == TileType::Wall;
@@ -61,90 +93,6 @@ impl Map {
         !self.blocked[idx]
     }
 
-    fn is_revealed_and_wall(&self, x: i32, y: i32) -> bool {
-        let idx = self.xy_idx(x, y);
-        self.tiles[idx] == TileType::Wall && self.revealed_tiles[idx]
-    }
-
-    fn wall_glyph(&self, x: i32, y: i32) -> rltk::FontCharType {
-        if x < 1 || x > self.width - 2 || y < 1 || y > self.height - 2 as i32 {
-            return 35;
-        }
-        let mut mask: u8 = 0;
-
-        if self.is_revealed_and_wall(x, y - 1) {
-            mask += 1;
-        }
-        if self.is_revealed_and_wall(x, y + 1) {
-            mask += 2;
-        }
-        if self.is_revealed_and_wall(x - 1, y) {
-            mask += 4;
-        }
-        if self.is_revealed_and_wall(x + 1, y) {
-            mask += 8;
-        }
-
-        match mask {
-            0 => 9,    // Pillar because we can't see neighbors
-            1 => 186,  // Wall only to the north
-            2 => 186,  // Wall only to the south
-            3 => 186,  // Wall to the north and south
-            4 => 205,  // Wall only to the west
-            5 => 188,  // Wall to the north and west
-            6 => 187,  // Wall to the south and west
-            7 => 185,  // Wall to the north, south and west
-            8 => 205,  // Wall only to the east
-            9 => 200,  // Wall to the north and east
-            10 => 201, // Wall to the south and east
-            11 => 204, // Wall to the north, south and east
-            12 => 205, // Wall to the east and west
-            13 => 202, // Wall to the east, west, and south
-            14 => 203, // Wall to the east, west, and north
-            15 => 206, // ╬ Wall on all sides
-            _ => 35,   // We missed one?
-        }
-    }
-
-    pub fn draw(&self, ctx: &mut Rltk) {
-        let mut y = 0;
-        let mut x = 0;
-        for (idx, tile) in self.tiles.iter().enumerate() {
-            if self.revealed_tiles[idx] {
-                let glyph;
-                let mut fg;
-                let mut bg = RGB::from_f32(0., 0., 0.);
-                match tile {
-                    TileType::Floor => {
-                        glyph = to_cp437('.');
-                        fg = RGB::from_f32(0.0, 0.5, 0.5);
-                    }
-                    TileType::Wall => {
-                        glyph = self.wall_glyph(x, y);
-                        fg = RGB::from_f32(0., 1.0, 0.);
-                    }
-                    TileType::DownStairs => {
-                        glyph = to_cp437('>');
-                        fg = RGB::from_f32(0., 1.0, 1.0);
-                    }
-                }
-                if self.bloodstains.contains(&idx) {
-                    bg = RGB::from_f32(0.75, 0., 0.);
-                }
-                if !self.visible_tiles[idx] {
-                    fg = fg.to_greyscale()
-                }
-                ctx.set(x, y, fg, bg, glyph);
-            }
-            // Move the coordinates
-            x += 1;
-            if x >= self.width {
-                x = 0;
-                y += 1;
-            }
-        }
-    }
-
     /// Generates an empty map, consisting entirely of solid walls
     pub fn new(new_depth: i32) -> Map {
         Map {
@@ -157,6 +105,8 @@ impl Map {
             tile_content: vec![Vec::new(); MAPCOUNT],
             depth: new_depth,
             bloodstains: HashSet::new(),
+            theme: Theme::for_depth(new_depth),
+            light: Vec::new(),
         }
     }
 }
@@ -2,7 +2,7 @@ use super::{
     apply_room_to_map, draw_corridor, spawner, Map, MapBuilder, Position, Rect, TileType,
     SHOW_MAPGEN_VISUALIZER,
 };
-use rltk::RandomNumberGenerator;
+use bracket_random::prelude::RandomNumberGenerator;
 
 pub struct BspDungeonBuilder {
     map: Map,
@@ -27,8 +27,8 @@ impl MapBuilder for BspDungeonBuilder {
         self.history.clone()
     }
 
-    fn build_map(&mut self) {
-        self.build();
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
     }
 
     fn get_spawn_list(&self) -> &Vec<(usize, String)> {
@@ -59,9 +59,7 @@ impl BspDungeonBuilder {
         }
     }
 
-    fn build(&mut self) {
-        let mut rng = RandomNumberGenerator::new();
-
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
         self.rects.clear();
         self.rects
             .push(Rect::new(2, 2, self.map.width - 5, self.map.height - 5)); // Start with a single map-sized rectangle
@@ -72,8 +70,8 @@ impl BspDungeonBuilder {
         // room in there, we place it and add it to the rooms list.
         let mut n_rooms = 0;
         while n_rooms < 240 {
-            let rect = self.get_random_rect(&mut rng);
-            let candidate = self.get_random_sub_rect(rect, &mut rng);
+            let rect = self.get_random_rect(rng);
+            let candidate = self.get_random_sub_rect(rect, rng);
 
             if self.is_possible(candidate) {
                 apply_room_to_map(&mut self.map, &candidate);
@@ -86,7 +84,7 @@ impl BspDungeonBuilder {
         }
 
         // Now we sort the rooms
-        self.rooms.sort_by(|a, b| a.x1.cmp(&b.x1));
+        self.rooms.sort_by_key(|a| a.x1);
 
         // Now we want corridors
         for i in 0..self.rooms.len() - 1 {
@@ -117,7 +115,7 @@ impl BspDungeonBuilder {
 
         // Spawn some entities
         for room in self.rooms.iter().skip(1) {
-            spawner::spawn_room(&self.map, &mut rng, room, self.depth, &mut self.spawn_list);
+            spawner::spawn_room(&self.map, rng, room, self.depth, &mut self.spawn_list);
         }
     }
 
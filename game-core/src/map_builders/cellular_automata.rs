@@ -2,15 +2,15 @@ use super::{
     generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map,
     MapBuilder, Position, TileType, SHOW_MAPGEN_VISUALIZER,
 };
-use rltk::RandomNumberGenerator;
-use std::collections::HashMap;
+use bracket_random::prelude::RandomNumberGenerator;
+use std::collections::BTreeMap;
 
 pub struct CellularAutomataBuilder {
     map: Map,
     starting_position: Position,
     depth: i32,
     history: Vec<Map>,
-    noise_areas: HashMap<i32, Vec<usize>>,
+    noise_areas: BTreeMap<i32, Vec<usize>>,
     spawn_list: Vec<(usize, String)>,
 }
 
@@ -27,8 +27,8 @@ impl MapBuilder for CellularAutomataBuilder {
         self.history.clone()
     }
 
-    fn build_map(&mut self) {
-        self.build();
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
     }
 
     fn get_spawn_list(&self) -> &Vec<(usize, String)> {
@@ -53,14 +53,12 @@ impl CellularAutomataBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             spawn_list: Vec::new(),
         }
     }
 
-    fn build(&mut self) {
-        let mut rng = RandomNumberGenerator::new();
-
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
         // First we completely randomize the map, setting 55% of it to be floor.
         for y in 1..self.map.height - 1 {
             for x in 1..self.map.width - 1 {
@@ -145,11 +143,11 @@ impl CellularAutomataBuilder {
         self.take_snapshot();
 
         // Now we build a noise map for use in spawning entities later
-        self.noise_areas = generate_voronoi_spawn_regions(&self.map, &mut rng);
+        self.noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
 
         // Spawn the entities
         for area in self.noise_areas.iter() {
-            spawner::spawn_region(&mut rng, area.1, self.depth, &mut self.spawn_list);
+            spawner::spawn_region(rng, area.1, self.depth, &mut self.spawn_list);
         }
     }
 }
@@ -1,6 +1,6 @@
 use super::{Map, Rect, TileType};
 use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[allow(dead_code)]
 #[derive(PartialEq, Copy, Clone)]
@@ -26,7 +26,7 @@ pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) {
     for x in min(x1, x2)..=max(x1, x2) {
         let idx = map.xy_idx(x, y);
         if idx > 0 && idx < map.width as usize * map.height as usize {
-            map.tiles[idx as usize] = TileType::Floor;
+            map.tiles[idx] = TileType::Floor;
         }
     }
 }
@@ -35,7 +35,7 @@ pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
     for y in min(y1, y2)..=max(y1, y2) {
         let idx = map.xy_idx(x, y);
         if idx > 0 && idx < map.width as usize * map.height as usize {
-            map.tiles[idx as usize] = TileType::Floor;
+            map.tiles[idx] = TileType::Floor;
         }
     }
 }
@@ -44,7 +44,7 @@ pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
 pub fn remove_unreachable_areas_returning_most_distant(map: &mut Map, start_idx: usize) -> usize {
     map.populate_blocked();
     let map_starts: Vec<usize> = vec![start_idx];
-    let dijkstra_map = rltk::DijkstraMap::new(
+    let dijkstra_map = bracket_pathfinding::prelude::DijkstraMap::new(
         map.width as usize,
         map.height as usize,
         &map_starts,
@@ -56,7 +56,7 @@ pub fn remove_unreachable_areas_returning_most_distant(map: &mut Map, start_idx:
         if *tile == TileType::Floor {
             let distance_to_start = dijkstra_map.map[i];
             // We can't get to this tile - so we'll make it a wall
-            if distance_to_start == std::f32::MAX {
+            if distance_to_start == f32::MAX {
                 *tile = TileType::Wall;
             } else {
                 // If it is further away than our current exit candidate, move the exit
@@ -74,13 +74,15 @@ pub fn remove_unreachable_areas_returning_most_distant(map: &mut Map, start_idx:
 /// Generates a Voronoi/cellular noise map of a region, and divides it into spawn regions.
 pub fn generate_voronoi_spawn_regions(
     map: &Map,
-    rng: &mut rltk::RandomNumberGenerator,
-) -> HashMap<i32, Vec<usize>> {
-    let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
-    let mut noise = rltk::FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
-    noise.set_noise_type(rltk::NoiseType::Cellular);
+    rng: &mut bracket_random::prelude::RandomNumberGenerator,
+) -> BTreeMap<i32, Vec<usize>> {
+    let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+    let mut noise = bracket_noise::prelude::FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
+    noise.set_noise_type(bracket_noise::prelude::NoiseType::Cellular);
     noise.set_frequency(0.08);
-    noise.set_cellular_distance_function(rltk::CellularDistanceFunction::Manhattan);
+    noise.set_cellular_distance_function(
+        bracket_noise::prelude::CellularDistanceFunction::Manhattan,
+    );
 
     for y in 1..map.height - 1 {
         for x in 1..map.width - 1 {
@@ -89,11 +91,7 @@ pub fn generate_voronoi_spawn_regions(
                 let cell_value_f = noise.get_noise(x as f32, y as f32) * 10240.0;
                 let cell_value = cell_value_f as i32;
 
-                if noise_areas.contains_key(&cell_value) {
-                    noise_areas.get_mut(&cell_value).unwrap().push(idx);
-                } else {
-                    noise_areas.insert(cell_value, vec![idx]);
-                }
+                noise_areas.entry(cell_value).or_default().push(idx);
             }
         }
     }
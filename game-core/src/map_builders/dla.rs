@@ -2,8 +2,8 @@ use super::{
     generate_voronoi_spawn_regions, paint, remove_unreachable_areas_returning_most_distant,
     spawner, Map, MapBuilder, Position, Symmetry, TileType, SHOW_MAPGEN_VISUALIZER,
 };
-use rltk::RandomNumberGenerator;
-use std::collections::HashMap;
+use bracket_random::prelude::RandomNumberGenerator;
+use std::collections::BTreeMap;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum DLAAlgorithm {
@@ -17,7 +17,7 @@ pub struct DLABuilder {
     starting_position: Position,
     depth: i32,
     history: Vec<Map>,
-    noise_areas: HashMap<i32, Vec<usize>>,
+    noise_areas: BTreeMap<i32, Vec<usize>>,
     algorithm: DLAAlgorithm,
     brush_size: i32,
     symmetry: Symmetry,
@@ -38,8 +38,8 @@ impl MapBuilder for DLABuilder {
         self.history.clone()
     }
 
-    fn build_map(&mut self) {
-        self.build();
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
     }
 
     fn get_spawn_list(&self) -> &Vec<(usize, String)> {
@@ -64,7 +64,7 @@ impl DLABuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             algorithm: DLAAlgorithm::WalkInwards,
             brush_size: 1,
             symmetry: Symmetry::None,
@@ -79,7 +79,7 @@ impl DLABuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             algorithm: DLAAlgorithm::WalkOutwards,
             brush_size: 2,
             symmetry: Symmetry::None,
@@ -94,7 +94,7 @@ impl DLABuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             algorithm: DLAAlgorithm::CentralAttractor,
             brush_size: 2,
             symmetry: Symmetry::None,
@@ -109,7 +109,7 @@ impl DLABuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             algorithm: DLAAlgorithm::CentralAttractor,
             brush_size: 2,
             symmetry: Symmetry::Horizontal,
@@ -118,9 +118,7 @@ impl DLABuilder {
         }
     }
 
-    fn build(&mut self) {
-        let mut rng = RandomNumberGenerator::new();
-
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
         // Carve a starting seed
         self.starting_position = Position {
             x: self.map.width / 2,
@@ -236,10 +234,13 @@ impl DLABuilder {
                     let mut prev_y = digger_y;
                     let mut digger_idx = self.map.xy_idx(digger_x, digger_y);
 
-                    let mut path = rltk::line2d(
-                        rltk::LineAlg::Bresenham,
-                        rltk::Point::new(digger_x, digger_y),
-                        rltk::Point::new(self.starting_position.x, self.starting_position.y),
+                    let mut path = bracket_geometry::prelude::line2d(
+                        bracket_geometry::prelude::LineAlg::Bresenham,
+                        bracket_geometry::prelude::Point::new(digger_x, digger_y),
+                        bracket_geometry::prelude::Point::new(
+                            self.starting_position.x,
+                            self.starting_position.y,
+                        ),
                     );
 
                     while self.map.tiles[digger_idx] == TileType::Wall && !path.is_empty() {
@@ -279,11 +280,11 @@ impl DLABuilder {
         self.take_snapshot();
 
         // Now we build a noise map for use in spawning entities later
-        self.noise_areas = generate_voronoi_spawn_regions(&self.map, &mut rng);
+        self.noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
 
         // Spawn the entities
         for area in self.noise_areas.iter() {
-            spawner::spawn_region(&mut rng, area.1, self.depth, &mut self.spawn_list);
+            spawner::spawn_region(rng, area.1, self.depth, &mut self.spawn_list);
         }
     }
 }
@@ -2,8 +2,8 @@ use super::{
     generate_voronoi_spawn_regions, paint, remove_unreachable_areas_returning_most_distant,
     spawner, Map, MapBuilder, Position, Symmetry, TileType, SHOW_MAPGEN_VISUALIZER,
 };
-use rltk::RandomNumberGenerator;
-use std::collections::HashMap;
+use bracket_random::prelude::RandomNumberGenerator;
+use std::collections::BTreeMap;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum DrunkSpawnMode {
@@ -24,7 +24,7 @@ pub struct DrunkardsWalkBuilder {
     starting_position: Position,
     depth: i32,
     history: Vec<Map>,
-    noise_areas: HashMap<i32, Vec<usize>>,
+    noise_areas: BTreeMap<i32, Vec<usize>>,
     settings: DrunkardSettings,
     spawn_list: Vec<(usize, String)>,
 }
@@ -42,8 +42,8 @@ impl MapBuilder for DrunkardsWalkBuilder {
         self.history.clone()
     }
 
-    fn build_map(&mut self) {
-        self.build();
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
     }
 
     fn get_spawn_list(&self) -> &Vec<(usize, String)> {
@@ -68,7 +68,7 @@ impl DrunkardsWalkBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             settings: DrunkardSettings {
                 spawn_mode: DrunkSpawnMode::StartingPoint,
                 drunken_lifetime: 400,
@@ -86,7 +86,7 @@ impl DrunkardsWalkBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             settings: DrunkardSettings {
                 spawn_mode: DrunkSpawnMode::Random,
                 drunken_lifetime: 400,
@@ -104,7 +104,7 @@ impl DrunkardsWalkBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             settings: DrunkardSettings {
                 spawn_mode: DrunkSpawnMode::Random,
                 drunken_lifetime: 100,
@@ -122,7 +122,7 @@ impl DrunkardsWalkBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             settings: DrunkardSettings {
                 spawn_mode: DrunkSpawnMode::Random,
                 drunken_lifetime: 100,
@@ -140,7 +140,7 @@ impl DrunkardsWalkBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             settings: DrunkardSettings {
                 spawn_mode: DrunkSpawnMode::Random,
                 drunken_lifetime: 100,
@@ -152,9 +152,7 @@ impl DrunkardsWalkBuilder {
         }
     }
 
-    fn build(&mut self) {
-        let mut rng = RandomNumberGenerator::new();
-
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
         // Set a central starting point
         self.starting_position = Position {
             x: self.map.width / 2,
@@ -254,7 +252,7 @@ impl DrunkardsWalkBuilder {
                 .filter(|a| **a == TileType::Floor)
                 .count();
         }
-        rltk::console::log(format!(
+        crate::console::log(format!(
             "{} dwarves gave up their sobriety, of whom {} actually found a wall.",
             digger_count, active_digger_count
         ));
@@ -268,11 +266,11 @@ impl DrunkardsWalkBuilder {
         self.take_snapshot();
 
         // Now we build a noise map for use in spawning entities later
-        self.noise_areas = generate_voronoi_spawn_regions(&self.map, &mut rng);
+        self.noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
 
         // Spawn the entities
         for (_, area) in self.noise_areas.iter() {
-            spawner::spawn_region(&mut rng, area, self.depth, &mut self.spawn_list);
+            spawner::spawn_region(rng, area, self.depth, &mut self.spawn_list);
         }
     }
 }
@@ -2,15 +2,15 @@ use super::{
     generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map,
     MapBuilder, Position, TileType, SHOW_MAPGEN_VISUALIZER,
 };
-use rltk::RandomNumberGenerator;
-use std::collections::HashMap;
+use bracket_random::prelude::RandomNumberGenerator;
+use std::collections::BTreeMap;
 
 pub struct MazeBuilder {
     map: Map,
     starting_position: Position,
     depth: i32,
     history: Vec<Map>,
-    noise_areas: HashMap<i32, Vec<usize>>,
+    noise_areas: BTreeMap<i32, Vec<usize>>,
     spawn_list: Vec<(usize, String)>,
 }
 
@@ -27,8 +27,8 @@ impl MapBuilder for MazeBuilder {
         self.history.clone()
     }
 
-    fn build_map(&mut self) {
-        self.build();
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
     }
 
     fn get_spawn_list(&self) -> &Vec<(usize, String)> {
@@ -53,20 +53,14 @@ impl MazeBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             spawn_list: Vec::new(),
         }
     }
 
-    fn build(&mut self) {
-        let mut rng = RandomNumberGenerator::new();
-
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
         // Maze gen
-        let mut maze = Grid::new(
-            (self.map.width / 2) - 2,
-            (self.map.height / 2) - 2,
-            &mut rng,
-        );
+        let mut maze = Grid::new((self.map.width / 2) - 2, (self.map.height / 2) - 2, rng);
         maze.generate_maze(self);
 
         // Find a starting point; start at the middle and walk left until we find an open tile
@@ -85,11 +79,11 @@ impl MazeBuilder {
         self.take_snapshot();
 
         // Now we build a noise map for use in spawning entities later
-        self.noise_areas = generate_voronoi_spawn_regions(&self.map, &mut rng);
+        self.noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
 
         // Spawn the entities
         for (_, area) in self.noise_areas.iter() {
-            spawner::spawn_region(&mut rng, area, self.depth, &mut self.spawn_list);
+            spawner::spawn_region(rng, area, self.depth, &mut self.spawn_list);
         }
     }
 }
@@ -149,7 +143,7 @@ struct Grid<'a> {
 }
 
 impl<'a> Grid<'a> {
-    fn new(width: i32, height: i32, rng: &mut RandomNumberGenerator) -> Grid {
+    fn new(width: i32, height: i32, rng: &mut RandomNumberGenerator) -> Grid<'_> {
         let mut grid = Grid {
             width,
             height,
@@ -0,0 +1,240 @@
+mod bsp_dungeon;
+mod bsp_interior;
+mod cellular_automata;
+mod common;
+mod dla;
+mod drunkard;
+mod maze;
+mod prefab_builder;
+mod simple_map;
+mod town;
+mod voronoi;
+mod waveform_collapse;
+
+use super::{
+    components::Position,
+    map::{Map, TileType},
+    progression::{FixedLayout, PROGRESSION},
+    rect::Rect,
+    spawner,
+    theme::Theme,
+    SHOW_MAPGEN_VISUALIZER,
+};
+use bevy_ecs::prelude::*;
+use bracket_random::prelude::RandomNumberGenerator;
+use bsp_dungeon::BspDungeonBuilder;
+use bsp_interior::BspInteriorBuilder;
+use cellular_automata::CellularAutomataBuilder;
+use common::*;
+use dla::DLABuilder;
+use drunkard::DrunkardsWalkBuilder;
+use maze::MazeBuilder;
+use prefab_builder::{
+    prefab_library::{PrefabKind, PREFABS},
+    PrefabBuilder,
+};
+use simple_map::SimpleMapBuilder;
+use town::TownBuilder;
+use voronoi::VoronoiCellBuilder;
+use waveform_collapse::{PinnedConstraint, WaveformCollapseBuilder, WfcMode};
+
+pub trait MapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator);
+    fn get_map(&self) -> Map;
+    fn get_starting_position(&self) -> Position;
+    fn get_snapshot_history(&self) -> Vec<Map>;
+    fn take_snapshot(&mut self);
+    fn get_spawn_list(&self) -> &Vec<(usize, String)>;
+
+    fn spawn_entities(&mut self, ecs: &mut World) {
+        for (spawn_idx, spawn_name) in self.get_spawn_list().iter() {
+            spawner::spawn_entity(ecs, &(spawn_idx, spawn_name));
+        }
+    }
+}
+
+/// Picks the builder for a given depth: the town sits on top of the dungeon, the progression
+/// has some levels built by hand, and everything else is randomly generated.
+pub fn level_builder(new_depth: i32, rng: &mut RandomNumberGenerator) -> Box<dyn MapBuilder> {
+    if new_depth == 0 {
+        return Box::new(TownBuilder::new(new_depth));
+    }
+
+    match PROGRESSION
+        .fixed_level(new_depth)
+        .map(|level| &level.layout)
+    {
+        Some(FixedLayout::Prefab(name)) => {
+            let level = PREFABS
+                .get(name)
+                .expect("Fixed level prefabs are checked when the progression loads");
+            Box::new(PrefabBuilder::constant(new_depth, level))
+        }
+        Some(FixedLayout::Rex(path)) => Box::new(PrefabBuilder::rex_level(new_depth, path)),
+        None => random_builder(new_depth, rng),
+    }
+}
+
+pub fn random_builder(new_depth: i32, rng: &mut RandomNumberGenerator) -> Box<dyn MapBuilder> {
+    let theme = Theme::for_depth(new_depth);
+
+    // The progression can narrow things down further than the theme does
+    let band_builders: Vec<&str> = PROGRESSION
+        .band(new_depth)
+        .map(|band| band.builders.iter().map(|b| b.as_str()).collect())
+        .unwrap_or_default();
+    let builders = if band_builders.is_empty() {
+        theme_builders(theme)
+    } else {
+        &band_builders
+    };
+    let builder = builders[(rng.roll_dice(1, builders.len() as i32) - 1) as usize];
+
+    let mut result = base_builder(builder, new_depth, theme, rng);
+
+    if rng.roll_dice(1, 3) == 1 {
+        result = if rng.roll_dice(1, 2) == 1 {
+            Box::new(WaveformCollapseBuilder::derived_map(new_depth, result))
+        } else {
+            Box::new(WaveformCollapseBuilder::overlapping(new_depth, result))
+        };
+    }
+
+    if rng.roll_dice(1, 20) == 1 {
+        if let Some(section) = PREFABS.pick(PrefabKind::Section, new_depth, theme, rng) {
+            result = Box::new(PrefabBuilder::sectional(new_depth, section, result));
+        }
+    }
+
+    result = Box::new(PrefabBuilder::vaults(new_depth, result));
+
+    result
+}
+
+/// Whether the prefab library has a whole level prefab by this name.
+pub fn is_level_prefab(name: &str) -> bool {
+    PREFABS
+        .get(name)
+        .is_some_and(|prefab| prefab.kind == PrefabKind::Level)
+}
+
+/// Every builder that can start off a random level, by the name used in `base_builder` and the
+/// progression file.
+pub const BUILDER_NAMES: &[&str] = &[
+    "bsp dungeon",
+    "bsp interior",
+    "cellular automata",
+    "open area",
+    "open halls",
+    "winding passages",
+    "fat passages",
+    "fearful symmetry",
+    "maze",
+    "walk inwards",
+    "walk outwards",
+    "central attractor",
+    "insectoid",
+    "voronoi pythagoras",
+    "voronoi manhattan",
+    "prefab level",
+    "wfc caves sample",
+    "wfc populated sample",
+    "simple map",
+];
+
+/// The base builders whose layouts suit each theme.
+fn theme_builders(theme: Theme) -> &'static [&'static str] {
+    match theme {
+        Theme::Dungeon => BUILDER_NAMES,
+        // Organic, twisting caves
+        Theme::FungalCave => &[
+            "cellular automata",
+            "open area",
+            "winding passages",
+            "walk inwards",
+            "walk outwards",
+            "central attractor",
+            "insectoid",
+            "wfc caves sample",
+        ],
+        // Tight corridors and burial chambers
+        Theme::Crypt => &[
+            "bsp dungeon",
+            "bsp interior",
+            "maze",
+            "voronoi manhattan",
+            "prefab level",
+            "simple map",
+        ],
+        // Broken-up, eroded spaces
+        Theme::FloodedRuins => &[
+            "open halls",
+            "fat passages",
+            "fearful symmetry",
+            "voronoi pythagoras",
+            "voronoi manhattan",
+            "wfc populated sample",
+        ],
+        // Built by someone who knew what they were doing
+        Theme::DwarvenFort => &[
+            "bsp dungeon",
+            "bsp interior",
+            "voronoi manhattan",
+            "prefab level",
+            "wfc populated sample",
+            "simple map",
+        ],
+    }
+}
+
+fn base_builder(
+    builder: &str,
+    new_depth: i32,
+    theme: Theme,
+    rng: &mut RandomNumberGenerator,
+) -> Box<dyn MapBuilder> {
+    match builder {
+        "bsp dungeon" => Box::new(BspDungeonBuilder::new(new_depth)),
+        "bsp interior" => Box::new(BspInteriorBuilder::new(new_depth)),
+        "cellular automata" => Box::new(CellularAutomataBuilder::new(new_depth)),
+        "open area" => Box::new(DrunkardsWalkBuilder::open_area(new_depth)),
+        "open halls" => Box::new(DrunkardsWalkBuilder::open_halls(new_depth)),
+        "winding passages" => Box::new(DrunkardsWalkBuilder::winding_passages(new_depth)),
+        "fat passages" => Box::new(DrunkardsWalkBuilder::fat_passages(new_depth)),
+        "fearful symmetry" => Box::new(DrunkardsWalkBuilder::fearful_symmetry(new_depth)),
+        "maze" => Box::new(MazeBuilder::new(new_depth)),
+        "walk inwards" => Box::new(DLABuilder::walk_inwards(new_depth)),
+        "walk outwards" => Box::new(DLABuilder::walk_outwards(new_depth)),
+        "central attractor" => Box::new(DLABuilder::central_attractor(new_depth)),
+        "insectoid" => Box::new(DLABuilder::insectoid(new_depth)),
+        "voronoi pythagoras" => Box::new(VoronoiCellBuilder::pythagoras(new_depth)),
+        "voronoi manhattan" => Box::new(VoronoiCellBuilder::manhattan(new_depth)),
+        "prefab level" => match PREFABS.pick(PrefabKind::Level, new_depth, theme, rng) {
+            Some(level) => Box::new(PrefabBuilder::constant(new_depth, level)),
+            None => Box::new(SimpleMapBuilder::new(new_depth)),
+        },
+        "wfc caves sample" => Box::new(WaveformCollapseBuilder::from_sample(
+            new_depth,
+            "../resources/wfc-demo1.xp",
+            WfcMode::Overlapping,
+            vec![
+                PinnedConstraint::WallBorder,
+                PinnedConstraint::FixedTile {
+                    x: 40,
+                    y: 21,
+                    tile: TileType::Floor,
+                },
+            ],
+        )),
+        "wfc populated sample" => Box::new(WaveformCollapseBuilder::from_sample(
+            new_depth,
+            "../resources/wfc-populated.xp",
+            WfcMode::Tiled,
+            vec![
+                PinnedConstraint::WallBorder,
+                PinnedConstraint::StairsIn(Rect::new(60, 30, 18, 11)),
+            ],
+        )),
+        _ => Box::new(SimpleMapBuilder::new(new_depth)),
+    }
+}
@@ -0,0 +1,173 @@
+use super::TileType;
+use crate::{loot::LOOT_TABLES, spawner};
+use bracket_random::prelude::RandomNumberGenerator;
+use std::collections::HashMap;
+
+/// Something a glyph places on its tile when the prefab is stamped onto the map.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum GlyphSpawn {
+    /// Anything the spawner knows by name.
+    Entity(String),
+    /// A roll on one of the loot tables, which may come up empty.
+    Table(String),
+}
+
+impl GlyphSpawn {
+    /// Decides what actually gets spawned.
+    pub fn resolve(&self, depth: i32, rng: &mut RandomNumberGenerator) -> Option<String> {
+        match self {
+            GlyphSpawn::Entity(name) => Some(name.clone()),
+            GlyphSpawn::Table(table) => LOOT_TABLES.roll(table, depth, rng),
+        }
+    }
+}
+
+/// A spot a glyph flags for the builder, on top of its tile and spawn.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Marker {
+    /// Where the player starts.
+    Start,
+    Door,
+    UpStairs,
+}
+
+/// What a glyph in a prefab means.
+#[derive(PartialEq, Clone)]
+pub struct GlyphMeaning {
+    pub tile: TileType,
+    pub spawn: Option<GlyphSpawn>,
+    pub marker: Option<Marker>,
+}
+
+impl GlyphMeaning {
+    /// Reads a meaning such as `floor, spawn Goblin`, `floor, table Floor Loot` or `floor, door`:
+    /// a tile, then whatever sits on it. The markers are `start`, `door` and `up stairs`.
+    pub fn parse(text: &str) -> Result<GlyphMeaning, String> {
+        let mut parts = text.split(',').map(|part| part.trim());
+
+        let tile = match parts.next().unwrap_or("") {
+            "wall" => TileType::Wall,
+            "floor" => TileType::Floor,
+            "stairs" => TileType::DownStairs,
+            "road" => TileType::Road,
+            "grass" => TileType::Grass,
+            "wood floor" => TileType::WoodFloor,
+            other => return Err(format!("unknown tile `{}`", other)),
+        };
+
+        let mut meaning = GlyphMeaning {
+            tile,
+            spawn: None,
+            marker: None,
+        };
+        for part in parts {
+            let marker = match part {
+                "start" => Some(Marker::Start),
+                "door" => Some(Marker::Door),
+                "up stairs" => Some(Marker::UpStairs),
+                _ => None,
+            };
+            if let Some(marker) = marker {
+                if meaning.marker.is_some() {
+                    return Err(format!("`{}` has more than one marker", text.trim()));
+                }
+                meaning.marker = Some(marker);
+                continue;
+            }
+            let spawn = match part.split_once(' ') {
+                Some(("spawn", name)) => {
+                    let name = name.trim();
+                    if !spawner::is_spawnable(name) {
+                        return Err(format!("unknown spawn `{}`", name));
+                    }
+                    GlyphSpawn::Entity(name.to_string())
+                }
+                Some(("table", table)) => {
+                    let table = table.trim();
+                    if !LOOT_TABLES.contains(table) {
+                        return Err(format!("unknown loot table `{}`", table));
+                    }
+                    GlyphSpawn::Table(table.to_string())
+                }
+                _ => {
+                    return Err(format!(
+                        "expected `spawn`, `table` or a marker, found `{}`",
+                        part
+                    ))
+                }
+            };
+            if meaning.spawn.is_some() {
+                return Err(format!("`{}` spawns more than one thing", text.trim()));
+            }
+            meaning.spawn = Some(spawn);
+        }
+
+        Ok(meaning)
+    }
+}
+
+/// Maps prefab glyphs to what they mean. There's a global legend shared by every prefab, which
+/// individual prefabs can add to or override.
+#[derive(PartialEq, Clone)]
+pub struct Legend {
+    glyphs: HashMap<char, GlyphMeaning>,
+}
+
+impl Legend {
+    /// The glyphs every prefab has always understood.
+    pub fn builtin() -> Legend {
+        let mut legend = Legend {
+            glyphs: HashMap::new(),
+        };
+        for (ch, meaning) in [
+            (' ', "floor"),
+            ('#', "wall"),
+            ('@', "floor, start"),
+            ('>', "stairs"),
+            ('<', "floor, up stairs"),
+            ('+', "floor, door"),
+            ('g', "floor, spawn Goblin"),
+            ('o', "floor, spawn Orc"),
+            ('^', "floor, spawn Bear Trap"),
+            ('%', "floor, spawn Rations"),
+            ('!', "floor, spawn Health Potion"),
+        ] {
+            legend.insert(ch, GlyphMeaning::parse(meaning).unwrap());
+        }
+        legend
+    }
+
+    pub fn get(&self, ch: char) -> Option<&GlyphMeaning> {
+        self.glyphs.get(&ch)
+    }
+
+    pub fn insert(&mut self, ch: char, meaning: GlyphMeaning) {
+        self.glyphs.insert(ch, meaning);
+    }
+
+    /// Reads `X: meaning` lines, one glyph per line, on top of this legend.
+    pub fn extend_from_lines<'a>(
+        &mut self,
+        lines: impl Iterator<Item = &'a str>,
+    ) -> Result<(), String> {
+        for line in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (ch, meaning) = parse_glyph_line(line)?;
+            self.insert(ch, meaning);
+        }
+        Ok(())
+    }
+}
+
+/// Splits `X: meaning` into the glyph and what it means. The glyph may itself be a colon.
+pub fn parse_glyph_line(line: &str) -> Result<(char, GlyphMeaning), String> {
+    let mut chars = line.chars();
+    let (Some(ch), Some(':')) = (chars.next(), chars.next()) else {
+        return Err(format!("expected `X: meaning`, found `{}`", line));
+    };
+    let meaning =
+        GlyphMeaning::parse(chars.as_str()).map_err(|e| format!("glyph `{}`: {}", ch, e))?;
+    Ok((ch, meaning))
+}
@@ -1,28 +1,22 @@
-pub mod prefab_levels;
-pub mod prefab_rooms;
-pub mod prefab_sections;
+pub mod legend;
+pub mod prefab_library;
 
 use super::{
     remove_unreachable_areas_returning_most_distant, Map, MapBuilder, Position, TileType,
     SHOW_MAPGEN_VISUALIZER,
 };
-use prefab_rooms::PrefabRoom;
-use rltk::RandomNumberGenerator;
+use bracket_random::prelude::RandomNumberGenerator;
+use legend::{Legend, Marker};
+use prefab_library::{Prefab, PrefabKind, PrefabTemplate, PREFABS};
 use std::collections::HashSet;
 
 #[allow(dead_code)]
 #[derive(PartialEq, Copy, Clone)]
 
 pub enum PrefabMode {
-    RexLevel {
-        template: &'static str,
-    },
-    Constant {
-        level: prefab_levels::PrefabLevel,
-    },
-    Sectional {
-        section: prefab_sections::PrefabSection,
-    },
+    RexLevel { template: &'static str },
+    Constant { level: &'static Prefab },
+    Sectional { section: &'static Prefab },
     RoomVaults,
 }
 
@@ -34,6 +28,7 @@ pub struct PrefabBuilder {
     mode: PrefabMode,
     previous_builder: Option<Box<dyn MapBuilder>>,
     spawn_list: Vec<(usize, String)>,
+    markers: Vec<(usize, Marker)>,
 }
 
 impl MapBuilder for PrefabBuilder {
@@ -49,8 +44,8 @@ impl MapBuilder for PrefabBuilder {
         self.history.clone()
     }
 
-    fn build_map(&mut self) {
-        self.build();
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
     }
 
     fn get_spawn_list(&self) -> &Vec<(usize, String)> {
@@ -79,6 +74,7 @@ impl PrefabBuilder {
             mode: PrefabMode::RoomVaults,
             previous_builder,
             spawn_list: Vec::new(),
+            markers: Vec::new(),
         }
     }
 
@@ -91,10 +87,11 @@ impl PrefabBuilder {
             mode: PrefabMode::RexLevel { template },
             previous_builder: None,
             spawn_list: Vec::new(),
+            markers: Vec::new(),
         }
     }
 
-    pub fn constant(new_depth: i32, level: prefab_levels::PrefabLevel) -> PrefabBuilder {
+    pub fn constant(new_depth: i32, level: &'static Prefab) -> PrefabBuilder {
         PrefabBuilder {
             map: Map::new(new_depth),
             starting_position: Position { x: 0, y: 0 },
@@ -103,12 +100,13 @@ impl PrefabBuilder {
             mode: PrefabMode::Constant { level },
             previous_builder: None,
             spawn_list: Vec::new(),
+            markers: Vec::new(),
         }
     }
 
     pub fn sectional(
         new_depth: i32,
-        section: prefab_sections::PrefabSection,
+        section: &'static Prefab,
         previous_builder: Box<dyn MapBuilder>,
     ) -> PrefabBuilder {
         PrefabBuilder {
@@ -119,6 +117,7 @@ impl PrefabBuilder {
             mode: PrefabMode::Sectional { section },
             previous_builder: Some(previous_builder),
             spawn_list: Vec::new(),
+            markers: Vec::new(),
         }
     }
 
@@ -131,15 +130,22 @@ impl PrefabBuilder {
             mode: PrefabMode::RoomVaults,
             previous_builder: Some(previous_builder),
             spawn_list: Vec::new(),
+            markers: Vec::new(),
         }
     }
 
-    fn build(&mut self) {
+    /// Doors and up stairs the prefab marked, by map index. Nothing in the game places a door
+    /// or a way up yet, so for now they're only recorded here.
+    pub fn markers(&self) -> &[(usize, Marker)] {
+        &self.markers
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
         match self.mode {
-            PrefabMode::RexLevel { template } => self.load_rex_map(&template),
-            PrefabMode::Constant { level } => self.load_ascii_map(&level),
-            PrefabMode::Sectional { section } => self.apply_sectional(&section),
-            PrefabMode::RoomVaults => self.apply_room_vaults(),
+            PrefabMode::RexLevel { template } => self.load_rex_map(template, rng),
+            PrefabMode::Constant { level } => self.load_ascii_map(level, rng),
+            PrefabMode::Sectional { section } => self.apply_sectional(section, rng),
+            PrefabMode::RoomVaults => self.apply_room_vaults(rng),
         }
         self.take_snapshot();
 
@@ -161,6 +167,7 @@ impl PrefabBuilder {
             }
             self.take_snapshot();
         }
+        let wants_exit = !matches!(self.mode, PrefabMode::Constant { level } if !level.stairs);
         let mut has_exit = false;
         for t in self.map.tiles.iter() {
             if *t == TileType::DownStairs {
@@ -168,7 +175,7 @@ impl PrefabBuilder {
             }
         }
 
-        if !has_exit {
+        if wants_exit && !has_exit {
             start_idx = self
                 .map
                 .xy_idx(self.starting_position.x, self.starting_position.y);
@@ -184,48 +191,39 @@ impl PrefabBuilder {
         }
     }
 
-    fn char_to_map(&mut self, ch: char, idx: usize) {
-        match ch {
-            ' ' => self.map.tiles[idx] = TileType::Floor,
-            '#' => self.map.tiles[idx] = TileType::Wall,
-            '@' => {
-                let x = idx as i32 % self.map.width;
-                let y = idx as i32 / self.map.width;
-                self.map.tiles[idx] = TileType::Floor;
-                self.starting_position = Position {
-                    x: x as i32,
-                    y: y as i32,
-                };
-            }
-            '>' => self.map.tiles[idx] = TileType::DownStairs,
-            'g' => {
-                self.map.tiles[idx] = TileType::Floor;
-                self.spawn_list.push((idx, "Goblin".to_string()));
-            }
-            'o' => {
-                self.map.tiles[idx] = TileType::Floor;
-                self.spawn_list.push((idx, "Orc".to_string()));
-            }
-            '^' => {
-                self.map.tiles[idx] = TileType::Floor;
-                self.spawn_list.push((idx, "Bear Trap".to_string()));
-            }
-            '%' => {
-                self.map.tiles[idx] = TileType::Floor;
-                self.spawn_list.push((idx, "Rations".to_string()));
-            }
-            '!' => {
-                self.map.tiles[idx] = TileType::Floor;
-                self.spawn_list.push((idx, "Health Potion".to_string()));
+    fn char_to_map(
+        &mut self,
+        legend: &Legend,
+        ch: char,
+        idx: usize,
+        rng: &mut RandomNumberGenerator,
+    ) {
+        match legend.get(ch) {
+            Some(meaning) => {
+                self.map.tiles[idx] = meaning.tile;
+                if let Some(spawn) = &meaning.spawn {
+                    if let Some(name) = spawn.resolve(self.depth, rng) {
+                        self.spawn_list.push((idx, name));
+                    }
+                }
+                match meaning.marker {
+                    Some(Marker::Start) => {
+                        let x = idx as i32 % self.map.width;
+                        let y = idx as i32 / self.map.width;
+                        self.starting_position = Position { x, y };
+                    }
+                    Some(marker) => self.markers.push((idx, marker)),
+                    None => {}
+                }
             }
-            _ => {
-                rltk::console::log(format!("Unknown glyph loading map: {}", (ch as u8) as char));
+            None => {
+                crate::console::log(format!("Unknown glyph loading map: {}", (ch as u8) as char));
             }
         }
     }
 
-    fn load_rex_map(&mut self, path: &str) {
-        let xp_file = rltk::rex::XpFile::from_resource(path).unwrap();
+    fn load_rex_map(&mut self, path: &str, rng: &mut RandomNumberGenerator) {
+        let xp_file = bracket_rex::prelude::XpFile::from_resource(path).unwrap();
 
         for layer in &xp_file.layers {
             for y in 0..layer.height {
@@ -234,97 +232,74 @@ impl PrefabBuilder {
                     if x < self.map.width as usize && y < self.map.height as usize {
                         let idx = self.map.xy_idx(x as i32, y as i32);
                         // We're doing some nasty casting to make it easier to type things like '#' in the match
-                        self.char_to_map(cell.ch as u8 as char, idx);
+                        self.char_to_map(PREFABS.legend(), cell.ch as u8 as char, idx, rng);
                     }
                 }
             }
         }
     }
 
-    fn read_ascii_to_vec(template: &str) -> Vec<char> {
-        let mut string_vec: Vec<char> = template
-            .chars()
-            .filter(|a| *a != '\r' && *a != '\n')
-            .collect();
-        for c in string_vec.iter_mut() {
-            if *c as u8 == 160u8 {
-                *c = ' ';
-            }
-        }
-        string_vec
-    }
+    fn load_ascii_map(&mut self, level: &Prefab, rng: &mut RandomNumberGenerator) {
+        let template = level.random_orientation(rng);
 
-    fn load_ascii_map(&mut self, level: &prefab_levels::PrefabLevel) {
-        let string_vec = PrefabBuilder::read_ascii_to_vec(level.template);
-
-        let mut i = 0;
-        for ty in 0..level.height {
-            for tx in 0..level.width {
+        for ty in 0..template.height {
+            for tx in 0..template.width {
                 if tx < self.map.width as usize && ty < self.map.height as usize {
                     let idx = self.map.xy_idx(tx as i32, ty as i32);
-                    self.char_to_map(string_vec[i], idx);
+                    self.char_to_map(&level.legend, template.glyph(tx, ty), idx, rng);
                 }
-                i += 1;
             }
         }
     }
 
-    pub fn apply_sectional(&mut self, section: &prefab_sections::PrefabSection) {
-        use prefab_sections::*;
+    pub fn apply_sectional(&mut self, section: &Prefab, rng: &mut RandomNumberGenerator) {
+        use prefab_library::*;
 
-        let string_vec = PrefabBuilder::read_ascii_to_vec(section.template);
+        let template = section.random_orientation(rng);
 
         // Place the new section
-        let chunk_x;
-        match section.placement.0 {
-            HorizontalPlacement::Left => chunk_x = 0,
-            HorizontalPlacement::Center => {
-                chunk_x = (self.map.width / 2) - (section.width as i32 / 2)
-            }
-            HorizontalPlacement::Right => chunk_x = (self.map.width - 1) - section.width as i32,
-        }
-
-        let chunk_y;
-        match section.placement.1 {
-            VerticalPlacement::Top => chunk_y = 0,
-            VerticalPlacement::Center => {
-                chunk_y = (self.map.height / 2) - (section.height as i32 / 2)
-            }
-            VerticalPlacement::Bottom => chunk_y = (self.map.height - 1) - section.height as i32,
-        }
+        let chunk_x = match section.placement.0 {
+            HorizontalPlacement::Left => 0,
+            HorizontalPlacement::Center => (self.map.width / 2) - (template.width as i32 / 2),
+            HorizontalPlacement::Right => (self.map.width - 1) - template.width as i32,
+        };
+
+        let chunk_y = match section.placement.1 {
+            VerticalPlacement::Top => 0,
+            VerticalPlacement::Center => (self.map.height / 2) - (template.height as i32 / 2),
+            VerticalPlacement::Bottom => (self.map.height - 1) - template.height as i32,
+        };
 
         // Build the map
-        self.apply_previous_iteration(|x, y, _e| {
+        self.apply_previous_iteration(rng, |x, y, _e| {
             x < chunk_x
-                || x > (chunk_x + section.width as i32)
+                || x > (chunk_x + template.width as i32)
                 || y < chunk_y
-                || y > (chunk_y + section.height as i32)
+                || y > (chunk_y + template.height as i32)
         });
 
-        let mut i = 0;
-        for ty in 0..section.height {
-            for tx in 0..section.width {
+        for ty in 0..template.height {
+            for tx in 0..template.width {
                 if tx > 0
                     && tx < self.map.width as usize - 1
                     && ty < self.map.height as usize - 1
                     && ty > 0
                 {
                     let idx = self.map.xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                    self.char_to_map(string_vec[i], idx);
+                    self.char_to_map(&section.legend, template.glyph(tx, ty), idx, rng);
                 }
-                i += 1;
             }
         }
         self.take_snapshot();
     }
 
-    fn apply_previous_iteration<F>(&mut self, mut filter: F)
+    fn apply_previous_iteration<F>(&mut self, rng: &mut RandomNumberGenerator, mut filter: F)
     where
         F: FnMut(i32, i32, &(usize, String)) -> bool,
     {
         // Build the map
         let prev_builder = self.previous_builder.as_mut().unwrap();
-        prev_builder.build_map();
+        prev_builder.build_map(rng);
         self.starting_position = prev_builder.get_starting_position();
         self.map = prev_builder.get_map().clone();
         for e in prev_builder.get_spawn_list().iter() {
@@ -338,12 +313,9 @@ impl PrefabBuilder {
         self.history = prev_builder.get_snapshot_history();
     }
 
-    fn apply_room_vaults(&mut self) {
-        use prefab_rooms::*;
-        let mut rng = RandomNumberGenerator::new();
-
+    fn apply_room_vaults(&mut self, rng: &mut RandomNumberGenerator) {
         // Apply the previous builder, and keep all entities it spawns (for now)
-        self.apply_previous_iteration(|_x, _y, _e| true);
+        self.apply_previous_iteration(rng, |_x, _y, _e| true);
 
         // Do we want a vault at all?
         let vault_roll = rng.roll_dice(1, 6) + self.depth;
@@ -351,14 +323,9 @@ impl PrefabBuilder {
             return;
         }
 
-        // Note that this is a place-holder and will be moved out of this function
-        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE];
-
         // Filter the vault list down to ones that are applicable to the current depth
-        let mut possible_vaults: Vec<&PrefabRoom> = master_vault_list
-            .iter()
-            .filter(|v| self.depth >= v.first_depth && self.depth <= v.last_depth)
-            .collect();
+        let mut possible_vaults: Vec<&Prefab> =
+            PREFABS.for_level(PrefabKind::Room, self.depth, self.map.theme);
 
         if possible_vaults.is_empty() {
             return;
@@ -367,12 +334,10 @@ impl PrefabBuilder {
         let mut used_tiles: HashSet<usize> = HashSet::new();
         let n_vaults = i32::min(rng.roll_dice(1, 3), possible_vaults.len() as i32);
         for _i in 0..n_vaults {
-            let vault_index = if possible_vaults.len() == 1 {
-                0
-            } else {
-                (rng.roll_dice(1, possible_vaults.len() as i32) - 1) as usize
+            let Some(prefab) = prefab_library::pick_weighted(&possible_vaults, rng) else {
+                break;
             };
-            let vault = possible_vaults[vault_index];
+            let vault = &prefab.random_orientation(rng);
 
             // We'll make a list of places in which the vault could fit
             let mut vault_positions: Vec<Position> = Vec::new();
@@ -387,11 +352,9 @@ impl PrefabBuilder {
                     && (x + vault.width as i32) < self.map.width - 2
                     && y > 1
                     && (y + vault.height as i32) < self.map.height - 2
+                    && self.can_place_vault(vault, x, y, &used_tiles)
                 {
-                    if self.can_place_vault(vault, x, y, &used_tiles) {
-                        vault_positions.push(Position { x, y });
-                        // break;
-                    }
+                    vault_positions.push(Position { x, y });
                 }
 
                 idx += 1;
@@ -423,26 +386,23 @@ impl PrefabBuilder {
                         || y > chunk_y + vault.height as i32
                 });
 
-                let string_vec = PrefabBuilder::read_ascii_to_vec(vault.template);
-                let mut i = 0;
                 for ty in 0..vault.height {
                     for tx in 0..vault.width {
                         let idx = self.map.xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                        self.char_to_map(string_vec[i], idx);
+                        self.char_to_map(&prefab.legend, vault.glyph(tx, ty), idx, rng);
                         used_tiles.insert(idx);
-                        i += 1;
                     }
                 }
                 self.take_snapshot();
 
-                possible_vaults.remove(vault_index);
+                possible_vaults.retain(|v| v.name != prefab.name);
             }
         }
     }
 
     fn can_place_vault(
         &self,
-        vault: &PrefabRoom,
+        vault: &PrefabTemplate,
         x: i32,
         y: i32,
         used_tiles: &HashSet<usize>,
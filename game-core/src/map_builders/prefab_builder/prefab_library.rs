@@ -0,0 +1,523 @@
+use super::legend::{parse_glyph_line, Legend};
+use crate::{
+    map::{MAPHEIGHT, MAPWIDTH},
+    theme::Theme,
+};
+use bracket_random::prelude::RandomNumberGenerator;
+use lazy_static::lazy_static;
+use std::{
+    collections::HashMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Where the vaults, sections and levels live, relative to the game. Every `.prefab` file in
+/// here is loaded at startup.
+pub const PREFAB_DIR: &str = "resources/prefabs";
+
+/// Glyphs every prefab understands, on top of the built in ones; lives in the prefab directory.
+const LEGEND_FILE: &str = "legend.txt";
+
+lazy_static! {
+    pub static ref PREFABS: PrefabLibrary =
+        PrefabLibrary::load(&prefab_dir()).expect("Unable to load prefabs");
+}
+
+/// Finds the prefab directory: under the working directory, beside the executable, or failing
+/// those in the source tree the game was built from.
+fn prefab_dir() -> PathBuf {
+    let beside_exe = env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(PREFAB_DIR)));
+    let source_tree = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(PREFAB_DIR);
+    [
+        Some(PathBuf::from(PREFAB_DIR)),
+        beside_exe,
+        Some(source_tree.clone()),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|dir| dir.is_dir())
+    .unwrap_or(source_tree)
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum PrefabKind {
+    /// A small vault dropped into open floor on an otherwise generated map.
+    Room,
+    /// A large chunk stamped onto one side of a generated map.
+    Section,
+    /// A whole map.
+    Level,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum HorizontalPlacement {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum VerticalPlacement {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// A rectangle of prefab glyphs, as it will be stamped onto the map.
+#[derive(PartialEq, Clone, Debug)]
+pub struct PrefabTemplate {
+    pub width: usize,
+    pub height: usize,
+    pub glyphs: Vec<char>,
+}
+
+impl PrefabTemplate {
+    pub fn glyph(&self, x: usize, y: usize) -> char {
+        self.glyphs[y * self.width + x]
+    }
+
+    /// Turns the template a quarter turn clockwise.
+    fn rotated(&self) -> PrefabTemplate {
+        let mut glyphs = Vec::with_capacity(self.glyphs.len());
+        for y in 0..self.width {
+            for x in 0..self.height {
+                glyphs.push(self.glyph(y, self.height - 1 - x));
+            }
+        }
+        PrefabTemplate {
+            width: self.height,
+            height: self.width,
+            glyphs,
+        }
+    }
+
+    /// Flips the template left to right.
+    fn mirrored(&self) -> PrefabTemplate {
+        let mut glyphs = Vec::with_capacity(self.glyphs.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                glyphs.push(self.glyph(self.width - 1 - x, y));
+            }
+        }
+        PrefabTemplate {
+            width: self.width,
+            height: self.height,
+            glyphs,
+        }
+    }
+
+    fn fits_map(&self) -> bool {
+        self.width <= MAPWIDTH && self.height <= MAPHEIGHT
+    }
+}
+
+/// A template and everything we know about where it may be used.
+#[derive(PartialEq, Clone)]
+pub struct Prefab {
+    pub name: String,
+    pub kind: PrefabKind,
+    pub first_depth: i32,
+    pub last_depth: i32,
+    pub weight: i32,
+    /// Whether the template may be turned in quarter turns when it's placed.
+    pub rotate: bool,
+    /// Whether the template may be flipped when it's placed.
+    pub mirror: bool,
+    /// Whether a level gets stairs down when its template doesn't have any; the last level has
+    /// nowhere further to go.
+    pub stairs: bool,
+    pub placement: (HorizontalPlacement, VerticalPlacement),
+    /// The level themes this prefab suits; empty if it suits them all.
+    pub themes: Vec<Theme>,
+    pub template: PrefabTemplate,
+    /// The global legend, plus any glyphs this prefab defines for itself.
+    pub legend: Legend,
+}
+
+impl Prefab {
+    /// Every way this prefab is allowed to be placed that still fits on a map.
+    pub fn orientations(&self) -> Vec<PrefabTemplate> {
+        let mut orientations = vec![self.template.clone()];
+        if self.mirror {
+            orientations.push(self.template.mirrored());
+        }
+        if self.rotate {
+            for i in 0..orientations.len() {
+                let mut turned = orientations[i].clone();
+                for _ in 0..3 {
+                    turned = turned.rotated();
+                    orientations.push(turned.clone());
+                }
+            }
+        }
+        orientations.retain(|t| t.fits_map());
+        orientations
+    }
+
+    /// Picks one of the allowed orientations at random.
+    pub fn random_orientation(&self, rng: &mut RandomNumberGenerator) -> PrefabTemplate {
+        let mut orientations = self.orientations();
+        let pick = (rng.roll_dice(1, orientations.len() as i32) - 1) as usize;
+        orientations.swap_remove(pick)
+    }
+
+    /// Reads a prefab file: `key: value` metadata, a line holding `---`, then the template (or a
+    /// `rex` key naming a REX Paint file beside it instead). Metadata lines such as
+    /// `glyph k: floor, spawn Kobold` add to the legend for this prefab only.
+    pub fn parse(
+        file: &str,
+        text: &str,
+        dir: &Path,
+        legend: &Legend,
+    ) -> Result<Prefab, PrefabError> {
+        let error = |problem: String| PrefabError {
+            file: file.to_string(),
+            problem,
+        };
+
+        let mut lines = text.lines();
+        let mut fields: HashMap<&str, &str> = HashMap::new();
+        let mut legend = legend.clone();
+        for line in lines.by_ref() {
+            if line.trim() == "---" {
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(glyph) = line.strip_prefix("glyph ") {
+                let (ch, meaning) = parse_glyph_line(glyph).map_err(error)?;
+                legend.insert(ch, meaning);
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(error(format!("expected `key: value`, found `{}`", line)));
+            };
+            fields.insert(key.trim(), value.trim());
+        }
+        let rows: Vec<&str> = lines.collect();
+
+        let field = |key: &str| {
+            fields
+                .get(key)
+                .copied()
+                .ok_or_else(|| error(format!("missing `{}`", key)))
+        };
+        let flag = |key: &str, default: bool| match fields.get(key).copied() {
+            None => Ok(default),
+            Some("no") => Ok(false),
+            Some("yes") => Ok(true),
+            Some(other) => Err(error(format!(
+                "`{}` should be yes or no, not `{}`",
+                key, other
+            ))),
+        };
+
+        let name = field("name")?.to_string();
+        let kind = match field("kind")? {
+            "room" => PrefabKind::Room,
+            "section" => PrefabKind::Section,
+            "level" => PrefabKind::Level,
+            other => return Err(error(format!("unknown kind `{}`", other))),
+        };
+        let (first_depth, last_depth) = field("depth")?
+            .split_once('-')
+            .and_then(|(first, last)| Some((first.trim().parse().ok()?, last.trim().parse().ok()?)))
+            .filter(|(first, last)| first <= last)
+            .ok_or_else(|| error("depth should look like `1-10`".to_string()))?;
+        let weight = match fields.get("weight") {
+            None => 1,
+            Some(weight) => weight
+                .parse()
+                .ok()
+                .filter(|w| *w > 0)
+                .ok_or_else(|| error(format!("bad weight `{}`", weight)))?,
+        };
+        let placement = match fields.get("placement") {
+            None => (HorizontalPlacement::Center, VerticalPlacement::Center),
+            Some(placement) => parse_placement(placement)
+                .ok_or_else(|| error(format!("bad placement `{}`", placement)))?,
+        };
+
+        let mut themes = Vec::new();
+        if let Some(names) = fields.get("themes") {
+            for name in names.split(',').map(|name| name.trim()) {
+                let theme = Theme::from_name(name)
+                    .ok_or_else(|| error(format!("unknown theme `{}`", name)))?;
+                themes.push(theme);
+            }
+        }
+
+        let template = match fields.get("rex") {
+            Some(rex) => read_rex(&dir.join(rex)).map_err(error)?,
+            None => {
+                let (width, height) = field("size")?
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+                    .ok_or_else(|| error("size should look like `6x5`".to_string()))?;
+                read_ascii(&rows, width, height).map_err(error)?
+            }
+        };
+
+        if template.width == 0 || template.height == 0 {
+            return Err(error("the template is empty".to_string()));
+        }
+        if !template.fits_map() {
+            return Err(error(format!(
+                "{}x{} is bigger than the map",
+                template.width, template.height
+            )));
+        }
+        for (i, glyph) in template.glyphs.iter().enumerate() {
+            if legend.get(*glyph).is_none() {
+                return Err(error(format!(
+                    "unknown glyph `{}` at {},{}",
+                    glyph,
+                    i % template.width,
+                    i / template.width
+                )));
+            }
+        }
+
+        Ok(Prefab {
+            name,
+            kind,
+            first_depth,
+            last_depth,
+            weight,
+            rotate: flag("rotate", false)?,
+            mirror: flag("mirror", false)?,
+            stairs: flag("stairs", true)?,
+            placement,
+            themes,
+            template,
+            legend,
+        })
+    }
+}
+
+fn parse_placement(placement: &str) -> Option<(HorizontalPlacement, VerticalPlacement)> {
+    let (horizontal, vertical) = placement.split_once(' ')?;
+    let horizontal = match horizontal.trim() {
+        "left" => HorizontalPlacement::Left,
+        "center" => HorizontalPlacement::Center,
+        "right" => HorizontalPlacement::Right,
+        _ => return None,
+    };
+    let vertical = match vertical.trim() {
+        "top" => VerticalPlacement::Top,
+        "center" => VerticalPlacement::Center,
+        "bottom" => VerticalPlacement::Bottom,
+        _ => return None,
+    };
+    Some((horizontal, vertical))
+}
+
+/// Editors like to trim trailing spaces, so short rows (and missing rows at the end) are padded
+/// out with floor; anything beyond the declared size is a mistake.
+fn read_ascii(rows: &[&str], width: usize, height: usize) -> Result<PrefabTemplate, String> {
+    if rows.len() > height {
+        return Err(format!("{} rows, but the size says {}", rows.len(), height));
+    }
+
+    let mut glyphs = vec![' '; width * height];
+    for (y, row) in rows.iter().enumerate() {
+        let row: Vec<char> = row
+            .chars()
+            .filter(|c| *c != '\r')
+            .map(|c| if c == '\u{a0}' { ' ' } else { c })
+            .collect();
+        if row.len() > width {
+            return Err(format!(
+                "row {} is {} wide, but the size says {}",
+                y,
+                row.len(),
+                width
+            ));
+        }
+        for (x, c) in row.into_iter().enumerate() {
+            glyphs[y * width + x] = c;
+        }
+    }
+
+    Ok(PrefabTemplate {
+        width,
+        height,
+        glyphs,
+    })
+}
+
+fn read_rex(path: &Path) -> Result<PrefabTemplate, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let xp_file = bracket_rex::prelude::XpFile::read(&mut file)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let Some(first) = xp_file.layers.first() else {
+        return Err(format!("{} has no layers", path.display()));
+    };
+
+    // Later layers are drawn over earlier ones
+    let (width, height) = (first.width, first.height);
+    let mut glyphs = vec![' '; width * height];
+    for layer in &xp_file.layers {
+        for y in 0..layer.height.min(height) {
+            for x in 0..layer.width.min(width) {
+                glyphs[y * width + x] = layer.get(x, y).unwrap().ch as u8 as char;
+            }
+        }
+    }
+
+    Ok(PrefabTemplate {
+        width,
+        height,
+        glyphs,
+    })
+}
+
+/// A template that couldn't be loaded, and why.
+#[derive(Debug)]
+pub struct PrefabError {
+    pub file: String,
+    pub problem: String,
+}
+
+impl fmt::Display for PrefabError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.file, self.problem)
+    }
+}
+
+/// Every prefab we know about, by name.
+pub struct PrefabLibrary {
+    prefabs: Vec<Prefab>,
+    legend: Legend,
+}
+
+impl PrefabLibrary {
+    /// Loads every prefab in a directory. Broken files are reported and left out, rather than
+    /// taking the whole game down with them, but a directory with nothing usable in it is an
+    /// error.
+    pub fn load(dir: &Path) -> Result<PrefabLibrary, PrefabError> {
+        let (library, errors) = PrefabLibrary::load_checked(dir);
+        for e in errors.iter() {
+            crate::console::log(format!("Skipping prefab {}", e));
+        }
+        if library.prefabs.is_empty() {
+            return Err(PrefabError {
+                file: dir.display().to_string(),
+                problem: "no prefabs could be loaded".to_string(),
+            });
+        }
+        Ok(library)
+    }
+
+    /// Loads what it can from a directory, along with everything that was wrong with the rest.
+    pub fn load_checked(dir: &Path) -> (PrefabLibrary, Vec<PrefabError>) {
+        let mut prefabs: Vec<Prefab> = Vec::new();
+        let mut errors = Vec::new();
+
+        let mut legend = Legend::builtin();
+        let legend_path = dir.join(LEGEND_FILE);
+        if legend_path.exists() {
+            let extended = fs::read_to_string(&legend_path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| legend.extend_from_lines(text.lines()));
+            if let Err(problem) = extended {
+                errors.push(PrefabError {
+                    file: legend_path.display().to_string(),
+                    problem,
+                });
+            }
+        }
+
+        let mut files: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "prefab"))
+                .collect(),
+            Err(e) => {
+                errors.push(PrefabError {
+                    file: dir.display().to_string(),
+                    problem: e.to_string(),
+                });
+                Vec::new()
+            }
+        };
+        // Directory order varies between systems; keep things reproducible
+        files.sort();
+
+        for path in files {
+            let file = path.display().to_string();
+            let prefab = fs::read_to_string(&path)
+                .map_err(|e| PrefabError {
+                    file: file.clone(),
+                    problem: e.to_string(),
+                })
+                .and_then(|text| Prefab::parse(&file, &text, dir, &legend));
+            match prefab {
+                Ok(prefab) if prefabs.iter().any(|p| p.name == prefab.name) => {
+                    errors.push(PrefabError {
+                        problem: format!("`{}` is defined twice", prefab.name),
+                        file,
+                    })
+                }
+                Ok(prefab) => prefabs.push(prefab),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (PrefabLibrary { prefabs, legend }, errors)
+    }
+
+    /// The glyphs every prefab understands.
+    pub fn legend(&self) -> &Legend {
+        &self.legend
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.iter().find(|p| p.name == name)
+    }
+
+    /// All the prefabs of a kind that may appear on a level of this depth and theme.
+    pub fn for_level(&self, kind: PrefabKind, depth: i32, theme: Theme) -> Vec<&Prefab> {
+        self.prefabs
+            .iter()
+            .filter(|p| p.kind == kind && depth >= p.first_depth && depth <= p.last_depth)
+            .filter(|p| p.themes.is_empty() || p.themes.contains(&theme))
+            .collect()
+    }
+
+    /// Picks a prefab of a kind for a level, favouring the heavier ones.
+    pub fn pick(
+        &self,
+        kind: PrefabKind,
+        depth: i32,
+        theme: Theme,
+        rng: &mut RandomNumberGenerator,
+    ) -> Option<&Prefab> {
+        pick_weighted(&self.for_level(kind, depth, theme), rng)
+    }
+}
+
+/// Picks from a list of prefabs, favouring the heavier ones.
+pub fn pick_weighted<'a>(
+    prefabs: &[&'a Prefab],
+    rng: &mut RandomNumberGenerator,
+) -> Option<&'a Prefab> {
+    let total: i32 = prefabs.iter().map(|p| p.weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = rng.roll_dice(1, total) - 1;
+    for prefab in prefabs.iter() {
+        if roll < prefab.weight {
+            return Some(prefab);
+        }
+        roll -= prefab.weight;
+    }
+    None
+}
@@ -0,0 +1,305 @@
+use super::{apply_room_to_map, Map, MapBuilder, Position, Rect, TileType, SHOW_MAPGEN_VISUALIZER};
+use bracket_random::prelude::RandomNumberGenerator;
+
+#[derive(PartialEq, Copy, Clone)]
+enum BuildingTag {
+    Pub,
+    Temple,
+    Blacksmith,
+    Alchemist,
+    Clothier,
+    Hovel,
+}
+
+/// Builds the surface town that sits on top of the dungeon: a main street, a handful of
+/// buildings with doors facing the road, their occupants, and the way down.
+pub struct TownBuilder {
+    map: Map,
+    starting_position: Position,
+    history: Vec<Map>,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for TownBuilder {
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Map> {
+        self.history.clone()
+    }
+
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+
+    fn take_snapshot(&mut self) {
+        if SHOW_MAPGEN_VISUALIZER {
+            let mut snapshot = self.map.clone();
+            for v in snapshot.revealed_tiles.iter_mut() {
+                *v = true;
+            }
+            self.history.push(snapshot);
+        }
+    }
+}
+
+impl TownBuilder {
+    pub fn new(new_depth: i32) -> TownBuilder {
+        TownBuilder {
+            map: Map::new(new_depth),
+            starting_position: Position { x: 0, y: 0 },
+            history: Vec::new(),
+            spawn_list: Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
+        let road_y = self.map.height / 2;
+
+        // The town needs at least one building for the pub, so lay it out again if none fit
+        let buildings = loop {
+            self.grass_layer();
+            self.main_street(road_y);
+            let buildings = self.buildings(rng, road_y);
+            if !buildings.is_empty() {
+                break buildings;
+            }
+        };
+        self.add_doors(&buildings, road_y);
+
+        // Biggest buildings get the important jobs
+        let mut by_size: Vec<&Rect> = buildings.iter().collect();
+        by_size.sort_by_key(|b| -((b.x2 - b.x1) * (b.y2 - b.y1)));
+        for (i, building) in by_size.iter().enumerate() {
+            let tag = match i {
+                0 => BuildingTag::Pub,
+                1 => BuildingTag::Temple,
+                2 => BuildingTag::Blacksmith,
+                3 => BuildingTag::Alchemist,
+                4 => BuildingTag::Clothier,
+                _ => BuildingTag::Hovel,
+            };
+            self.populate_building(rng, building, tag);
+        }
+
+        // The player wakes up in the pub, and the way down is at the far end of town
+        let pub_building = by_size[0];
+        let (start_x, start_y) = pub_building.center();
+        self.starting_position = Position {
+            x: start_x,
+            y: start_y,
+        };
+        let exit_x = if start_x < self.map.width / 2 {
+            self.map.width - 2
+        } else {
+            1
+        };
+        let exit_idx = self.map.xy_idx(exit_x, road_y);
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+        self.take_snapshot();
+
+        self.townsfolk(rng);
+    }
+
+    fn grass_layer(&mut self) {
+        for y in 0..self.map.height {
+            for x in 0..self.map.width {
+                let idx = self.map.xy_idx(x, y);
+                if x == 0 || y == 0 || x == self.map.width - 1 || y == self.map.height - 1 {
+                    self.map.tiles[idx] = TileType::Wall;
+                } else {
+                    self.map.tiles[idx] = TileType::Grass;
+                }
+            }
+        }
+        self.take_snapshot();
+    }
+
+    fn main_street(&mut self, road_y: i32) {
+        for y in road_y - 1..=road_y + 1 {
+            for x in 1..self.map.width - 1 {
+                let idx = self.map.xy_idx(x, y);
+                self.map.tiles[idx] = TileType::Road;
+            }
+        }
+        self.take_snapshot();
+    }
+
+    /// Scatters non-overlapping buildings either side of the main street.
+    fn buildings(&mut self, rng: &mut RandomNumberGenerator, road_y: i32) -> Vec<Rect> {
+        const MAX_BUILDINGS: i32 = 12;
+        const ATTEMPTS: i32 = 200;
+
+        let mut buildings: Vec<Rect> = Vec::new();
+        let mut attempts = 0;
+        while attempts < ATTEMPTS && (buildings.len() as i32) < MAX_BUILDINGS {
+            attempts += 1;
+
+            let w = rng.range(7, 14);
+            let h = rng.range(5, 9);
+            let x = rng.range(2, self.map.width - w - 2);
+            let north = rng.range(0, 2) == 0;
+            let y = if north {
+                rng.range(2, i32::max(3, road_y - h - 3))
+            } else {
+                rng.range(road_y + 4, i32::max(road_y + 5, self.map.height - h - 2))
+            };
+            let candidate = Rect::new(x, y, w, h);
+
+            // Keep a clear strip between the building and the road, and the map edges
+            if candidate.y2 >= road_y - 2 && candidate.y1 <= road_y + 2 {
+                continue;
+            }
+            if candidate.y1 < 2 || candidate.y2 > self.map.height - 3 {
+                continue;
+            }
+
+            // Leave an alley between neighbouring buildings, and don't block anyone's way out
+            let padded = Rect::new(x - 2, y - 1, w + 4, h + 2);
+            let (_, path) = TownBuilder::door_path(&candidate, road_y);
+            if buildings.iter().any(|b| {
+                padded.intersect(b)
+                    || path.intersect(b)
+                    || padded.intersect(&TownBuilder::door_path(b, road_y).1)
+            }) {
+                continue;
+            }
+
+            for by in candidate.y1..=candidate.y2 {
+                for bx in candidate.x1..=candidate.x2 {
+                    let idx = self.map.xy_idx(bx, by);
+                    self.map.tiles[idx] = TileType::Wall;
+                }
+            }
+            apply_room_to_map(&mut self.map, &Rect::new(x, y, w - 1, h - 1));
+            for by in candidate.y1 + 1..candidate.y2 {
+                for bx in candidate.x1 + 1..candidate.x2 {
+                    let idx = self.map.xy_idx(bx, by);
+                    self.map.tiles[idx] = TileType::WoodFloor;
+                }
+            }
+
+            buildings.push(candidate);
+            self.take_snapshot();
+        }
+
+        buildings
+    }
+
+    /// Returns the y coordinate of a building's door, and the strip of land between it and
+    /// the main street.
+    fn door_path(building: &Rect, road_y: i32) -> (i32, Rect) {
+        let (door_x, _) = building.center();
+        if building.y2 < road_y {
+            (
+                building.y2,
+                Rect::new(door_x, building.y2 + 1, 0, road_y - building.y2 - 3),
+            )
+        } else {
+            (
+                building.y1,
+                Rect::new(door_x, road_y + 2, 0, building.y1 - road_y - 3),
+            )
+        }
+    }
+
+    /// Knocks a door in the wall facing the road, and lays a path from it to the street.
+    fn add_doors(&mut self, buildings: &[Rect], road_y: i32) {
+        for building in buildings.iter() {
+            let (door_y, path) = TownBuilder::door_path(building, road_y);
+
+            let door_idx = self.map.xy_idx(path.x1, door_y);
+            self.map.tiles[door_idx] = TileType::WoodFloor;
+            for y in path.y1..=path.y2 {
+                let idx = self.map.xy_idx(path.x1, y);
+                self.map.tiles[idx] = TileType::Road;
+            }
+            self.take_snapshot();
+        }
+    }
+
+    fn populate_building(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        building: &Rect,
+        tag: BuildingTag,
+    ) {
+        let mut free_tiles: Vec<usize> = Vec::new();
+        for y in building.y1 + 1..building.y2 {
+            for x in building.x1 + 1..building.x2 {
+                let idx = self.map.xy_idx(x, y);
+                if self.map.tiles[idx] == TileType::WoodFloor {
+                    free_tiles.push(idx);
+                }
+            }
+        }
+
+        // Keep the middle free, that's where the player arrives if this is the pub
+        let (cx, cy) = building.center();
+        let center_idx = self.map.xy_idx(cx, cy);
+        free_tiles.retain(|idx| *idx != center_idx);
+
+        let occupants: Vec<&str> = match tag {
+            BuildingTag::Pub => {
+                let mut o = vec!["Barkeep"];
+                o.extend(vec!["Patron"; rng.roll_dice(1, 3) as usize + 1]);
+                o
+            }
+            BuildingTag::Temple => {
+                let mut o = vec!["Priest"];
+                o.extend(vec!["Parishioner"; rng.roll_dice(1, 3) as usize]);
+                o
+            }
+            BuildingTag::Blacksmith => vec!["Blacksmith"],
+            BuildingTag::Alchemist => vec!["Alchemist"],
+            BuildingTag::Clothier => vec!["Clothier"],
+            BuildingTag::Hovel => vec!["Peasant"; rng.roll_dice(1, 3) as usize - 1],
+        };
+
+        for name in occupants {
+            if free_tiles.is_empty() {
+                break;
+            }
+            let tile_index = (rng.roll_dice(1, free_tiles.len() as i32) - 1) as usize;
+            self.spawn_list
+                .push((free_tiles[tile_index], name.to_string()));
+            free_tiles.remove(tile_index);
+        }
+        self.take_snapshot();
+    }
+
+    /// Puts a few people out on the streets.
+    fn townsfolk(&mut self, rng: &mut RandomNumberGenerator) {
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        let mut road_tiles: Vec<usize> = self
+            .map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(idx, tile)| **tile == TileType::Road && *idx != start_idx)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let count = rng.roll_dice(2, 3);
+        for _ in 0..count {
+            if road_tiles.is_empty() {
+                break;
+            }
+            let tile_index = (rng.roll_dice(1, road_tiles.len() as i32) - 1) as usize;
+            self.spawn_list
+                .push((road_tiles[tile_index], "Townsperson".to_string()));
+            road_tiles.remove(tile_index);
+        }
+    }
+}
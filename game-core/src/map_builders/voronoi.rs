@@ -2,8 +2,8 @@ use super::{
     generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map,
     MapBuilder, Position, TileType, SHOW_MAPGEN_VISUALIZER,
 };
-use rltk::RandomNumberGenerator;
-use std::collections::HashMap;
+use bracket_random::prelude::RandomNumberGenerator;
+use std::collections::BTreeMap;
 
 #[allow(dead_code)]
 #[derive(PartialEq, Copy, Clone)]
@@ -18,7 +18,7 @@ pub struct VoronoiCellBuilder {
     starting_position: Position,
     depth: i32,
     history: Vec<Map>,
-    noise_areas: HashMap<i32, Vec<usize>>,
+    noise_areas: BTreeMap<i32, Vec<usize>>,
     n_seeds: usize,
     distance_algorithm: DistanceAlgorithm,
     spawn_list: Vec<(usize, String)>,
@@ -37,8 +37,8 @@ impl MapBuilder for VoronoiCellBuilder {
         self.history.clone()
     }
 
-    fn build_map(&mut self) {
-        self.build();
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
     }
 
     fn get_spawn_list(&self) -> &Vec<(usize, String)> {
@@ -63,7 +63,7 @@ impl VoronoiCellBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             n_seeds: 64,
             distance_algorithm: DistanceAlgorithm::Pythagoras,
             spawn_list: Vec::new(),
@@ -76,24 +76,22 @@ impl VoronoiCellBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             history: Vec::new(),
-            noise_areas: HashMap::new(),
+            noise_areas: BTreeMap::new(),
             n_seeds: 64,
             distance_algorithm: DistanceAlgorithm::Manhattan,
             spawn_list: Vec::new(),
         }
     }
 
-    fn build(&mut self) {
-        let mut rng = RandomNumberGenerator::new();
-
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
         // Make a Voronoi diagram. We'll do this the hard way to learn about the technique!
-        let mut voronoi_seeds: Vec<(usize, rltk::Point)> = Vec::new();
+        let mut voronoi_seeds: Vec<(usize, bracket_geometry::prelude::Point)> = Vec::new();
 
         while voronoi_seeds.len() < self.n_seeds {
             let vx = rng.roll_dice(1, self.map.width - 1);
             let vy = rng.roll_dice(1, self.map.height - 1);
             let vidx = self.map.xy_idx(vx, vy);
-            let candidate = (vidx, rltk::Point::new(vx, vy));
+            let candidate = (vidx, bracket_geometry::prelude::Point::new(vx, vy));
             if !voronoi_seeds.contains(&candidate) {
                 voronoi_seeds.push(candidate);
             }
@@ -107,21 +105,20 @@ impl VoronoiCellBuilder {
             let y = i as i32 / self.map.width;
 
             for (seed, pos) in voronoi_seeds.iter().enumerate() {
-                let distance;
-                match self.distance_algorithm {
+                let distance = match self.distance_algorithm {
                     DistanceAlgorithm::Pythagoras => {
-                        distance = rltk::DistanceAlg::PythagorasSquared
-                            .distance2d(rltk::Point::new(x, y), pos.1);
+                        bracket_geometry::prelude::DistanceAlg::PythagorasSquared
+                            .distance2d(bracket_geometry::prelude::Point::new(x, y), pos.1)
                     }
                     DistanceAlgorithm::Manhattan => {
-                        distance =
-                            rltk::DistanceAlg::Manhattan.distance2d(rltk::Point::new(x, y), pos.1);
+                        bracket_geometry::prelude::DistanceAlg::Manhattan
+                            .distance2d(bracket_geometry::prelude::Point::new(x, y), pos.1)
                     }
                     DistanceAlgorithm::Chebyshev => {
-                        distance =
-                            rltk::DistanceAlg::Chebyshev.distance2d(rltk::Point::new(x, y), pos.1);
+                        bracket_geometry::prelude::DistanceAlg::Chebyshev
+                            .distance2d(bracket_geometry::prelude::Point::new(x, y), pos.1)
                     }
-                }
+                };
                 voronoi_distance[seed] = (seed, distance);
             }
 
@@ -180,11 +177,11 @@ impl VoronoiCellBuilder {
         self.take_snapshot();
 
         // Now we build a noise map for use in spawning entities later
-        self.noise_areas = generate_voronoi_spawn_regions(&self.map, &mut rng);
+        self.noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
 
         // Spawn the entities
         for (_, area) in self.noise_areas.iter() {
-            spawner::spawn_region(&mut rng, area, self.depth, &mut self.spawn_list);
+            spawner::spawn_region(rng, area, self.depth, &mut self.spawn_list);
         }
     }
 }
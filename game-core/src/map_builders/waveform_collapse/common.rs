@@ -0,0 +1,123 @@
+use super::{GlyphSpawn, Map, TileType, PREFABS};
+
+/// One tile of a pattern: the terrain, and whatever the sample had standing on it.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct PatternTile {
+    pub tile: TileType,
+    pub spawn: Option<&'static GlyphSpawn>,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub struct MapChunk {
+    pub pattern: Vec<PatternTile>,
+    pub exits: [Vec<bool>; 4],
+    pub has_exits: bool,
+    pub compatible_with: [Vec<usize>; 4],
+    /// How many times this pattern appeared in the source map; more common patterns are picked
+    /// more often.
+    pub frequency: i32,
+}
+
+pub fn tile_idx_in_chunk(chunk_size: i32, x: i32, y: i32) -> usize {
+    ((y * chunk_size) + x) as usize
+}
+
+/// The image patterns are learned from: either the output of another builder, or an authored
+/// REX Paint sample.
+pub struct Sample {
+    pub width: i32,
+    pub height: i32,
+    pub tiles: Vec<PatternTile>,
+}
+
+impl Sample {
+    pub fn idx(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub fn from_map(map: &Map) -> Sample {
+        Sample {
+            width: map.width,
+            height: map.height,
+            tiles: map
+                .tiles
+                .iter()
+                .map(|tile| PatternTile {
+                    tile: Sample::without_stairs(*tile),
+                    spawn: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Reads a sample drawn with the prefab glyphs, including anything spawned on the tiles.
+    pub fn from_rex(path: &str) -> Sample {
+        let xp_file = bracket_rex::prelude::XpFile::from_resource(path).unwrap();
+        let width = xp_file.layers[0].width as i32;
+        let height = xp_file.layers[0].height as i32;
+        let mut tiles = vec![
+            PatternTile {
+                tile: TileType::Wall,
+                spawn: None,
+            };
+            (width * height) as usize
+        ];
+
+        for layer in &xp_file.layers {
+            for y in 0..layer.height.min(height as usize) {
+                for x in 0..layer.width.min(width as usize) {
+                    let ch = layer.get(x, y).unwrap().ch as u8 as char;
+                    match PREFABS.legend().get(ch) {
+                        Some(meaning) => {
+                            tiles[y * width as usize + x] = PatternTile {
+                                tile: Sample::without_stairs(meaning.tile),
+                                spawn: meaning.spawn.as_ref(),
+                            }
+                        }
+                        None => {
+                            crate::console::log(format!("Unknown glyph loading sample: {}", ch))
+                        }
+                    }
+                }
+            }
+        }
+
+        Sample {
+            width,
+            height,
+            tiles,
+        }
+    }
+
+    /// Stairs are placed once the map is finished, so they're just floor as far as patterns go.
+    fn without_stairs(tile: TileType) -> TileType {
+        if tile == TileType::DownStairs {
+            TileType::Floor
+        } else {
+            tile
+        }
+    }
+
+    pub fn has_spawns(&self) -> bool {
+        self.tiles.iter().any(|t| t.spawn.is_some())
+    }
+
+    pub fn floor_count(&self) -> usize {
+        self.tiles
+            .iter()
+            .filter(|t| t.tile == TileType::Floor)
+            .count()
+    }
+
+    /// Draws the sample onto a map, so it can be shown or used as a fallback.
+    pub fn to_map(&self, depth: i32) -> Map {
+        let mut map = Map::new(depth);
+        for y in 0..self.height.min(map.height) {
+            for x in 0..self.width.min(map.width) {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = self.tiles[self.idx(x, y)].tile;
+            }
+        }
+        map
+    }
+}
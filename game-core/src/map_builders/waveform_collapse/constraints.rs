@@ -1,12 +1,15 @@
-use super::{tile_idx_in_chunk, Map, MapChunk, TileType};
-use std::collections::HashSet;
+use super::{tile_idx_in_chunk, Map, MapChunk, PatternTile, Sample, TileType};
+use std::collections::HashMap;
+
+/// A pattern of tiles, and how many times it was seen.
+pub type Pattern = (Vec<PatternTile>, i32);
 
 pub fn build_patterns(
-    map: &Map,
+    map: &Sample,
     chunk_size: i32,
     include_flipping: bool,
     dedupe: bool,
-) -> Vec<Vec<TileType>> {
+) -> Vec<Pattern> {
     let chunks_x = map.width / chunk_size;
     let chunks_y = map.height / chunk_size;
     let mut patterns = Vec::new();
@@ -14,7 +17,7 @@ pub fn build_patterns(
     for cy in 0..chunks_y {
         for cx in 0..chunks_x {
             // Normal orientation
-            let mut pattern: Vec<TileType> = Vec::new();
+            let mut pattern: Vec<PatternTile> = Vec::new();
             let start_x = cx * chunk_size;
             let end_x = (cx + 1) * chunk_size;
             let start_y = cy * chunk_size;
@@ -22,7 +25,7 @@ pub fn build_patterns(
 
             for y in start_y..end_y {
                 for x in start_x..end_x {
-                    let idx = map.xy_idx(x, y);
+                    let idx = map.idx(x, y);
                     pattern.push(map.tiles[idx]);
                 }
             }
@@ -33,7 +36,7 @@ pub fn build_patterns(
                 pattern = Vec::new();
                 for y in start_y..end_y {
                     for x in start_x..end_x {
-                        let idx = map.xy_idx(end_x - (x + 1), y);
+                        let idx = map.idx(end_x - (x + 1), y);
                         pattern.push(map.tiles[idx]);
                     }
                 }
@@ -43,7 +46,7 @@ pub fn build_patterns(
                 pattern = Vec::new();
                 for y in start_y..end_y {
                     for x in start_x..end_x {
-                        let idx = map.xy_idx(x, end_y - (y + 1));
+                        let idx = map.idx(x, end_y - (y + 1));
                         pattern.push(map.tiles[idx]);
                     }
                 }
@@ -53,7 +56,7 @@ pub fn build_patterns(
                 pattern = Vec::new();
                 for y in start_y..end_y {
                     for x in start_x..end_x {
-                        let idx = map.xy_idx(end_x - (x + 1), end_y - (y + 1));
+                        let idx = map.idx(end_x - (x + 1), end_y - (y + 1));
                         pattern.push(map.tiles[idx]);
                     }
                 }
@@ -64,16 +67,65 @@ pub fn build_patterns(
 
     // Dedupe
     if dedupe {
-        rltk::console::log(format!(
+        crate::console::log(format!(
             "Pre de-duplication, there are {} patterns",
             patterns.len()
         ));
-        let set: HashSet<Vec<TileType>> = patterns.drain(..).collect(); // dedup
-        patterns.extend(set.into_iter());
-        rltk::console::log(format!("There are {} patterns", patterns.len()));
+        let counted = count_patterns(patterns);
+        crate::console::log(format!("There are {} patterns", counted.len()));
+        counted
+    } else {
+        patterns.into_iter().map(|p| (p, 1)).collect()
+    }
+}
+
+/// Collapses duplicate patterns into one, remembering how often each occurred. The patterns
+/// keep the order they were first seen in, so the same map always gives the same constraints.
+fn count_patterns(patterns: Vec<Vec<PatternTile>>) -> Vec<Pattern> {
+    let mut counted: Vec<Pattern> = Vec::new();
+    let mut index: HashMap<Vec<PatternTile>, usize> = HashMap::new();
+    for pattern in patterns {
+        match index.get(&pattern) {
+            Some(&i) => counted[i].1 += 1,
+            None => {
+                index.insert(pattern.clone(), counted.len());
+                counted.push((pattern, 1));
+            }
+        }
+    }
+    counted
+}
+
+/// Reads every overlapping `n`x`n` window of the map, rather than cutting it into chunks.
+pub fn build_overlapping_patterns(map: &Sample, n: i32, include_flipping: bool) -> Vec<Pattern> {
+    let mut patterns = Vec::new();
+
+    for start_y in 0..=map.height - n {
+        for start_x in 0..=map.width - n {
+            let window = |flip_x: bool, flip_y: bool| {
+                let mut pattern = Vec::new();
+                for y in 0..n {
+                    for x in 0..n {
+                        let tx = if flip_x { n - (x + 1) } else { x };
+                        let ty = if flip_y { n - (y + 1) } else { y };
+                        pattern.push(map.tiles[map.idx(start_x + tx, start_y + ty)]);
+                    }
+                }
+                pattern
+            };
+
+            patterns.push(window(false, false));
+            if include_flipping {
+                patterns.push(window(true, false));
+                patterns.push(window(false, true));
+                patterns.push(window(true, true));
+            }
+        }
     }
 
-    patterns
+    let counted = count_patterns(patterns);
+    crate::console::log(format!("There are {} overlapping patterns", counted.len()));
+    counted
 }
 
 pub fn render_pattern_to_map(
@@ -87,7 +139,7 @@ pub fn render_pattern_to_map(
     for tile_y in 0..chunk_size {
         for tile_x in 0..chunk_size {
             let map_idx = map.xy_idx(start_x + tile_x, start_y + tile_y);
-            map.tiles[map_idx] = chunk.pattern[i];
+            map.tiles[map_idx] = chunk.pattern[i].tile;
             map.visible_tiles[map_idx] = true;
             i += 1;
         }
@@ -119,15 +171,16 @@ pub fn render_pattern_to_map(
     }
 }
 
-pub fn patterns_to_constraints(patterns: Vec<Vec<TileType>>, chunk_size: i32) -> Vec<MapChunk> {
+pub fn patterns_to_constraints(patterns: Vec<Pattern>, chunk_size: i32) -> Vec<MapChunk> {
     // Move into the new constraints object
     let mut constraints: Vec<MapChunk> = Vec::new();
-    for p in patterns {
+    for (p, frequency) in patterns {
         let mut new_chunk = MapChunk {
             pattern: p,
             exits: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
             has_exits: true,
             compatible_with: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
+            frequency,
         };
         for exit in new_chunk.exits.iter_mut() {
             for _i in 0..chunk_size {
@@ -139,28 +192,28 @@ pub fn patterns_to_constraints(patterns: Vec<Vec<TileType>>, chunk_size: i32) ->
         for x in 0..chunk_size {
             // Check for north-bound exits
             let north_idx = tile_idx_in_chunk(chunk_size, x, 0);
-            if new_chunk.pattern[north_idx] == TileType::Floor {
+            if new_chunk.pattern[north_idx].tile == TileType::Floor {
                 new_chunk.exits[0][x as usize] = true;
                 n_exits += 1;
             }
 
             // Check for south-bound exits
             let south_idx = tile_idx_in_chunk(chunk_size, x, chunk_size - 1);
-            if new_chunk.pattern[south_idx] == TileType::Floor {
+            if new_chunk.pattern[south_idx].tile == TileType::Floor {
                 new_chunk.exits[1][x as usize] = true;
                 n_exits += 1;
             }
 
             // Check for west-bound exits
             let west_idx = tile_idx_in_chunk(chunk_size, 0, x);
-            if new_chunk.pattern[west_idx] == TileType::Floor {
+            if new_chunk.pattern[west_idx].tile == TileType::Floor {
                 new_chunk.exits[2][x as usize] = true;
                 n_exits += 1;
             }
 
             // Check for east-bound exits
             let east_idx = tile_idx_in_chunk(chunk_size, chunk_size - 1, x);
-            if new_chunk.pattern[east_idx] == TileType::Floor {
+            if new_chunk.pattern[east_idx].tile == TileType::Floor {
                 new_chunk.exits[3][x as usize] = true;
                 n_exits += 1;
             }
@@ -208,7 +261,7 @@ pub fn patterns_to_constraints(patterns: Vec<Vec<TileType>>, chunk_size: i32) ->
                     if !has_any {
                         // There's no exits on this side, let's match only if the other edge also has no exits
                         let matching_exit_count =
-                            potential.exits[opposite].iter().filter(|a| !**a).count();
+                            potential.exits[opposite].iter().filter(|a| **a).count();
                         if matching_exit_count == 0 {
                             c.compatible_with[direction].push(j);
                         }
@@ -220,3 +273,48 @@ pub fn patterns_to_constraints(patterns: Vec<Vec<TileType>>, chunk_size: i32) ->
 
     constraints
 }
+
+/// Builds constraints for the overlapping model: two patterns may sit side by side if, when
+/// offset by a single tile, every tile they share agrees.
+pub fn overlapping_constraints(patterns: Vec<Pattern>, n: i32) -> Vec<MapChunk> {
+    // North, South, West, East - matching the exit order used by the tiled constraints
+    const OFFSETS: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+    let agrees = |a: &[PatternTile], b: &[PatternTile], (dx, dy): (i32, i32)| {
+        for y in 0..n {
+            for x in 0..n {
+                let (bx, by) = (x - dx, y - dy);
+                if bx < 0 || bx >= n || by < 0 || by >= n {
+                    continue;
+                }
+                if a[tile_idx_in_chunk(n, x, y)] != b[tile_idx_in_chunk(n, bx, by)] {
+                    return false;
+                }
+            }
+        }
+        true
+    };
+
+    let mut constraints: Vec<MapChunk> = patterns
+        .into_iter()
+        .map(|(pattern, frequency)| MapChunk {
+            pattern,
+            exits: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
+            has_exits: false,
+            compatible_with: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
+            frequency,
+        })
+        .collect();
+
+    for i in 0..constraints.len() {
+        for j in 0..constraints.len() {
+            for (direction, offset) in OFFSETS.iter().enumerate() {
+                if agrees(&constraints[i].pattern, &constraints[j].pattern, *offset) {
+                    constraints[i].compatible_with[direction].push(j);
+                }
+            }
+        }
+    }
+
+    constraints
+}
@@ -0,0 +1,449 @@
+mod common;
+mod constraints;
+mod solver;
+
+use super::{
+    generate_voronoi_spawn_regions,
+    prefab_builder::{legend::GlyphSpawn, prefab_library::PREFABS},
+    remove_unreachable_areas_returning_most_distant, spawner, Map, MapBuilder, Position, Rect,
+    TileType, SHOW_MAPGEN_VISUALIZER,
+};
+use bracket_geometry::prelude::Point;
+use bracket_random::prelude::RandomNumberGenerator;
+use common::*;
+use constraints::*;
+use solver::*;
+use std::collections::BTreeMap;
+
+/// How many times we start over from scratch before giving up on a map.
+const MAX_ATTEMPTS: i32 = 5;
+
+/// How the source map is cut into patterns.
+#[derive(PartialEq, Copy, Clone)]
+pub enum WfcMode {
+    /// Non-overlapping chunks whose exits have to line up.
+    Tiled,
+    /// Every small window of the source; neighbours have to agree wherever they overlap.
+    Overlapping,
+}
+
+/// Authored rules the generated map has to obey, whatever the patterns would like.
+#[derive(Clone, Copy)]
+pub enum PinnedConstraint {
+    /// This exact tile must be the given type.
+    FixedTile { x: i32, y: i32, tile: TileType },
+    /// The stairs down go somewhere inside this area, so it must be reachable.
+    StairsIn(Rect),
+    /// The outermost ring of the map is solid wall.
+    WallBorder,
+}
+
+/// Provides a map builder using the Wave Function Collapse algorithm.
+pub struct WaveformCollapseBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    history: Vec<Map>,
+    noise_areas: BTreeMap<i32, Vec<usize>>,
+    derive_from: Option<Box<dyn MapBuilder>>,
+    spawn_list: Vec<(usize, String)>,
+    mode: WfcMode,
+    /// A REX Paint file to learn the patterns from, instead of another builder's output.
+    sample: Option<&'static str>,
+    pinned: Vec<PinnedConstraint>,
+}
+
+impl MapBuilder for WaveformCollapseBuilder {
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Map> {
+        self.history.clone()
+    }
+
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+
+    fn take_snapshot(&mut self) {
+        if SHOW_MAPGEN_VISUALIZER {
+            let mut snapshot = self.map.clone();
+            for v in snapshot.revealed_tiles.iter_mut() {
+                *v = true;
+            }
+            self.history.push(snapshot);
+        }
+    }
+}
+
+impl WaveformCollapseBuilder {
+    pub fn new(
+        new_depth: i32,
+        derive_from: Option<Box<dyn MapBuilder>>,
+        mode: WfcMode,
+    ) -> WaveformCollapseBuilder {
+        WaveformCollapseBuilder {
+            map: Map::new(new_depth),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            history: Vec::new(),
+            noise_areas: BTreeMap::new(),
+            derive_from,
+            spawn_list: Vec::new(),
+            mode,
+            sample: None,
+            pinned: Vec::new(),
+        }
+    }
+
+    pub fn derived_map(new_depth: i32, builder: Box<dyn MapBuilder>) -> WaveformCollapseBuilder {
+        WaveformCollapseBuilder::new(new_depth, Some(builder), WfcMode::Tiled)
+    }
+
+    pub fn overlapping(new_depth: i32, builder: Box<dyn MapBuilder>) -> WaveformCollapseBuilder {
+        WaveformCollapseBuilder::new(new_depth, Some(builder), WfcMode::Overlapping)
+    }
+
+    /// Learns the patterns from a REX Paint sample drawn with the prefab glyphs, so that
+    /// monsters and items in the sample turn up in matching places on the generated map.
+    pub fn from_sample(
+        new_depth: i32,
+        sample: &'static str,
+        mode: WfcMode,
+        pinned: Vec<PinnedConstraint>,
+    ) -> WaveformCollapseBuilder {
+        let mut builder = WaveformCollapseBuilder::new(new_depth, None, mode);
+        builder.sample = Some(sample);
+        builder.pinned = pinned;
+        builder
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
+        let sample = match self.sample {
+            Some(path) => {
+                let sample = Sample::from_rex(path);
+                self.map = sample.to_map(self.depth);
+                self.take_snapshot();
+                sample
+            }
+            None => {
+                let prev_builder = &mut self.derive_from.as_mut().unwrap();
+                prev_builder.build_map(rng);
+                self.history = prev_builder.get_snapshot_history();
+                Sample::from_map(&prev_builder.get_map())
+            }
+        };
+        let source = sample.to_map(self.depth);
+
+        let (chunk_size, stride, constraints) = match self.mode {
+            WfcMode::Tiled => {
+                const CHUNK_SIZE: i32 = 8;
+                let patterns = build_patterns(&sample, CHUNK_SIZE, true, true);
+                let constraints = patterns_to_constraints(patterns, CHUNK_SIZE);
+                (CHUNK_SIZE, CHUNK_SIZE, constraints)
+            }
+            WfcMode::Overlapping => {
+                const PATTERN_SIZE: i32 = 3;
+                let patterns = build_overlapping_patterns(&sample, PATTERN_SIZE, true);
+                let constraints = overlapping_constraints(patterns, PATTERN_SIZE);
+                (PATTERN_SIZE, 1, constraints)
+            }
+        };
+        self.render_tile_gallery(&constraints, chunk_size);
+        self.render_pinned_gallery();
+
+        // Insist on a decent amount of connected open space, compared to what we started with
+        let map_area = (self.map.width * self.map.height) as usize;
+        let sample_area = (sample.width * sample.height) as usize;
+        let min_open = sample.floor_count() * map_area / sample_area / 3;
+        let spawns = match self.solve(&constraints, chunk_size, stride, min_open, rng) {
+            Ok(spawns) => spawns,
+            Err(e) => {
+                // Better a plain map than no map at all
+                crate::console::log(format!("Wave function collapse failed: {}", e));
+                self.map = source;
+                self.take_snapshot();
+                Vec::new()
+            }
+        };
+
+        // Find a starting point; the tile in the biggest open area closest to the middle
+        let center = Point::new(self.map.width / 2, self.map.height / 2);
+        let width = self.map.width;
+        let start_idx = match largest_open_region(&self.map)
+            .into_iter()
+            .min_by_key(|idx| {
+                let x = *idx as i32 % width;
+                let y = *idx as i32 / width;
+                (x - center.x).pow(2) + (y - center.y).pow(2)
+            }) {
+            Some(idx) => idx,
+            None => {
+                // A sample without any floor leaves nowhere to stand, so make room in the middle
+                crate::console::log("Wave function collapse left no open space");
+                let idx = self.map.xy_idx(center.x, center.y);
+                self.map.tiles[idx] = TileType::Floor;
+                idx
+            }
+        };
+        self.starting_position = Position {
+            x: start_idx as i32 % self.map.width,
+            y: start_idx as i32 / self.map.width,
+        };
+        self.take_snapshot();
+
+        // Find all tiles we can reach from the starting point
+        let exit_tile = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.take_snapshot();
+
+        // Place the stairs, as far away as possible within any area they're pinned to
+        let exit_tile = self
+            .stairs_region()
+            .and_then(|region| most_distant_in(&self.map, start_idx, &region))
+            .unwrap_or(exit_tile);
+        self.map.tiles[exit_tile] = TileType::DownStairs;
+        self.take_snapshot();
+
+        // If the sample came with its own inhabitants, use the ones that ended up on the map
+        let spawns: Vec<(usize, String)> = spawns
+            .into_iter()
+            .filter(|(idx, _)| *idx != start_idx && self.map.tiles[*idx] == TileType::Floor)
+            .filter_map(|(idx, spawn)| Some((idx, spawn.resolve(self.depth, rng)?)))
+            .collect();
+        if sample.has_spawns() && !spawns.is_empty() {
+            self.spawn_list = spawns;
+            return;
+        }
+
+        // Now we build a noise map for use in spawning entities later
+        self.noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
+
+        // Spawn the entities
+        for (_, area) in self.noise_areas.iter() {
+            spawner::spawn_region(rng, area, self.depth, &mut self.spawn_list);
+        }
+    }
+
+    fn stairs_region(&self) -> Option<Rect> {
+        self.pinned.iter().find_map(|pin| match pin {
+            PinnedConstraint::StairsIn(region) => Some(*region),
+            _ => None,
+        })
+    }
+
+    /// Applies the pinned constraints to a fresh solver.
+    fn pin(&self, solver: &mut Solver) {
+        for pin in self.pinned.iter() {
+            match pin {
+                PinnedConstraint::FixedTile { x, y, tile } => solver.pin_tile(*x, *y, *tile),
+                PinnedConstraint::WallBorder => {
+                    for y in 0..self.map.height {
+                        for x in 0..self.map.width {
+                            if x == 0
+                                || y == 0
+                                || x == self.map.width - 1
+                                || y == self.map.height - 1
+                            {
+                                solver.pin_tile(x, y, TileType::Wall);
+                            }
+                        }
+                    }
+                }
+                // Checked once the map is finished; there's no one tile to pin
+                PinnedConstraint::StairsIn(_) => {}
+            }
+        }
+    }
+
+    /// Runs the solver until it produces a usable map, starting over when it gets stuck.
+    fn solve(
+        &mut self,
+        constraints: &[MapChunk],
+        chunk_size: i32,
+        stride: i32,
+        min_open: usize,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<Vec<(usize, &'static GlyphSpawn)>, WfcError> {
+        // The overlapping model decides a single tile at a time, so don't snapshot every step
+        let snapshot_every = if stride == 1 { 100 } else { 1 };
+
+        for _ in 0..MAX_ATTEMPTS {
+            self.map = Map::new(self.depth);
+            let mut solver = Solver::new(constraints.to_vec(), chunk_size, stride, &self.map, rng);
+            self.pin(&mut solver);
+
+            let mut steps = 0;
+            let solved = loop {
+                match solver.iteration(rng) {
+                    Ok(true) => break true,
+                    Ok(false) => {
+                        steps += 1;
+                        if steps % snapshot_every == 0 {
+                            solver.render(&mut self.map);
+                            self.take_snapshot();
+                        }
+                    }
+                    Err(e) => {
+                        crate::console::log(format!("Wave function collapse: {}, retrying", e));
+                        break false;
+                    }
+                }
+            };
+
+            if solved {
+                solver.render(&mut self.map);
+                self.wall_off_edges();
+                self.take_snapshot();
+                let open = largest_open_region(&self.map);
+                let reaches_stairs = match self.stairs_region() {
+                    Some(region) => open.iter().any(|idx| {
+                        let x = *idx as i32 % self.map.width;
+                        let y = *idx as i32 / self.map.width;
+                        x >= region.x1 && x < region.x2 && y >= region.y1 && y < region.y2
+                    }),
+                    None => true,
+                };
+                if !open.is_empty() && open.len() >= min_open && reaches_stairs {
+                    return Ok(solver.spawns(&self.map));
+                }
+                crate::console::log("Wave function collapse: map doesn't fit, retrying");
+            }
+        }
+
+        Err(WfcError::Unsolvable {
+            attempts: MAX_ATTEMPTS,
+        })
+    }
+
+    /// Patterns can put floor anywhere, including the very edge of the map.
+    fn wall_off_edges(&mut self) {
+        for y in 0..self.map.height {
+            for x in 0..self.map.width {
+                if x == 0 || y == 0 || x == self.map.width - 1 || y == self.map.height - 1 {
+                    let idx = self.map.xy_idx(x, y);
+                    self.map.tiles[idx] = TileType::Wall;
+                }
+            }
+        }
+    }
+
+    /// Shows the authored constraints: pinned tiles as they'll be, and the stairs area as stairs.
+    fn render_pinned_gallery(&mut self) {
+        if self.pinned.is_empty() {
+            return;
+        }
+        self.map = Map::new(0);
+        for t in self.map.tiles.iter_mut() {
+            *t = TileType::Floor;
+        }
+        for pin in self.pinned.clone() {
+            match pin {
+                PinnedConstraint::FixedTile { x, y, tile } => {
+                    let idx = self.map.xy_idx(x, y);
+                    self.map.tiles[idx] = tile;
+                }
+                PinnedConstraint::StairsIn(region) => {
+                    for y in region.y1..region.y2 {
+                        for x in region.x1..region.x2 {
+                            let idx = self.map.xy_idx(x, y);
+                            self.map.tiles[idx] = TileType::DownStairs;
+                        }
+                    }
+                }
+                PinnedConstraint::WallBorder => self.wall_off_edges(),
+            }
+        }
+        self.take_snapshot();
+    }
+
+    fn render_tile_gallery(&mut self, constraints: &[MapChunk], chunk_size: i32) {
+        self.map = Map::new(0);
+        let mut counter = 0;
+        let mut x = 1;
+        let mut y = 1;
+        while counter < constraints.len() {
+            render_pattern_to_map(&mut self.map, &constraints[counter], chunk_size, x, y);
+
+            x += chunk_size + 1;
+            if x + chunk_size > self.map.width {
+                // Move to the next row
+                x = 1;
+                y += chunk_size + 1;
+
+                if y + chunk_size > self.map.height {
+                    // Move to the next page
+                    self.take_snapshot();
+                    self.map = Map::new(0);
+
+                    x = 1;
+                    y = 1;
+                }
+            }
+
+            counter += 1;
+        }
+        self.take_snapshot();
+    }
+}
+
+/// Finds the reachable floor tile inside a region that is furthest from the start.
+fn most_distant_in(map: &Map, start_idx: usize, region: &Rect) -> Option<usize> {
+    let dijkstra_map = bracket_pathfinding::prelude::DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &[start_idx],
+        map,
+        1000.0,
+    );
+    (region.y1..region.y2)
+        .flat_map(|y| (region.x1..region.x2).map(move |x| (x, y)))
+        .filter(|(x, y)| *x >= 0 && *y >= 0 && *x < map.width && *y < map.height)
+        .map(|(x, y)| map.xy_idx(x, y))
+        .filter(|idx| map.tiles[*idx] == TileType::Floor && dijkstra_map.map[*idx] < f32::MAX)
+        .max_by(|a, b| dijkstra_map.map[*a].total_cmp(&dijkstra_map.map[*b]))
+}
+
+/// Returns the tiles of the biggest connected patch of floor on the map.
+fn largest_open_region(map: &Map) -> Vec<usize> {
+    let mut seen = vec![false; map.tiles.len()];
+    let mut largest = Vec::new();
+
+    for start in 0..map.tiles.len() {
+        if seen[start] || map.tiles[start] != TileType::Floor {
+            continue;
+        }
+
+        let mut region = Vec::new();
+        let mut open = vec![start];
+        seen[start] = true;
+        while let Some(idx) = open.pop() {
+            region.push(idx);
+            let (x, y) = (idx as i32 % map.width, idx as i32 / map.width);
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                if nx < 0 || ny < 0 || nx >= map.width || ny >= map.height {
+                    continue;
+                }
+                let next = map.xy_idx(nx, ny);
+                if !seen[next] && map.tiles[next] == TileType::Floor {
+                    seen[next] = true;
+                    open.push(next);
+                }
+            }
+        }
+
+        if region.len() > largest.len() {
+            largest = region;
+        }
+    }
+
+    largest
+}
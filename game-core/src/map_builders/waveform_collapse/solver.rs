@@ -0,0 +1,365 @@
+use super::{GlyphSpawn, Map, MapChunk, RandomNumberGenerator, TileType};
+use std::fmt;
+
+/// How many decisions the solver may undo before it gives up on an attempt.
+const MAX_BACKTRACKS: i32 = 1000;
+
+/// North, South, West, East - the same order as `MapChunk::compatible_with`.
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+fn opposite(direction: usize) -> usize {
+    direction ^ 1
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WfcError {
+    /// Backtracking ran out of room (or decisions to undo) without finding a solution.
+    Contradiction,
+    /// Every attempt allowed by the builder ended in a contradiction.
+    Unsolvable { attempts: i32 },
+}
+
+impl fmt::Display for WfcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WfcError::Contradiction => write!(f, "the constraints contradict each other"),
+            WfcError::Unsolvable { attempts } => {
+                write!(f, "no solution found after {} attempts", attempts)
+            }
+        }
+    }
+}
+
+/// A choice the solver made, and how much of the trail to unwind to take it back.
+struct Decision {
+    cell: usize,
+    pattern: usize,
+    trail_len: usize,
+}
+
+/// Wave Function Collapse over a grid of cells, each of which ends up holding one pattern.
+/// Cells with the least entropy are collapsed first, every choice is propagated to the
+/// neighbours, and contradictions are resolved by undoing recent choices.
+pub struct Solver {
+    constraints: Vec<MapChunk>,
+    weights: Vec<f64>,
+    weight_log_weights: Vec<f64>,
+    chunk_size: i32,
+    stride: i32,
+    cells_x: usize,
+    cells_y: usize,
+    /// Whether each pattern is still possible in each cell, indexed by `cell * patterns + pattern`.
+    wave: Vec<bool>,
+    /// For each cell and pattern, how many patterns in the neighbour in each direction still
+    /// allow it. Once any of these reaches zero, the pattern is ruled out.
+    support: Vec<[i32; 4]>,
+    counts: Vec<usize>,
+    sum_weights: Vec<f64>,
+    sum_weight_log_weights: Vec<f64>,
+    noise: Vec<f64>,
+    pending: Vec<(usize, usize)>,
+    trail: Vec<(usize, usize)>,
+    decisions: Vec<Decision>,
+    backtracks: i32,
+    consistent: bool,
+}
+
+impl Solver {
+    /// Creates a solver that places `chunk_size` square patterns `stride` tiles apart: the tiled
+    /// model uses a stride of the chunk size, the overlapping model a stride of one.
+    pub fn new(
+        constraints: Vec<MapChunk>,
+        chunk_size: i32,
+        stride: i32,
+        map: &Map,
+        rng: &mut RandomNumberGenerator,
+    ) -> Solver {
+        let cells_x = ((map.width - chunk_size) / stride + 1) as usize;
+        let cells_y = ((map.height - chunk_size) / stride + 1) as usize;
+        let cell_count = cells_x * cells_y;
+        let pattern_count = constraints.len();
+
+        // Common patterns are picked more often, but damped so that the most common of all
+        // (usually solid rock) doesn't crowd everything else out
+        let weights: Vec<f64> = constraints
+            .iter()
+            .map(|c| f64::from(c.frequency.max(1)).sqrt())
+            .collect();
+        let weight_log_weights: Vec<f64> = weights.iter().map(|w| w * w.ln()).collect();
+        let total_weight: f64 = weights.iter().sum();
+        let total_weight_log_weight: f64 = weight_log_weights.iter().sum();
+
+        // How many patterns allow each pattern to sit in each direction from them
+        let mut incoming = vec![[0; 4]; pattern_count];
+        for c in constraints.iter() {
+            for (direction, compatible) in c.compatible_with.iter().enumerate() {
+                for j in compatible.iter() {
+                    incoming[*j][opposite(direction)] += 1;
+                }
+            }
+        }
+
+        let mut solver = Solver {
+            constraints,
+            weights,
+            weight_log_weights,
+            chunk_size,
+            stride,
+            cells_x,
+            cells_y,
+            wave: vec![true; cell_count * pattern_count],
+            support: Vec::with_capacity(cell_count * pattern_count),
+            counts: vec![pattern_count; cell_count],
+            sum_weights: vec![total_weight; cell_count],
+            sum_weight_log_weights: vec![total_weight_log_weight; cell_count],
+            // A little noise breaks ties between cells of equal entropy
+            noise: (0..cell_count).map(|_| rng.rand::<f64>() * 1e-6).collect(),
+            pending: Vec::new(),
+            trail: Vec::new(),
+            decisions: Vec::new(),
+            backtracks: 0,
+            consistent: true,
+        };
+
+        for cell in 0..cell_count {
+            for (pattern, incoming) in incoming.iter().enumerate() {
+                solver.support.push(*incoming);
+                // Patterns that nothing can sit next to are only usable at the edges
+                for (direction, count) in incoming.iter().enumerate() {
+                    if *count == 0 && solver.neighbor(cell, direction).is_some() {
+                        solver.pending.push((cell, pattern));
+                    }
+                }
+            }
+        }
+        solver.consistent = solver.propagate();
+
+        solver
+    }
+
+    fn neighbor(&self, cell: usize, direction: usize) -> Option<usize> {
+        let x = (cell % self.cells_x) as i32 + DIRECTIONS[direction].0;
+        let y = (cell / self.cells_x) as i32 + DIRECTIONS[direction].1;
+        if x < 0 || y < 0 || x >= self.cells_x as i32 || y >= self.cells_y as i32 {
+            None
+        } else {
+            Some(y as usize * self.cells_x + x as usize)
+        }
+    }
+
+    fn possible(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let pattern_count = self.constraints.len();
+        (0..pattern_count).filter(move |p| self.wave[cell * pattern_count + p])
+    }
+
+    fn entropy(&self, cell: usize) -> f64 {
+        let sum = self.sum_weights[cell];
+        sum.ln() - self.sum_weight_log_weights[cell] / sum + self.noise[cell]
+    }
+
+    /// Rules a pattern out for a cell, remembering it so it can be undone, and withdraws its
+    /// support from the neighbours. Returns false if the cell has nothing left.
+    fn ban(&mut self, cell: usize, pattern: usize) -> bool {
+        let pattern_count = self.constraints.len();
+        self.wave[cell * pattern_count + pattern] = false;
+        self.counts[cell] -= 1;
+        self.sum_weights[cell] -= self.weights[pattern];
+        self.sum_weight_log_weights[cell] -= self.weight_log_weights[pattern];
+        self.trail.push((cell, pattern));
+
+        for direction in 0..4 {
+            let Some(neighbor) = self.neighbor(cell, direction) else {
+                continue;
+            };
+            for other in self.constraints[pattern].compatible_with[direction].iter() {
+                let idx = neighbor * pattern_count + other;
+                let support = &mut self.support[idx][opposite(direction)];
+                *support -= 1;
+                if *support == 0 && self.wave[idx] {
+                    self.pending.push((neighbor, *other));
+                }
+            }
+        }
+
+        self.counts[cell] > 0
+    }
+
+    fn unwind(&mut self, trail_len: usize) {
+        // Anything still queued was worked out from the state being thrown away
+        self.pending.clear();
+        let pattern_count = self.constraints.len();
+        while self.trail.len() > trail_len {
+            let (cell, pattern) = self.trail.pop().unwrap();
+            self.wave[cell * pattern_count + pattern] = true;
+            self.counts[cell] += 1;
+            self.sum_weights[cell] += self.weights[pattern];
+            self.sum_weight_log_weights[cell] += self.weight_log_weights[pattern];
+
+            for direction in 0..4 {
+                let Some(neighbor) = self.neighbor(cell, direction) else {
+                    continue;
+                };
+                for other in self.constraints[pattern].compatible_with[direction].iter() {
+                    self.support[neighbor * pattern_count + other][opposite(direction)] += 1;
+                }
+            }
+        }
+    }
+
+    /// Works through the patterns that have lost all support. Returns false on a contradiction.
+    fn propagate(&mut self) -> bool {
+        let pattern_count = self.constraints.len();
+        while let Some((cell, pattern)) = self.pending.pop() {
+            if self.wave[cell * pattern_count + pattern] && !self.ban(cell, pattern) {
+                self.pending.clear();
+                return false;
+            }
+        }
+        true
+    }
+
+    fn pick_pattern(&self, cell: usize, rng: &mut RandomNumberGenerator) -> usize {
+        let mut roll = rng.rand::<f64>() * self.sum_weights[cell];
+        let mut last = 0;
+        for pattern in self.possible(cell) {
+            last = pattern;
+            roll -= self.weights[pattern];
+            if roll <= 0.0 {
+                return pattern;
+            }
+        }
+        last
+    }
+
+    /// Collapses one cell. Returns `Ok(true)` once every cell is decided.
+    pub fn iteration(&mut self, rng: &mut RandomNumberGenerator) -> Result<bool, WfcError> {
+        if !self.consistent {
+            return Err(WfcError::Contradiction);
+        }
+
+        let mut next = None;
+        let mut lowest = f64::MAX;
+        for cell in 0..self.counts.len() {
+            if self.counts[cell] > 1 {
+                let entropy = self.entropy(cell);
+                if entropy < lowest {
+                    lowest = entropy;
+                    next = Some(cell);
+                }
+            }
+        }
+        let Some(cell) = next else {
+            return Ok(true);
+        };
+
+        let pattern = self.pick_pattern(cell, rng);
+        self.decisions.push(Decision {
+            cell,
+            pattern,
+            trail_len: self.trail.len(),
+        });
+        let others: Vec<usize> = self.possible(cell).filter(|p| *p != pattern).collect();
+        for other in others {
+            self.ban(cell, other);
+        }
+        let mut consistent = self.propagate();
+
+        // Undo recent choices until things make sense again
+        while !consistent {
+            self.backtracks += 1;
+            if self.backtracks > MAX_BACKTRACKS {
+                self.consistent = false;
+                return Err(WfcError::Contradiction);
+            }
+            let Some(decision) = self.decisions.pop() else {
+                self.consistent = false;
+                return Err(WfcError::Contradiction);
+            };
+            self.unwind(decision.trail_len);
+            consistent = self.ban(decision.cell, decision.pattern) && self.propagate();
+        }
+
+        Ok(false)
+    }
+
+    /// Draws every decided cell onto the map.
+    pub fn render(&self, map: &mut Map) {
+        for cell in 0..self.counts.len() {
+            if self.counts[cell] != 1 {
+                continue;
+            }
+            let pattern = &self.constraints[self.possible(cell).next().unwrap()].pattern;
+            let left_x = (cell % self.cells_x) as i32 * self.stride;
+            let top_y = (cell / self.cells_x) as i32 * self.stride;
+
+            let mut i: usize = 0;
+            for y in top_y..top_y + self.chunk_size {
+                for x in left_x..left_x + self.chunk_size {
+                    let mapidx = map.xy_idx(x, y);
+                    map.tiles[mapidx] = pattern[i].tile;
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    /// Whatever the decided patterns have standing on them, by map index.
+    pub fn spawns(&self, map: &Map) -> Vec<(usize, &'static GlyphSpawn)> {
+        let mut spawns: Vec<(usize, &'static GlyphSpawn)> = Vec::new();
+        for cell in 0..self.counts.len() {
+            if self.counts[cell] != 1 {
+                continue;
+            }
+            let pattern = &self.constraints[self.possible(cell).next().unwrap()].pattern;
+            let left_x = (cell % self.cells_x) as i32 * self.stride;
+            let top_y = (cell / self.cells_x) as i32 * self.stride;
+
+            let mut i: usize = 0;
+            for y in top_y..top_y + self.chunk_size {
+                for x in left_x..left_x + self.chunk_size {
+                    if let Some(spawn) = pattern[i].spawn {
+                        spawns.push((map.xy_idx(x, y), spawn));
+                    }
+                    i += 1;
+                }
+            }
+        }
+
+        // Overlapping patterns will all report the same spawn
+        spawns.sort_unstable_by_key(|(idx, _)| *idx);
+        spawns.dedup_by_key(|(idx, _)| *idx);
+        spawns
+    }
+
+    /// Forces a map tile to be the given type, by ruling out every pattern that would put
+    /// something else there.
+    pub fn pin_tile(&mut self, x: i32, y: i32, tile: TileType) {
+        if !self.consistent {
+            return;
+        }
+        for cell in 0..self.counts.len() {
+            let offset_x = x - (cell % self.cells_x) as i32 * self.stride;
+            let offset_y = y - (cell / self.cells_x) as i32 * self.stride;
+            if offset_x < 0
+                || offset_y < 0
+                || offset_x >= self.chunk_size
+                || offset_y >= self.chunk_size
+            {
+                continue;
+            }
+            let i = (offset_y * self.chunk_size + offset_x) as usize;
+            let banned: Vec<usize> = self
+                .possible(cell)
+                .filter(|p| self.constraints[*p].pattern[i].tile != tile)
+                .collect();
+            for pattern in banned {
+                if !self.ban(cell, pattern) {
+                    self.consistent = false;
+                    self.pending.clear();
+                    return;
+                }
+            }
+        }
+        self.consistent = self.propagate();
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::{self, File},
+    io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{Equipped, InBackpack, Name, Player, RunStats},
+    gamelog::GameLog,
+    map::Map,
+};
+
+const HIGH_SCORE_FILE_NAME: &str = "./highscores.json";
+const MAX_HIGH_SCORES: usize = 10;
+/// How many of the last log entries make it into the morgue file.
+const MORGUE_LOG_LINES: usize = 10;
+
+/// How a run came to an end.
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum Outcome {
+    Died,
+    Won,
+}
+
+/// Everything worth remembering about a finished run.
+#[derive(Clone, Resource)]
+pub struct RunSummary {
+    pub outcome: Outcome,
+    pub depth: i32,
+    pub turns: i32,
+    pub kills: BTreeMap<String, i32>,
+    pub items_used: BTreeMap<String, i32>,
+    pub killed_by: Option<String>,
+    /// What the player was carrying, equipped items first.
+    pub belongings: Vec<String>,
+    pub last_messages: Vec<String>,
+    pub score: i32,
+    /// Where the morgue file went, if it could be written.
+    pub morgue_file: Option<String>,
+    /// The run's place in the high score table, counting from zero, if it made it in.
+    pub rank: Option<usize>,
+}
+
+impl RunSummary {
+    /// Gathers the summary from the player's stats and surroundings as the run ends.
+    pub fn from_world(world: &mut World, outcome: Outcome) -> RunSummary {
+        let (player, stats) = world
+            .query_filtered::<(Entity, Option<&RunStats>), With<Player>>()
+            .single(world);
+        let stats = stats.cloned().unwrap_or_default();
+
+        let mut belongings: Vec<String> = world
+            .query::<(&Equipped, &Name)>()
+            .iter(world)
+            .filter(|(equipped, _)| equipped.owner == player)
+            .map(|(_, name)| format!("{} (equipped)", name.name))
+            .collect();
+        belongings.extend(
+            world
+                .query::<(&InBackpack, &Name)>()
+                .iter(world)
+                .filter(|(pack, _)| pack.owner == player)
+                .map(|(_, name)| name.name.clone()),
+        );
+
+        let log = &world.resource::<GameLog>().entries;
+        let last_messages = log[log.len().saturating_sub(MORGUE_LOG_LINES)..].to_vec();
+
+        let mut summary = RunSummary {
+            outcome,
+            depth: world.resource::<Map>().depth,
+            turns: stats.turns,
+            kills: stats.kills,
+            items_used: stats.items_used,
+            killed_by: stats.killed_by,
+            belongings,
+            last_messages,
+            score: 0,
+            morgue_file: None,
+            rank: None,
+        };
+        summary.score = summary.calculate_score();
+        summary
+    }
+
+    /// A hundred points for every level descended, ten for every kill, and a thousand for
+    /// finding the amulet.
+    fn calculate_score(&self) -> i32 {
+        let kills: i32 = self.kills.values().sum();
+        let bonus = match self.outcome {
+            Outcome::Won => 1000,
+            Outcome::Died => 0,
+        };
+        self.depth * 100 + kills * 10 + bonus
+    }
+
+    /// One line saying how the run ended.
+    pub fn fate(&self) -> String {
+        match self.outcome {
+            Outcome::Won => format!("Recovered the Amulet of the Deep on depth {}", self.depth),
+            Outcome::Died => format!(
+                "Killed by {} on depth {}",
+                self.killed_by.as_deref().unwrap_or("something unknown"),
+                self.depth
+            ),
+        }
+    }
+
+    /// Renders the plain-text morgue file.
+    pub fn morgue_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "Rusty Roguelike morgue file");
+        let _ = writeln!(text, "===========================");
+        let _ = writeln!(text);
+        let _ = writeln!(text, "{}, after {} turns.", self.fate(), self.turns);
+        let _ = writeln!(text, "Score: {}", self.score);
+
+        for (title, counts) in [("Kills", &self.kills), ("Items used", &self.items_used)] {
+            let _ = writeln!(text);
+            let _ = writeln!(text, "{}", title);
+            let _ = writeln!(text, "{}", "-".repeat(title.len()));
+            if counts.is_empty() {
+                let _ = writeln!(text, "  None");
+            }
+            for (name, count) in counts.iter() {
+                let _ = writeln!(text, "  {:>3} {}", count, name);
+            }
+        }
+
+        for (title, lines) in [
+            ("Belongings", &self.belongings),
+            ("Last messages", &self.last_messages),
+        ] {
+            let _ = writeln!(text);
+            let _ = writeln!(text, "{}", title);
+            let _ = writeln!(text, "{}", "-".repeat(title.len()));
+            if lines.is_empty() {
+                let _ = writeln!(text, "  None");
+            }
+            for line in lines.iter() {
+                let _ = writeln!(text, "  {}", line);
+            }
+        }
+
+        text
+    }
+
+    /// Writes the morgue file, returning its name.
+    pub fn write_morgue(&self) -> io::Result<String> {
+        let file_name = format!("./morgue-{}.txt", timestamp());
+        fs::write(&file_name, self.morgue_text())?;
+        Ok(file_name)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: i32,
+    pub fate: String,
+    pub depth: i32,
+    pub turns: i32,
+    /// When the run ended, in seconds since the epoch.
+    pub timestamp: u64,
+}
+
+/// The best runs so far, highest score first.
+#[derive(Default, Resource, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Reads the table from disk. A missing file is an empty table, and so is a broken one, once
+    /// we've complained about it.
+    pub fn load() -> HighScores {
+        if !Path::new(HIGH_SCORE_FILE_NAME).exists() {
+            return HighScores::default();
+        }
+        let scores = File::open(HIGH_SCORE_FILE_NAME)
+            .map_err(|e| e.to_string())
+            .and_then(|reader| serde_json::from_reader(reader).map_err(|e| e.to_string()));
+        match scores {
+            Ok(scores) => scores,
+            Err(e) => {
+                crate::console::log(format!("Unable to read high scores: {}", e));
+                HighScores::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let writer = File::create(HIGH_SCORE_FILE_NAME)?;
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Adds a score to the table, returning where it ranks if it was good enough to stay.
+    pub fn add(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| e.score < entry.score)
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+/// Sums up the run that just ended: writes the morgue file, enters the score in the high score
+/// table, and leaves the summary in the world for the game over screen.
+pub fn record_run(world: &mut World, outcome: Outcome) {
+    let mut summary = RunSummary::from_world(world, outcome);
+
+    match summary.write_morgue() {
+        Ok(file_name) => summary.morgue_file = Some(file_name),
+        Err(e) => crate::console::log(format!("Unable to write morgue file: {}", e)),
+    }
+
+    let mut scores = HighScores::load();
+    summary.rank = scores.add(HighScore {
+        score: summary.score,
+        fate: summary.fate(),
+        depth: summary.depth,
+        turns: summary.turns,
+        timestamp: timestamp(),
+    });
+    if summary.rank.is_some() {
+        if let Err(e) = scores.save() {
+            crate::console::log(format!("Unable to save high scores: {}", e));
+        }
+    }
+
+    world.insert_resource(summary);
+}
+
+pub fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.as_secs())
+}
@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+
+use bevy_ecs::prelude::*;
+use bracket_random::prelude::RandomNumberGenerator;
+
+use crate::{
+    components::{
+        EntityMoved, HeardNoise, Monster, Name, Perception, Player, Position, Stealth, Unaware,
+    },
+    gamelog::GameLog,
+    map::{Map, TileType},
+    stealth::perception_check,
+};
+
+/// How far the player's footsteps carry, before stealth muffles them.
+const FOOTSTEP_NOISE: i32 = 6;
+/// The clash of a melee attack.
+pub const COMBAT_NOISE: i32 = 10;
+/// A trap snapping shut, or going off.
+pub const TRAP_NOISE: i32 = 12;
+/// A fireball, or anything else that blows up.
+pub const EXPLOSION_NOISE: i32 = 20;
+
+/// Something made a noise at (x, y). It carries `loudness` steps through open ground, and walls
+/// stop it dead.
+#[derive(Event)]
+pub struct NoiseEvent {
+    pub x: i32,
+    pub y: i32,
+    pub loudness: i32,
+}
+
+/// The player makes a little noise with every step; the stealthier they are, the less.
+pub fn footstep_system(
+    movers: Query<(&Position, Option<&Stealth>), (With<Player>, With<EntityMoved>)>,
+    mut noise_writer: EventWriter<NoiseEvent>,
+) {
+    for (pos, stealth) in movers.iter() {
+        let loudness = FOOTSTEP_NOISE - stealth.map_or(0, |s| s.value);
+        if loudness > 0 {
+            noise_writer.send(NoiseEvent {
+                x: pos.x,
+                y: pos.y,
+                loudness,
+            });
+        }
+    }
+}
+
+/// Spreads each noise out through the walkable tiles around it, and sends any monster that
+/// hears it off to take a look. Monsters that haven't noticed anything yet only pick it up if
+/// they pass a perception check, which is easier the louder the noise still is when it arrives.
+pub fn noise_system(
+    mut commands: Commands,
+    mut reader: EventReader<NoiseEvent>,
+    listeners: Query<
+        (
+            Entity,
+            &Position,
+            &Name,
+            Option<&Perception>,
+            Option<&Unaware>,
+        ),
+        With<Monster>,
+    >,
+    map: Res<Map>,
+    mut log: ResMut<GameLog>,
+    mut rng: NonSendMut<RandomNumberGenerator>,
+) {
+    for noise in reader.read() {
+        let heard = propagate(&map, map.xy_idx(noise.x, noise.y), noise.loudness);
+        for (monster, pos, name, perception, unaware) in listeners.iter() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            let Some(distance) = heard[idx] else {
+                continue;
+            };
+
+            if let Some(unaware) = unaware {
+                let margin = noise.loudness - distance;
+                if !perception_check(&mut rng, perception, unaware, None, margin) {
+                    continue;
+                }
+                if map.visible_tiles[idx] {
+                    if unaware.asleep {
+                        log.entries.push(format!("{} wakes up!", name.name));
+                    } else {
+                        log.entries
+                            .push(format!("{} pricks up its ears.", name.name));
+                    }
+                }
+                commands.entity(monster).remove::<Unaware>();
+            }
+
+            commands.entity(monster).insert(HeardNoise {
+                x: noise.x,
+                y: noise.y,
+            });
+        }
+    }
+}
+
+/// Floods out from `start` a step at a time, returning how many steps away each tile within
+/// `loudness` steps is. Creatures don't muffle sound, so only walls get in the way.
+fn propagate(map: &Map, start: usize, loudness: i32) -> Vec<Option<i32>> {
+    let mut heard = vec![None; map.tiles.len()];
+    let mut open = VecDeque::new();
+    heard[start] = Some(0);
+    open.push_back((start, 0));
+
+    while let Some((idx, distance)) = open.pop_front() {
+        if distance >= loudness {
+            continue;
+        }
+        let x = idx as i32 % map.width;
+        let y = idx as i32 / map.width;
+        for (dx, dy) in [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ] {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || nx >= map.width || ny < 0 || ny >= map.height {
+                continue;
+            }
+            let next = map.xy_idx(nx, ny);
+            if heard[next].is_none() && map.tiles[next] != TileType::Wall {
+                heard[next] = Some(distance + 1);
+                open.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    heard
+}
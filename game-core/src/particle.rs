@@ -1,8 +1,8 @@
 use bevy_ecs::prelude::*;
-use rltk::FontCharType;
 
 use crate::{
     components::{ParticleLifetime, Position, Renderable},
+    cp437::FontCharType,
     FrameTime,
 };
 
@@ -11,7 +11,7 @@ struct ParticleRequest {
     y: i32,
     fg: (u8, u8, u8),
     bg: (u8, u8, u8),
-    glyph: rltk::FontCharType,
+    glyph: FontCharType,
     lifetime: f32,
 }
 
@@ -20,6 +20,12 @@ pub struct ParticleBuilder {
     requests: Vec<ParticleRequest>,
 }
 
+impl Default for ParticleBuilder {
+    fn default() -> ParticleBuilder {
+        ParticleBuilder::new()
+    }
+}
+
 impl ParticleBuilder {
     pub fn new() -> ParticleBuilder {
         ParticleBuilder {
@@ -1,21 +1,19 @@
 use bevy_ecs::prelude::*;
 use bevy_ecs::system::EntityCommands;
-use lazy_static::lazy_static;
-use rltk::VirtualKeyCode;
-use std::cmp::{max, min};
-use std::collections::HashMap;
 
+use crate::actions::{awaiting_player, PendingAction, PlayerAction};
 use crate::components::{
-    EntityMoved, HungerClock, HungerState, Item, Monster, Player, Waiting, WantsToMelee,
-    WantsToPickupItem,
+    EntityMoved, HungerClock, HungerState, Item, Monster, Player, RunStats, Waiting,
+    WantsToDropItem, WantsToMelee, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
 };
 use crate::gamelog::GameLog;
 use crate::map::TileType;
+use crate::progression::PROGRESSION;
+use crate::RunState;
 use crate::{
     components::{CombatStats, Position, Viewshed},
     map::Map,
 };
-use crate::{Key, RunState};
 
 fn try_move_player(
     mut commands: EntityCommands,
@@ -29,15 +27,16 @@ fn try_move_player(
     let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
     for target in map.tile_content[destination_idx].iter() {
-        if enemy_query.contains(*target) {
+        // Standing still mustn't turn into an attack on yourself
+        if *target != commands.id() && enemy_query.contains(*target) {
             commands.insert(WantsToMelee { target: *target });
             return true;
         }
     }
 
     if !map.blocked[destination_idx] {
-        pos.x = min(79, max(0, pos.x + delta_x));
-        pos.y = min(49, max(0, pos.y + delta_y));
+        pos.x = (pos.x + delta_x).clamp(0, 79);
+        pos.y = (pos.y + delta_y).clamp(0, 49);
         commands.insert(EntityMoved {});
 
         viewshed.dirty = true;
@@ -47,36 +46,8 @@ fn try_move_player(
     false
 }
 
-struct Translation(i32, i32);
-lazy_static! {
-    static ref MOVEMENT_KEYS: HashMap<VirtualKeyCode, Translation> = {
-        let mut m = HashMap::new();
-        m.insert(VirtualKeyCode::Left, Translation(-1, 0));
-        m.insert(VirtualKeyCode::Numpad4, Translation(-1, 0));
-        m.insert(VirtualKeyCode::H, Translation(-1, 0));
-        m.insert(VirtualKeyCode::Right, Translation(1, 0));
-        m.insert(VirtualKeyCode::Numpad6, Translation(1, 0));
-        m.insert(VirtualKeyCode::L, Translation(1, 0));
-        m.insert(VirtualKeyCode::Up, Translation(0, -1));
-        m.insert(VirtualKeyCode::Numpad8, Translation(0, -1));
-        m.insert(VirtualKeyCode::K, Translation(0, -1));
-        m.insert(VirtualKeyCode::Down, Translation(0, 1));
-        m.insert(VirtualKeyCode::Numpad2, Translation(0, 1));
-        m.insert(VirtualKeyCode::J, Translation(0, 1));
-        m.insert(VirtualKeyCode::Numpad9, Translation(1, -1));
-        m.insert(VirtualKeyCode::U, Translation(1, -1));
-        m.insert(VirtualKeyCode::Numpad7, Translation(-1, -1));
-        m.insert(VirtualKeyCode::Y, Translation(-1, -1));
-        m.insert(VirtualKeyCode::Numpad3, Translation(1, 1));
-        m.insert(VirtualKeyCode::N, Translation(1, 1));
-        m.insert(VirtualKeyCode::Numpad1, Translation(-1, 1));
-        m.insert(VirtualKeyCode::B, Translation(-1, 1));
-        m
-    };
-}
-
 fn get_item(
-    mut commands: EntityCommands,
+    commands: &mut EntityCommands,
     items: Query<(Entity, &Position), With<Item>>,
     player: Entity,
     player_pos: &Position,
@@ -94,26 +65,41 @@ fn get_item(
         None => {
             log.entries
                 .push("There is nothing here to pick up.".to_string());
-            return false;
+            false
         }
         Some(item) => {
             commands.insert(WantsToPickupItem {
                 collected_by: player,
                 item,
             });
-            return true;
+            true
         }
     }
 }
 
 pub fn try_next_level(player_pos: &Position, map: Res<Map>, mut log: ResMut<GameLog>) -> bool {
     let player_idx = map.xy_idx(player_pos.x, player_pos.y);
-    if map.tiles[player_idx] == TileType::DownStairs {
-        true
-    } else {
+    if map.tiles[player_idx] != TileType::DownStairs {
         log.entries
             .push("There is no way down from here.".to_string());
         false
+    } else if map.depth >= PROGRESSION.final_depth {
+        log.entries
+            .push("This is as deep as the dungeon goes.".to_string());
+        false
+    } else {
+        true
+    }
+}
+
+/// Counts the player's turns for the morgue file.
+pub fn turn_counter_system(state: Res<RunState>, mut stats: Query<&mut RunStats, With<Player>>) {
+    if *state != RunState::PlayerTurn {
+        return;
+    }
+
+    for mut stats in stats.iter_mut() {
+        stats.turns += 1;
     }
 }
 
@@ -152,64 +138,73 @@ pub fn waiting_system(
     }
 }
 
-pub fn player_input_system(
+/// Carries out the player's pending action, once the game is ready for it.
+pub fn player_action_system(
     mut commands: Commands,
     mut players: Query<(Entity, &mut Position, &mut Viewshed), (With<Player>, Without<Item>)>,
     enemies: Query<&CombatStats>,
     items: Query<(Entity, &Position), With<Item>>,
     map: Res<Map>,
-    key: NonSend<Key>,
+    mut pending: ResMut<PendingAction>,
     mut state: ResMut<RunState>,
     mut log: ResMut<GameLog>,
 ) {
-    if *state != RunState::AwaitingInput {
+    if !awaiting_player(*state) {
         return;
     }
+    let Some(action) = pending.0.take() else {
+        return;
+    };
 
     let (player, mut pos, mut viewshed) = players.single_mut();
     let mut player_commands = commands.entity(player);
-    let mut new_state = RunState::AwaitingInput;
+    let mut new_state = *state;
 
-    if let Some(k) = *key {
-        if let Some(delta) = MOVEMENT_KEYS.get(&k) {
+    match action {
+        PlayerAction::Move(delta_x, delta_y) => {
             if try_move_player(
                 player_commands,
                 enemies,
                 &mut pos,
                 &mut viewshed,
                 &map,
-                delta.0,
-                delta.1,
+                delta_x,
+                delta_y,
             ) {
                 new_state = RunState::PlayerTurn;
             }
-        } else {
-            match k {
-                VirtualKeyCode::G => {
-                    if get_item(player_commands, items, player, &pos, &mut log) {
-                        new_state = RunState::PlayerTurn;
-                    }
-                }
-                VirtualKeyCode::I => new_state = RunState::ShowInventory,
-                VirtualKeyCode::D => new_state = RunState::ShowDropItem,
-                VirtualKeyCode::R => new_state = RunState::ShowRemoveItem,
-
-                VirtualKeyCode::Escape => new_state = RunState::SaveGame,
-
-                VirtualKeyCode::Period => {
-                    if try_next_level(pos.as_ref(), map, log) {
-                        new_state = RunState::NextLevel;
-                    }
-                }
-
-                VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => {
-                    player_commands.insert(Waiting {});
-                    new_state = RunState::PlayerTurn;
-                }
-
-                _ => {}
+        }
+        PlayerAction::Pickup => {
+            if get_item(&mut player_commands, items, player, &pos, &mut log) {
+                new_state = RunState::PlayerTurn;
+            }
+        }
+        PlayerAction::Descend => {
+            if try_next_level(pos.as_ref(), map, log) {
+                new_state = RunState::NextLevel;
             }
         }
+        PlayerAction::Wait => {
+            player_commands.insert(Waiting {});
+            new_state = RunState::PlayerTurn;
+        }
+        PlayerAction::UseItem(item, target) => {
+            player_commands.insert(WantsToUseItem { item, target });
+            new_state = RunState::PlayerTurn;
+        }
+        PlayerAction::Drop(item) => {
+            player_commands.insert(WantsToDropItem { item });
+            new_state = RunState::PlayerTurn;
+        }
+        PlayerAction::Remove(item) => {
+            player_commands.insert(WantsToRemoveItem { item });
+            new_state = RunState::PlayerTurn;
+        }
+        PlayerAction::ShowInventory => new_state = RunState::ShowInventory,
+        PlayerAction::ShowDropItem => new_state = RunState::ShowDropItem,
+        PlayerAction::ShowRemoveItem => new_state = RunState::ShowRemoveItem,
+        PlayerAction::ShowHelp => new_state = RunState::ShowHelp,
+        PlayerAction::SaveAndQuit => new_state = RunState::SaveGame,
     }
 
     *state = new_state;
@@ -0,0 +1,190 @@
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::{
+    map_builders::{is_level_prefab, BUILDER_NAMES},
+    rex_assets::REX_MAPS,
+    theme::Theme,
+};
+
+const PROGRESSION_JSON: &str = include_str!("../../resources/progression.json");
+/// How brightly lit a level is when the progression doesn't say.
+const DEFAULT_AMBIENT: f32 = 0.5;
+
+lazy_static! {
+    pub static ref PROGRESSION: Progression =
+        Progression::from_json(PROGRESSION_JSON).expect("Invalid progression");
+}
+
+#[derive(Deserialize)]
+struct ProgressionFile {
+    final_depth: i32,
+    bands: Vec<BandDef>,
+    #[serde(default)]
+    fixed: Vec<FixedLevelDef>,
+}
+
+#[derive(Deserialize)]
+struct BandDef {
+    first: i32,
+    last: i32,
+    theme: String,
+    #[serde(default)]
+    builders: Vec<String>,
+    #[serde(default = "default_ambient")]
+    ambient: f32,
+}
+
+fn default_ambient() -> f32 {
+    DEFAULT_AMBIENT
+}
+
+#[derive(Deserialize)]
+struct FixedLevelDef {
+    depth: i32,
+    prefab: Option<String>,
+    rex: Option<String>,
+    message: Option<String>,
+}
+
+/// A run of consecutive depths that share a theme and a set of builders.
+pub struct Band {
+    pub first: i32,
+    pub last: i32,
+    pub theme: Theme,
+    /// Names of the builders this band draws from; empty to use the theme's own.
+    pub builders: Vec<String>,
+    /// How much light reaches every tile without any light sources, from 0 (pitch dark) to 1
+    /// (broad daylight).
+    pub ambient: f32,
+}
+
+/// How an authored level is built.
+pub enum FixedLayout {
+    /// A level prefab from the prefab library, by name.
+    Prefab(String),
+    /// A REX Paint file drawn with the prefab glyphs.
+    Rex(String),
+}
+
+/// A level that is always the same, rather than randomly generated.
+pub struct FixedLevel {
+    pub layout: FixedLayout,
+    /// Shown to the player on arrival.
+    pub message: Option<String>,
+}
+
+/// The shape of the whole dungeon: which themes and builders each depth uses, which levels are
+/// authored by hand, and how deep the player has to go.
+pub struct Progression {
+    pub final_depth: i32,
+    bands: Vec<Band>,
+    fixed: Vec<(i32, FixedLevel)>,
+}
+
+impl Progression {
+    pub fn from_json(json: &str) -> Result<Progression, String> {
+        let file: ProgressionFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut bands = Vec::new();
+        for band in file.bands {
+            if band.first > band.last {
+                return Err(format!("Band {}-{} runs backwards", band.first, band.last));
+            }
+            let theme = Theme::from_name(&band.theme).ok_or_else(|| {
+                format!(
+                    "Band {}-{} has unknown theme {}",
+                    band.first, band.last, band.theme
+                )
+            })?;
+            if !(0.0..=1.0).contains(&band.ambient) {
+                return Err(format!(
+                    "Band {}-{} has ambient light {}, outside 0 to 1",
+                    band.first, band.last, band.ambient
+                ));
+            }
+            for builder in band.builders.iter() {
+                if !BUILDER_NAMES.contains(&builder.as_str()) {
+                    return Err(format!(
+                        "Band {}-{} uses unknown builder {}",
+                        band.first, band.last, builder
+                    ));
+                }
+            }
+            bands.push(Band {
+                first: band.first,
+                last: band.last,
+                theme,
+                builders: band.builders,
+                ambient: band.ambient,
+            });
+        }
+
+        let mut fixed = Vec::new();
+        for level in file.fixed {
+            let layout = match (level.prefab, level.rex) {
+                (Some(prefab), None) => FixedLayout::Prefab(prefab),
+                (None, Some(rex)) => FixedLayout::Rex(rex),
+                _ => {
+                    return Err(format!(
+                        "Fixed level at depth {} needs exactly one of prefab or rex",
+                        level.depth
+                    ))
+                }
+            };
+            match &layout {
+                FixedLayout::Prefab(name) if !is_level_prefab(name) => {
+                    return Err(format!(
+                        "Fixed level at depth {} uses unknown level prefab {}",
+                        level.depth, name
+                    ));
+                }
+                FixedLayout::Rex(path) if !REX_MAPS.contains(&path.as_str()) => {
+                    return Err(format!(
+                        "Fixed level at depth {} uses REX map {}, which isn't embedded",
+                        level.depth, path
+                    ));
+                }
+                _ => {}
+            }
+            if fixed.iter().any(|(depth, _)| *depth == level.depth) {
+                return Err(format!("Depth {} has two fixed levels", level.depth));
+            }
+            fixed.push((
+                level.depth,
+                FixedLevel {
+                    layout,
+                    message: level.message,
+                },
+            ));
+        }
+
+        Ok(Progression {
+            final_depth: file.final_depth,
+            bands,
+            fixed,
+        })
+    }
+
+    /// The band a depth falls in, if any; depths outside every band are plain dungeon.
+    pub fn band(&self, depth: i32) -> Option<&Band> {
+        self.bands
+            .iter()
+            .find(|b| depth >= b.first && depth <= b.last)
+    }
+
+    pub fn theme(&self, depth: i32) -> Theme {
+        self.band(depth).map_or(Theme::Dungeon, |b| b.theme)
+    }
+
+    pub fn ambient_light(&self, depth: i32) -> f32 {
+        self.band(depth).map_or(DEFAULT_AMBIENT, |b| b.ambient)
+    }
+
+    pub fn fixed_level(&self, depth: i32) -> Option<&FixedLevel> {
+        self.fixed
+            .iter()
+            .find(|(d, _)| *d == depth)
+            .map(|(_, level)| level)
+    }
+}
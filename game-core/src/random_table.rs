@@ -0,0 +1,73 @@
+use bracket_random::prelude::RandomNumberGenerator;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RandomRoll {
+    Spawn(String),
+    Table(String),
+    Nothing,
+}
+
+pub struct RandomEntry {
+    result: RandomRoll,
+    weight: i32,
+}
+
+impl RandomEntry {
+    pub fn new(result: RandomRoll, weight: i32) -> RandomEntry {
+        RandomEntry { result, weight }
+    }
+}
+
+#[derive(Default)]
+pub struct RandomTable {
+    entries: Vec<RandomEntry>,
+    total_weight: i32,
+}
+
+impl RandomTable {
+    pub fn new() -> RandomTable {
+        RandomTable {
+            entries: Vec::new(),
+            total_weight: 0,
+        }
+    }
+
+    fn add_entry(mut self, result: RandomRoll, weight: i32) -> RandomTable {
+        if weight > 0 {
+            self.total_weight += weight;
+            self.entries.push(RandomEntry::new(result, weight));
+        }
+        self
+    }
+
+    /// Adds an entry that spawns the named entity.
+    pub fn add<S: ToString>(self, name: S, weight: i32) -> RandomTable {
+        self.add_entry(RandomRoll::Spawn(name.to_string()), weight)
+    }
+
+    /// Adds an entry that defers to another, named table.
+    pub fn add_table<S: ToString>(self, name: S, weight: i32) -> RandomTable {
+        self.add_entry(RandomRoll::Table(name.to_string()), weight)
+    }
+
+    /// Adds an entry that deliberately produces nothing.
+    pub fn add_nothing(self, weight: i32) -> RandomTable {
+        self.add_entry(RandomRoll::Nothing, weight)
+    }
+
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> RandomRoll {
+        if self.total_weight == 0 {
+            return RandomRoll::Nothing;
+        }
+        let mut roll = rng.roll_dice(1, self.total_weight) - 1;
+
+        for entry in self.entries.iter() {
+            if roll < entry.weight {
+                return entry.result.clone();
+            }
+            roll -= entry.weight;
+        }
+
+        RandomRoll::Nothing
+    }
+}
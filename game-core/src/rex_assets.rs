@@ -0,0 +1,37 @@
+use bevy_ecs::prelude::*;
+use bracket_embedding::prelude::{embedded_resource, link_resource, EMBED};
+use bracket_rex::prelude::XpFile;
+
+embedded_resource!(SMALL_DUNGEON, "../../resources/rust.xp");
+embedded_resource!(WFC_DEMO_IMAGE1, "../../resources/wfc-demo1.xp");
+embedded_resource!(WFC_POPULATED, "../../resources/wfc-populated.xp");
+
+/// Resource paths of every REX map linked in by `RexAssets::new`.
+pub const REX_MAPS: &[&str] = &[
+    "../resources/rust.xp",
+    "../resources/wfc-demo1.xp",
+    "../resources/wfc-populated.xp",
+];
+
+#[derive(Resource)]
+pub struct RexAssets {
+    pub menu: XpFile,
+}
+
+impl RexAssets {
+    pub fn new() -> RexAssets {
+        link_resource!(SMALL_DUNGEON, "../resources/rust.xp");
+        link_resource!(WFC_DEMO_IMAGE1, "../resources/wfc-demo1.xp");
+        link_resource!(WFC_POPULATED, "../resources/wfc-populated.xp");
+
+        RexAssets {
+            menu: XpFile::from_resource("../resources/rust.xp").unwrap(),
+        }
+    }
+}
+
+impl Default for RexAssets {
+    fn default() -> RexAssets {
+        RexAssets::new()
+    }
+}
@@ -0,0 +1,181 @@
+use std::{collections::HashSet, io::Read};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    map::{Map, TileType, MAPCOUNT},
+    saveload::SaveError,
+    theme::Theme,
+};
+
+/// Marks a file as a binary save, so it can be told apart from JSON without trusting the name.
+const MAGIC: &[u8] = b"RRSAVE\0";
+
+/// How saves are written. Either kind loads, whichever is selected.
+#[derive(Copy, Clone, PartialEq, Default)]
+pub enum SaveFormat {
+    /// Plain JSON, for reading and poking at while debugging.
+    #[default]
+    Json,
+    /// Compressed, with the map packed down and anything that can be worked out again left out.
+    Binary,
+}
+
+impl SaveFormat {
+    pub fn from_name(name: &str) -> Option<SaveFormat> {
+        match name {
+            "json" => Some(SaveFormat::Json),
+            "binary" => Some(SaveFormat::Binary),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            SaveFormat::Json => "json",
+            SaveFormat::Binary => "sav",
+        }
+    }
+}
+
+/// The parts of a map that can't be worked out again, run-length encoded. `blocked` comes back
+/// from `populate_blocked` and the indexing system, and `visible_tiles` from the next pass of
+/// the visibility system.
+#[derive(Serialize, Deserialize)]
+struct CompactMap {
+    width: i32,
+    height: i32,
+    depth: i32,
+    theme: Theme,
+    bloodstains: Vec<usize>,
+    /// Each tile type, and how many times it repeats.
+    tiles: Vec<(TileType, usize)>,
+    /// Alternating runs of unrevealed and revealed tiles, starting with unrevealed.
+    revealed_tiles: Vec<usize>,
+}
+
+impl CompactMap {
+    fn pack(map: &Map) -> CompactMap {
+        let mut tiles: Vec<(TileType, usize)> = Vec::new();
+        for tile in map.tiles.iter() {
+            match tiles.last_mut() {
+                Some((last, count)) if last == tile => *count += 1,
+                _ => tiles.push((*tile, 1)),
+            }
+        }
+
+        let mut revealed_tiles = Vec::new();
+        let mut current = false;
+        let mut count = 0;
+        for revealed in map.revealed_tiles.iter() {
+            if *revealed != current {
+                revealed_tiles.push(count);
+                current = *revealed;
+                count = 0;
+            }
+            count += 1;
+        }
+        revealed_tiles.push(count);
+
+        let mut bloodstains: Vec<usize> = map.bloodstains.iter().copied().collect();
+        bloodstains.sort_unstable();
+
+        CompactMap {
+            width: map.width,
+            height: map.height,
+            depth: map.depth,
+            theme: map.theme,
+            bloodstains,
+            tiles,
+            revealed_tiles,
+        }
+    }
+
+    fn unpack(self) -> Result<Map, SaveError> {
+        let corrupt = || SaveError::Corrupt("the map doesn't add up".to_string());
+        let count = usize::try_from(self.width)
+            .ok()
+            .zip(usize::try_from(self.height).ok())
+            .and_then(|(width, height)| width.checked_mul(height))
+            .filter(|count| *count <= MAPCOUNT)
+            .ok_or_else(corrupt)?;
+
+        // Add the runs up before expanding them, so a damaged length can't ask for more memory
+        // than there is
+        let tile_runs = run_total(self.tiles.iter().map(|(_, run)| *run));
+        let revealed_runs = run_total(self.revealed_tiles.iter().copied());
+        if tile_runs != Some(count) || revealed_runs != Some(count) {
+            return Err(corrupt());
+        }
+
+        let tiles: Vec<TileType> = self
+            .tiles
+            .iter()
+            .flat_map(|(tile, run)| std::iter::repeat_n(*tile, *run))
+            .collect();
+        let revealed_tiles: Vec<bool> = self
+            .revealed_tiles
+            .iter()
+            .enumerate()
+            .flat_map(|(i, run)| std::iter::repeat_n(i % 2 == 1, *run))
+            .collect();
+
+        let mut map = Map {
+            tiles,
+            width: self.width,
+            height: self.height,
+            revealed_tiles,
+            visible_tiles: vec![false; count],
+            blocked: vec![false; count],
+            depth: self.depth,
+            bloodstains: self.bloodstains.into_iter().collect::<HashSet<usize>>(),
+            theme: self.theme,
+            tile_content: Vec::new(),
+            light: Vec::new(),
+        };
+        map.populate_blocked();
+        Ok(map)
+    }
+}
+
+/// How many tiles a list of run lengths covers, or `None` if that's more than we can count.
+fn run_total(mut runs: impl Iterator<Item = usize>) -> Option<usize> {
+    runs.try_fold(0usize, |total, run| total.checked_add(run))
+}
+
+/// Turns a save, as JSON, into the bytes that go on disk.
+pub fn encode(mut save: Value, format: SaveFormat) -> Result<Vec<u8>, SaveError> {
+    if format == SaveFormat::Json {
+        return Ok(serde_json::to_vec(&save)?);
+    }
+
+    let map = save
+        .pointer_mut("/game/map")
+        .ok_or_else(|| SaveError::Corrupt("no map".to_string()))?;
+    let full: Map = serde_json::from_value(map.take())?;
+    *map = serde_json::to_value(CompactMap::pack(&full))?;
+
+    let mut encoder = DeflateEncoder::new(MAGIC.to_vec(), Compression::default());
+    serde_json::to_writer(&mut encoder, &save)?;
+    Ok(encoder.finish()?)
+}
+
+/// Reads a save back into JSON, whichever format it was written in.
+pub fn decode(bytes: &[u8]) -> Result<Value, SaveError> {
+    let Some(compressed) = bytes.strip_prefix(MAGIC) else {
+        return Ok(serde_json::from_slice(bytes)?);
+    };
+
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed).read_to_end(&mut json)?;
+    let mut save: Value = serde_json::from_slice(&json)?;
+
+    let map = save
+        .pointer_mut("/game/map")
+        .ok_or_else(|| SaveError::Corrupt("no map".to_string()))?;
+    let compact: CompactMap = serde_json::from_value(map.take())?;
+    *map = serde_json::to_value(compact.unpack()?)?;
+    Ok(save)
+}
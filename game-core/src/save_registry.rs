@@ -0,0 +1,353 @@
+use std::{
+    any::TypeId,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use bevy_ecs::{prelude::*, world::EntityWorldMut};
+use lazy_static::lazy_static;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::components::{
+    AreaOfEffect, BlocksTile, CombatStats, Confused, Confusion, Consumable, Corpse, Cursed,
+    DefenseBonus, EntityMoved, EntryTrigger, Equippable, Equipped, HeardNoise, Hidden, HungerClock,
+    InBackpack, InflictsDamage, Item, LifeSteal, LightSource, LootTable, MagicItem, MagicMapper,
+    MeleePowerBonus, Monster, Name, ParticleLifetime, Perception, Player, Poisoned, Position,
+    ProvidesFood, ProvidesHealing, Ranged, RemovesCurse, Renderable, RunStats, SingleActivation,
+    SlowsDigestion, Stealth, SufferDamage, Thorns, Unaware, Viewshed, Waiting, WantsToDropItem,
+    WantsToMelee, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem, WinsGame,
+};
+
+/// A component that points at other entities. Those references mean nothing once the save has
+/// been loaded into a fresh world, so the registry uses this to find and fix them up.
+pub trait EntityRefs {
+    fn entity_refs(&mut self) -> Vec<&mut Entity>;
+}
+
+impl EntityRefs for InBackpack {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        vec![&mut self.owner]
+    }
+}
+
+impl EntityRefs for Equipped {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        vec![&mut self.owner]
+    }
+}
+
+impl EntityRefs for WantsToMelee {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        vec![&mut self.target]
+    }
+}
+
+impl EntityRefs for WantsToPickupItem {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        vec![&mut self.collected_by, &mut self.item]
+    }
+}
+
+impl EntityRefs for WantsToUseItem {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        vec![&mut self.item]
+    }
+}
+
+impl EntityRefs for WantsToDropItem {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        vec![&mut self.item]
+    }
+}
+
+impl EntityRefs for WantsToRemoveItem {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        vec![&mut self.item]
+    }
+}
+
+impl EntityRefs for SufferDamage {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        self.final_blow.source.iter_mut().collect()
+    }
+}
+
+/// What happens to a component when the game is saved.
+enum Policy {
+    /// Written out under the registration's key, and read back in on load.
+    Saved {
+        save: fn(&EntityRef) -> Option<serde_json::Result<Value>>,
+        load: fn(Value) -> serde_json::Result<Box<dyn LoadedComponent>>,
+    },
+    /// Left out of the save. For markers that only last until the systems next run, which
+    /// would be stale by the time the save was loaded.
+    Transient,
+}
+
+struct Registration {
+    type_id: TypeId,
+    key: &'static str,
+    policy: Policy,
+}
+
+impl Registration {
+    fn saved<T: Component + Serialize + DeserializeOwned>(key: &'static str) -> Registration {
+        Registration {
+            type_id: TypeId::of::<T>(),
+            key,
+            policy: Policy::Saved {
+                save: save_component::<T>,
+                load: load_component::<Plain<T>, T>,
+            },
+        }
+    }
+
+    /// Like `saved`, but for components whose entity references need remapping on load.
+    fn linked<T: Component + Serialize + DeserializeOwned + EntityRefs>(
+        key: &'static str,
+    ) -> Registration {
+        Registration {
+            type_id: TypeId::of::<T>(),
+            key,
+            policy: Policy::Saved {
+                save: save_component::<T>,
+                load: load_component::<Linked<T>, T>,
+            },
+        }
+    }
+
+    fn transient<T: Component>(key: &'static str) -> Registration {
+        Registration {
+            type_id: TypeId::of::<T>(),
+            key,
+            policy: Policy::Transient,
+        }
+    }
+}
+
+lazy_static! {
+    /// Every component the game uses, and what saving does with it. A component that isn't in
+    /// here gets left out of saves, with a complaint in the console.
+    static ref REGISTRY: Vec<Registration> = vec![
+        Registration::saved::<AreaOfEffect>("area_of_effect"),
+        Registration::saved::<BlocksTile>("blocks_tile"),
+        Registration::saved::<CombatStats>("combat_stats"),
+        // The turns left on a confusion carry over, so saving isn't a way to shake it off
+        Registration::saved::<Confused>("confused"),
+        Registration::saved::<Confusion>("confusion"),
+        Registration::saved::<Consumable>("consumable"),
+        Registration::saved::<Corpse>("corpse"),
+        Registration::saved::<Cursed>("cursed"),
+        Registration::saved::<DefenseBonus>("defense_bonus"),
+        Registration::transient::<EntityMoved>("entity_moved"),
+        Registration::saved::<EntryTrigger>("entry_trigger"),
+        Registration::saved::<Equippable>("equippable"),
+        Registration::linked::<Equipped>("equipped"),
+        Registration::saved::<HeardNoise>("heard_noise"),
+        Registration::saved::<Hidden>("hidden"),
+        Registration::saved::<HungerClock>("hunger_clock"),
+        Registration::linked::<InBackpack>("in_backpack"),
+        Registration::saved::<InflictsDamage>("inflicts_damage"),
+        Registration::saved::<Item>("item"),
+        Registration::saved::<LifeSteal>("life_steal"),
+        Registration::saved::<LightSource>("light_source"),
+        Registration::saved::<LootTable>("loot_table"),
+        Registration::saved::<MagicItem>("magic_item"),
+        Registration::saved::<MagicMapper>("magic_mapper"),
+        Registration::saved::<MeleePowerBonus>("melee_power_bonus"),
+        Registration::saved::<Monster>("monster"),
+        Registration::saved::<Name>("name"),
+        Registration::saved::<ParticleLifetime>("particle_lifetime"),
+        Registration::saved::<Perception>("perception"),
+        Registration::saved::<Player>("player"),
+        Registration::saved::<Poisoned>("poisoned"),
+        Registration::saved::<Position>("position"),
+        Registration::saved::<ProvidesFood>("provides_food"),
+        Registration::saved::<ProvidesHealing>("provides_healing"),
+        Registration::saved::<Ranged>("ranged"),
+        Registration::saved::<RemovesCurse>("removes_curse"),
+        Registration::saved::<Renderable>("renderable"),
+        Registration::saved::<RunStats>("run_stats"),
+        Registration::saved::<SingleActivation>("single_activation"),
+        Registration::saved::<SlowsDigestion>("slows_digestion"),
+        Registration::saved::<Stealth>("stealth"),
+        Registration::linked::<SufferDamage>("suffer_damage"),
+        Registration::saved::<Thorns>("thorns"),
+        Registration::saved::<Unaware>("unaware"),
+        Registration::saved::<Viewshed>("viewshed"),
+        // Only there between the player resting and their hunger being checked
+        Registration::transient::<Waiting>("waiting"),
+        Registration::linked::<WantsToDropItem>("wants_to_drop_item"),
+        Registration::linked::<WantsToMelee>("wants_to_melee"),
+        Registration::linked::<WantsToPickupItem>("wants_to_pickup_item"),
+        Registration::linked::<WantsToRemoveItem>("wants_to_remove_item"),
+        Registration::linked::<WantsToUseItem>("wants_to_use_item"),
+        Registration::saved::<WinsGame>("wins_game"),
+    ];
+}
+
+fn save_component<T: Component + Serialize>(
+    entity: &EntityRef,
+) -> Option<serde_json::Result<Value>> {
+    entity.get::<T>().map(serde_json::to_value)
+}
+
+fn load_component<W: LoadedComponent + From<T> + 'static, T: DeserializeOwned>(
+    value: Value,
+) -> serde_json::Result<Box<dyn LoadedComponent>> {
+    let component: T = serde_json::from_value(value)?;
+    Ok(Box::new(W::from(component)))
+}
+
+/// A component that has been read from a save, but not yet added to the world.
+trait LoadedComponent {
+    fn entity_refs(&mut self) -> Vec<&mut Entity>;
+    fn insert_into(self: Box<Self>, entity: &mut EntityWorldMut);
+}
+
+struct Plain<T>(T);
+
+impl<T> From<T> for Plain<T> {
+    fn from(component: T) -> Self {
+        Plain(component)
+    }
+}
+
+impl<T: Component> LoadedComponent for Plain<T> {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        Vec::new()
+    }
+
+    fn insert_into(self: Box<Self>, entity: &mut EntityWorldMut) {
+        entity.insert(self.0);
+    }
+}
+
+struct Linked<T>(T);
+
+impl<T> From<T> for Linked<T> {
+    fn from(component: T) -> Self {
+        Linked(component)
+    }
+}
+
+impl<T: Component + EntityRefs> LoadedComponent for Linked<T> {
+    fn entity_refs(&mut self) -> Vec<&mut Entity> {
+        self.0.entity_refs()
+    }
+
+    fn insert_into(self: Box<Self>, entity: &mut EntityWorldMut) {
+        entity.insert(self.0);
+    }
+}
+
+/// An entity as it appears in a save: its old id, and its saved components by key.
+#[derive(Clone, Serialize, serde::Deserialize)]
+pub struct EntityRecord {
+    pub id: Entity,
+    pub components: BTreeMap<String, Value>,
+}
+
+impl EntityRecord {
+    pub fn has(&self, key: &str) -> bool {
+        self.components.contains_key(key)
+    }
+}
+
+/// Records every entity in the world, leaving out the transient components.
+pub fn entity_records(world: &World) -> serde_json::Result<Vec<EntityRecord>> {
+    let registered: HashSet<TypeId> = REGISTRY.iter().map(|r| r.type_id).collect();
+    let mut unregistered = HashSet::new();
+
+    let mut records = Vec::new();
+    for entity in world.iter_entities() {
+        let mut components = BTreeMap::new();
+        for registration in REGISTRY.iter() {
+            if let Policy::Saved { save, .. } = registration.policy {
+                if let Some(value) = save(&entity) {
+                    components.insert(registration.key.to_string(), value?);
+                }
+            }
+        }
+
+        for id in entity.archetype().components() {
+            let Some(info) = world.components().get_info(id) else {
+                continue;
+            };
+            if info.type_id().is_some_and(|t| !registered.contains(&t)) {
+                unregistered.insert(info.name().to_string());
+            }
+        }
+
+        records.push(EntityRecord {
+            id: entity.id(),
+            components,
+        });
+    }
+
+    for name in unregistered {
+        crate::console::log(format!(
+            "{} isn't registered for saving, so was left out",
+            name
+        ));
+    }
+    Ok(records)
+}
+
+/// The saved entities, read back into real components and checked over, ready to go into a
+/// world.
+pub struct LoadedEntities {
+    entities: Vec<(Entity, Vec<Box<dyn LoadedComponent>>)>,
+}
+
+/// Reads every component back out of the records, and makes sure each entity they refer to
+/// was saved too.
+pub fn load_records(records: Vec<EntityRecord>) -> Result<LoadedEntities, String> {
+    let by_key: HashMap<&str, &Registration> = REGISTRY.iter().map(|r| (r.key, r)).collect();
+    let ids: HashSet<Entity> = records.iter().map(|r| r.id).collect();
+
+    let mut entities = Vec::new();
+    for record in records {
+        let mut components = Vec::new();
+        for (key, value) in record.components {
+            let Some(registration) = by_key.get(key.as_str()) else {
+                return Err(format!("unknown component {}", key));
+            };
+            let Policy::Saved { load, .. } = registration.policy else {
+                return Err(format!("{} should never have been saved", key));
+            };
+            let mut component = load(value).map_err(|e| format!("{}: {}", key, e))?;
+            if component.entity_refs().iter().any(|e| !ids.contains(e)) {
+                return Err(format!("{} refers to an entity that isn't there", key));
+            }
+            components.push(component);
+        }
+        entities.push((record.id, components));
+    }
+
+    Ok(LoadedEntities { entities })
+}
+
+impl LoadedEntities {
+    /// Spawns the entities, pointing their references at each other's new ids. Returns how the
+    /// saved ids map onto the spawned entities.
+    pub fn spawn(self, world: &mut World) -> HashMap<Entity, Entity> {
+        let id_transfer: HashMap<Entity, Entity> = self
+            .entities
+            .iter()
+            .map(|(id, _)| (*id, world.spawn_empty().id()))
+            .collect();
+
+        for (id, components) in self.entities {
+            let mut entity = world.entity_mut(id_transfer[&id]);
+            for mut component in components {
+                for reference in component.entity_refs() {
+                    *reference = id_transfer[reference];
+                }
+                component.insert_into(&mut entity);
+            }
+        }
+
+        id_transfer
+    }
+}
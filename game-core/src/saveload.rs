@@ -0,0 +1,507 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy_ecs::prelude::*;
+use bracket_random::prelude::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    components::{Name, Player, Viewshed},
+    gamelog::GameLog,
+    map::{Map, MAPCOUNT},
+    save_format::{self, SaveFormat},
+    save_registry::{entity_records, load_records, EntityRecord, LoadedEntities},
+    RunState, Seed,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedGame {
+    map: Map,
+    entities: Vec<EntityRecord>,
+}
+
+/// The version of the save format this build writes. Bump it, and add a migration, whenever a
+/// change would stop older saves from loading.
+const SAVE_VERSION: u32 = 2;
+const SAVE_DIRECTORY: &str = "./saves";
+pub const SAVE_SLOTS: usize = 3;
+/// Where saves went before there were slots.
+const LEGACY_SAVE_FILE_NAME: &str = "./savegame.json";
+
+/// What the slot menu shows about a save without loading the whole thing.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveHeader {
+    pub version: u32,
+    /// When the game was saved, in seconds since the epoch.
+    pub timestamp: u64,
+    pub character: String,
+    pub depth: i32,
+    /// Whether the game saved itself, rather than the player choosing to.
+    #[serde(default)]
+    pub autosave: bool,
+}
+
+/// A save file: the header, then the game itself.
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    header: SaveHeader,
+    game: SavedGame,
+}
+
+/// How the game writes its saves.
+#[derive(Resource, Copy, Clone)]
+pub struct SaveSettings {
+    pub format: SaveFormat,
+    /// Keep the save being replaced alongside the new one, in case the new one is damaged.
+    pub keep_backup: bool,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        SaveSettings {
+            format: SaveFormat::default(),
+            keep_backup: true,
+        }
+    }
+}
+
+/// Why the game is being saved.
+#[derive(PartialEq, Copy, Clone)]
+pub enum SaveKind {
+    /// The player asked to save and quit.
+    Manual,
+    /// The player reached a new level, so there's something to come back to after a crash.
+    Autosave,
+}
+
+/// How a load went, for telling the player.
+pub struct Loaded {
+    /// The save itself couldn't be read, so the backup was loaded instead.
+    pub from_backup: bool,
+    pub autosave: bool,
+}
+
+/// The slot the current game saves into.
+#[derive(Resource, Copy, Clone)]
+pub struct SaveSlot(pub usize);
+
+/// What's in a save slot, as far as the slot menu is concerned.
+#[derive(Clone)]
+pub enum SlotStatus {
+    Empty,
+    Saved(SaveHeader),
+    Unreadable(String),
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    /// The file isn't a save we can make sense of.
+    Corrupt(String),
+    /// The file was written by a newer version of the game.
+    TooNew(u32),
+    /// The game wasn't started from a save slot, so there's nowhere of its own to save it.
+    NoSlot,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{}", e),
+            SaveError::Corrupt(problem) => write!(f, "the save is corrupt ({})", problem),
+            SaveError::TooNew(version) => write!(
+                f,
+                "the save is from a newer version of the game (format {}, we read up to {})",
+                version, SAVE_VERSION
+            ),
+            SaveError::NoSlot => write!(f, "this game doesn't belong to a save slot"),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        SaveError::Corrupt(e.to_string())
+    }
+}
+
+/// Upgrades a save from one format version to the next; `MIGRATIONS[n]` takes version `n` to
+/// `n + 1`.
+type Migration = fn(Value) -> Result<Value, SaveError>;
+const MIGRATIONS: [Migration; SAVE_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Version 0 was the bare game, without a header.
+fn migrate_v0_to_v1(save: Value) -> Result<Value, SaveError> {
+    let depth = save
+        .pointer("/map/depth")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| SaveError::Corrupt("no map depth".to_string()))?;
+    Ok(json!({
+        "header": {
+            "version": 1,
+            "timestamp": 0,
+            "character": "Player",
+            "depth": depth,
+        },
+        "game": save,
+    }))
+}
+
+/// Version 1 gave every entity a field for each component, null where it didn't have one;
+/// version 2 keeps only the components it has, keyed by name.
+fn migrate_v1_to_v2(mut save: Value) -> Result<Value, SaveError> {
+    let entities = save
+        .pointer_mut("/game/entities")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Corrupt("no entities".to_string()))?;
+    for entity in entities.iter_mut() {
+        let Value::Object(fields) = entity.take() else {
+            return Err(SaveError::Corrupt("an entity isn't an object".to_string()));
+        };
+        let mut id = Value::Null;
+        let mut components = serde_json::Map::new();
+        for (key, value) in fields {
+            if key == "id" {
+                id = value;
+            } else if !value.is_null() {
+                components.insert(key, value);
+            }
+        }
+        *entity = json!({ "id": id, "components": components });
+    }
+    save["header"]["version"] = json!(2);
+    Ok(save)
+}
+
+fn save_version(save: &Value) -> u32 {
+    save.pointer("/header/version")
+        .and_then(Value::as_u64)
+        .map_or(0, |v| v as u32)
+}
+
+/// Brings a save of any version we know about, as JSON, up to the current version.
+pub fn upgrade(mut save: Value) -> Result<Value, SaveError> {
+    let version = save_version(&save);
+    if version > SAVE_VERSION {
+        return Err(SaveError::TooNew(version));
+    }
+    for migration in MIGRATIONS[version as usize..].iter() {
+        save = migration(save)?;
+    }
+    Ok(save)
+}
+
+/// Reads a save of any version we know about, bringing it up to date.
+fn read_save_file(path: &Path) -> Result<SaveFile, SaveError> {
+    let save = upgrade(save_format::decode(&fs::read(path)?)?)?;
+    Ok(serde_json::from_value(save)?)
+}
+
+fn slot_path(slot: usize, format: SaveFormat) -> PathBuf {
+    Path::new(SAVE_DIRECTORY).join(format!("slot{}.{}", slot + 1, format.extension()))
+}
+
+/// `path` with `suffix` tacked on the end of the file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Finds the save in a slot, in whichever format it was written.
+fn slot_file(slot: usize) -> Option<PathBuf> {
+    [SaveFormat::Binary, SaveFormat::Json]
+        .into_iter()
+        .map(|format| slot_path(slot, format))
+        .find(|path| path.exists())
+}
+
+/// Finds the backup of the save before last in a slot, if one was kept.
+fn backup_file(slot: usize) -> Option<PathBuf> {
+    [SaveFormat::Binary, SaveFormat::Json]
+        .into_iter()
+        .map(|format| with_suffix(&slot_path(slot, format), ".bak"))
+        .find(|path| path.exists())
+}
+
+/// Replaces the file at `path` without ever leaving a half-written one there: the new contents
+/// go to a temporary file first, which is only renamed over the old one once it's safely on
+/// disk. If `keep_backup` is set, the old file is copied aside beforehand.
+fn write_atomically(path: &Path, bytes: &[u8], keep_backup: bool) -> io::Result<()> {
+    let temp = with_suffix(path, ".tmp");
+    {
+        let mut file = File::create(&temp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+
+    if keep_backup && path.exists() {
+        fs::copy(path, with_suffix(path, ".bak"))?;
+    }
+    fs::rename(&temp, path)
+}
+
+/// Moves a save from before there were slots into the first empty one, so it isn't lost.
+fn adopt_legacy_save() {
+    if !Path::new(LEGACY_SAVE_FILE_NAME).exists() {
+        return;
+    }
+    if let Some(slot) = (0..SAVE_SLOTS).find(|slot| slot_file(*slot).is_none()) {
+        let adopted = fs::create_dir_all(SAVE_DIRECTORY)
+            .and_then(|_| fs::rename(LEGACY_SAVE_FILE_NAME, slot_path(slot, SaveFormat::Json)));
+        if let Err(e) = adopted {
+            crate::console::log(format!("Unable to move old save into a slot: {}", e));
+        }
+    }
+}
+
+pub fn save_game(world: &mut World, kind: SaveKind) -> Result<(), SaveError> {
+    // Games without a slot of their own, like a loaded state dump, mustn't overwrite anyone's
+    let Some(slot) = world.get_resource::<SaveSlot>().map(|s| s.0) else {
+        return Err(SaveError::NoSlot);
+    };
+    let map = world.resource::<Map>().clone();
+    let character = world
+        .query_filtered::<&Name, With<Player>>()
+        .get_single(world)
+        .map_or_else(|_| "Player".to_string(), |name| name.name.clone());
+
+    let save = SaveFile {
+        header: SaveHeader {
+            version: SAVE_VERSION,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |t| t.as_secs()),
+            character,
+            depth: map.depth,
+            autosave: kind == SaveKind::Autosave,
+        },
+        game: SavedGame {
+            map,
+            entities: entity_records(world)?,
+        },
+    };
+
+    let settings = world
+        .get_resource::<SaveSettings>()
+        .copied()
+        .unwrap_or_default();
+    let bytes = save_format::encode(serde_json::to_value(&save)?, settings.format)?;
+
+    // If the last save here was in the other format, it becomes the backup
+    let path = slot_path(slot, settings.format);
+    fs::create_dir_all(SAVE_DIRECTORY)?;
+    if let Some(previous) = slot_file(slot).filter(|previous| *previous != path) {
+        if settings.keep_backup {
+            fs::rename(&previous, with_suffix(&previous, ".bak"))?;
+        } else {
+            fs::remove_file(&previous)?;
+        }
+    }
+    write_atomically(&path, &bytes, settings.keep_backup)?;
+    Ok(())
+}
+
+pub fn does_save_exist() -> bool {
+    adopt_legacy_save();
+    (0..SAVE_SLOTS).any(|slot| slot_file(slot).is_some())
+}
+
+/// Looks at every slot, reading just enough of each save to describe it.
+pub fn list_slots() -> Vec<SlotStatus> {
+    adopt_legacy_save();
+    (0..SAVE_SLOTS)
+        .map(|slot| {
+            let Some(path) = slot_file(slot) else {
+                return SlotStatus::Empty;
+            };
+            match read_save_file(&path) {
+                Ok(save) => SlotStatus::Saved(save.header),
+                Err(e) => SlotStatus::Unreadable(e.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Loads the game in a slot, falling back on the backup if the save itself won't load. The
+/// world is only touched once the whole save has been read, so a failed load leaves everything
+/// as it was.
+///
+/// The save stays in its slot while the game is played, so that after a crash the game can
+/// carry on from the last save. Dying or winning is what clears the slot out.
+pub fn load_game(world: &mut World, slot: usize) -> Result<Loaded, SaveError> {
+    let path = slot_file(slot).ok_or_else(|| {
+        SaveError::Io(io::Error::new(io::ErrorKind::NotFound, "the slot is empty"))
+    })?;
+
+    let (save, entities, from_backup) = match read_checked(&path) {
+        Ok((save, entities)) => (save, entities, false),
+        Err(e) => {
+            let Some(backup) = backup_file(slot) else {
+                return Err(e);
+            };
+            crate::console::log(format!("{}: {}, trying the backup", path.display(), e));
+            let (save, entities) = read_checked(&backup).map_err(|_| e)?;
+            (save, entities, true)
+        }
+    };
+
+    restore_world(world, save.game.map, entities);
+    world.insert_resource(SaveSlot(slot));
+    Ok(Loaded {
+        from_backup,
+        autosave: save.header.autosave,
+    })
+}
+
+fn read_checked(path: &Path) -> Result<(SaveFile, LoadedEntities), SaveError> {
+    let mut save = read_save_file(path)?;
+    let entities = check_game(std::mem::take(&mut save.game.entities), &save.game.map)?;
+    Ok((save, entities))
+}
+
+/// Catches the problems that would otherwise only show up halfway through restoring the world,
+/// reading the entities back in as it goes.
+fn check_game(entities: Vec<EntityRecord>, map: &Map) -> Result<LoadedEntities, SaveError> {
+    if map.tiles.len() != MAPCOUNT {
+        return Err(SaveError::Corrupt("the map is the wrong size".to_string()));
+    }
+
+    let players = entities.iter().filter(|e| e.has("player")).count();
+    if players != 1 {
+        return Err(SaveError::Corrupt(format!("{} players", players)));
+    }
+
+    load_records(entities).map_err(SaveError::Corrupt)
+}
+
+/// Replaces the map and every entity in the world with the saved ones, returning how the saved
+/// entity ids map onto the newly spawned entities.
+fn restore_world(world: &mut World, map: Map, entities: LoadedEntities) -> HashMap<Entity, Entity> {
+    let mut map = map;
+    map.tile_content = vec![Vec::new(); MAPCOUNT];
+
+    world.insert_resource(map);
+    world.clear_entities();
+    let id_transfer = entities.spawn(world);
+
+    // The player's view may not have been saved along with the map, so look again
+    for mut viewshed in world
+        .query_filtered::<&mut Viewshed, With<Player>>()
+        .iter_mut(world)
+    {
+        viewshed.dirty = true;
+    }
+
+    id_transfer
+}
+
+/// Empties a slot, backups and all.
+pub fn delete_save(slot: usize) {
+    for format in [SaveFormat::Binary, SaveFormat::Json] {
+        let path = slot_path(slot, format);
+        for path in [with_suffix(&path, ".bak"), with_suffix(&path, ".tmp"), path] {
+            if path.exists() {
+                if let Err(e) = fs::remove_file(&path) {
+                    crate::console::log(format!("Unable to delete {}: {}", path.display(), e));
+                }
+            }
+        }
+    }
+}
+
+/// Everything needed to reproduce a running game exactly, for attaching to bug reports.
+#[derive(Serialize, Deserialize)]
+struct StateDump {
+    seed: Seed,
+    /// The generator as it stood when the dump was taken, so the game carries on the same way.
+    rng: RandomNumberGenerator,
+    depth: i32,
+    state: RunState,
+    log: GameLog,
+    map: Map,
+    entities: Vec<EntityRecord>,
+}
+
+/// Writes the whole world to a timestamped file in the working directory, returning its name.
+pub fn dump_state(world: &World) -> io::Result<String> {
+    let map = world.resource::<Map>();
+    let dump = StateDump {
+        seed: *world.resource::<Seed>(),
+        rng: world.non_send_resource::<RandomNumberGenerator>().clone(),
+        depth: map.depth,
+        state: *world.resource::<RunState>(),
+        log: world.resource::<GameLog>().clone(),
+        map: map.clone(),
+        entities: entity_records(world)?,
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.as_secs());
+
+    // Never overwrite an earlier dump, even one taken in the same second
+    let mut attempt = 0;
+    let (file_name, writer) = loop {
+        let file_name = match attempt {
+            0 => format!("./statedump-{}.json", timestamp),
+            n => format!("./statedump-{}-{}.json", timestamp, n),
+        };
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&file_name)
+        {
+            Ok(writer) => break (file_name, writer),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    };
+    serde_json::to_writer_pretty(writer, &dump)?;
+    Ok(file_name)
+}
+
+/// Recreates the world recorded by `dump_state`, including the log and what the game was doing.
+pub fn load_state_dump(world: &mut World, file_name: &str) -> io::Result<()> {
+    let reader = File::open(file_name)?;
+    let dump: StateDump = serde_json::from_reader(reader)?;
+
+    let entities =
+        load_records(dump.entities).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let id_transfer = restore_world(world, dump.map, entities);
+
+    let state = match dump.state {
+        RunState::ShowTargeting { range, item } => RunState::ShowTargeting {
+            range,
+            item: *id_transfer.get(&item).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the item being targeted isn't in the dump",
+                )
+            })?,
+        },
+        // The map generation history isn't part of the dump, so there's nothing to replay
+        RunState::MapGeneration => RunState::AwaitingInput,
+        state => state,
+    };
+
+    world.insert_resource(dump.seed);
+    world.insert_non_send_resource(dump.rng);
+    world.insert_resource(dump.log);
+    world.insert_resource(state);
+
+    Ok(())
+}
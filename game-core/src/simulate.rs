@@ -0,0 +1,224 @@
+use std::{collections::BTreeMap, fmt::Write as _, fs, io};
+
+use bevy_ecs::prelude::*;
+use bracket_pathfinding::prelude::DijkstraMap;
+
+use crate::{
+    actions::{PendingAction, PlayerAction},
+    components::{
+        CombatStats, HungerClock, HungerState, InBackpack, Item, Monster, Player, Position,
+        ProvidesFood, ProvidesHealing, RunStats, WinsGame,
+    },
+    map::{Map, TileType},
+    morgue,
+    progression::PROGRESSION,
+    FrameTime, Game, RunState,
+};
+
+/// How many moves the bot gets before a game is called off, in case it gets stuck.
+const MAX_DECISIONS: i32 = 20_000;
+
+/// Further than any walk the bot could need to make across a map.
+const MAX_PATH_LENGTH: f32 = 4000.0;
+
+/// Long enough for any particle to burn out between one step and the next.
+const STEP_TIME_MS: FrameTime = 1000.0;
+
+/// How one simulated game went.
+struct GameResult {
+    seed: u64,
+    outcome: &'static str,
+    depth: i32,
+    turns: i32,
+    killed_by: Option<String>,
+    damage_taken: BTreeMap<i32, i32>,
+}
+
+/// Plays `games` games with the bot, one per seed counting up from `first_seed`, and writes a
+/// line of CSV for each. Returns the name of the file.
+pub fn run(first_seed: u64, games: u64) -> io::Result<String> {
+    let file_name = format!("./simulation-{}.csv", morgue::timestamp());
+
+    let mut csv = String::from("seed,outcome,depth,turns,cause_of_death");
+    for depth in 0..=PROGRESSION.final_depth {
+        let _ = write!(csv, ",damage_depth_{}", depth);
+    }
+    csv.push('\n');
+
+    for seed in first_seed..first_seed + games {
+        let result = play(seed);
+        crate::console::log(format!(
+            "Seed {}: {} on depth {} after {} turns",
+            seed, result.outcome, result.depth, result.turns
+        ));
+
+        let _ = write!(
+            csv,
+            "{},{},{},{},{}",
+            result.seed,
+            result.outcome,
+            result.depth,
+            result.turns,
+            csv_field(result.killed_by.as_deref().unwrap_or(""))
+        );
+        for depth in 0..=PROGRESSION.final_depth {
+            let damage = result.damage_taken.get(&depth).copied().unwrap_or(0);
+            let _ = write!(csv, ",{}", damage);
+        }
+        csv.push('\n');
+    }
+
+    fs::write(&file_name, csv)?;
+    Ok(file_name)
+}
+
+/// Quotes a field if it needs it.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Runs a whole game with the bot at the controls, the same way a frontend would but without a
+/// screen: no menus, no map generation to watch, and nothing saved.
+fn play(seed: u64) -> GameResult {
+    let mut game = Game::new();
+    game.world
+        .insert_non_send_resource::<FrameTime>(STEP_TIME_MS);
+    game.start_run(seed);
+    game.world.insert_resource(RunState::AwaitingInput);
+
+    let mut decisions = 0;
+    let outcome = loop {
+        let current = *game.world.resource::<RunState>();
+        let mut next = current.next();
+
+        match current {
+            RunState::AwaitingInput => {
+                if decisions >= MAX_DECISIONS {
+                    break "timed out";
+                }
+                decisions += 1;
+                let action = choose_action(&mut game.world);
+                game.world.resource_mut::<PendingAction>().0 = Some(action);
+                game.schedule.run(&mut game.world);
+            }
+            RunState::PlayerTurn | RunState::MonsterTurn => game.schedule.run(&mut game.world),
+            RunState::NextLevel => game.goto_next_level(),
+            RunState::MapGeneration => next = RunState::AwaitingInput,
+            // The bot already knows the map, so there's no need to wait for it to be drawn in
+            RunState::MagicMapReveal { .. } => next = RunState::MonsterTurn,
+            RunState::GameOver => break "died",
+            RunState::Victory => break "won",
+            _ => break "stuck in a menu",
+        }
+
+        if *game.world.resource::<RunState>() == current {
+            game.world.insert_resource(next);
+        }
+    };
+
+    let stats = game
+        .world
+        .query_filtered::<&RunStats, With<Player>>()
+        .single(&game.world)
+        .clone();
+    GameResult {
+        seed,
+        outcome,
+        depth: game.world.resource::<Map>().depth,
+        turns: stats.turns,
+        killed_by: stats.killed_by,
+        damage_taken: stats.damage_taken,
+    }
+}
+
+/// The bot. It fights whatever is next to it, drinks a potion when badly hurt, eats when
+/// hungry, picks up whatever it walks over, and otherwise heads straight for the stairs, or
+/// the amulet on the last level. It reads the whole map, seen or not.
+fn choose_action(world: &mut World) -> PlayerAction {
+    let (player, pos, stats, hunger) = world
+        .query_filtered::<(Entity, &Position, &CombatStats, Option<&HungerClock>), With<Player>>()
+        .single(world);
+    let (x, y) = (pos.x, pos.y);
+    let badly_hurt = stats.hp * 10 < stats.max_hp * 3;
+    let hungry =
+        hunger.is_some_and(|h| matches!(h.state, HungerState::Hungry | HungerState::Starving));
+
+    let mut backpack = world.query::<(
+        Entity,
+        &InBackpack,
+        Option<&ProvidesHealing>,
+        Option<&ProvidesFood>,
+    )>();
+    let carried: Vec<(Entity, bool, bool)> = backpack
+        .iter(world)
+        .filter(|(_, pack, _, _)| pack.owner == player)
+        .map(|(item, _, healing, food)| (item, healing.is_some(), food.is_some()))
+        .collect();
+    if badly_hurt {
+        if let Some((potion, _, _)) = carried.iter().find(|(_, healing, _)| *healing) {
+            return PlayerAction::UseItem(*potion, None);
+        }
+    }
+
+    // Anything standing on the bot's own tile can't be attacked, and moving nowhere would only
+    // have it hit itself
+    let mut monsters = world.query_filtered::<(Entity, &Position), With<Monster>>();
+    if let Some((_, monster)) = monsters.iter(world).find(|(monster, m)| {
+        let (dx, dy) = (m.x - x, m.y - y);
+        *monster != player && dx.abs() <= 1 && dy.abs() <= 1 && (dx, dy) != (0, 0)
+    }) {
+        return PlayerAction::Move(monster.x - x, monster.y - y);
+    }
+
+    if hungry {
+        if let Some((food, _, _)) = carried.iter().find(|(_, _, food)| *food) {
+            return PlayerAction::UseItem(*food, None);
+        }
+    }
+
+    let mut items = world.query_filtered::<(&Position, Option<&WinsGame>), With<Item>>();
+    if items
+        .iter(world)
+        .any(|(item, _)| item.x == x && item.y == y)
+    {
+        return PlayerAction::Pickup;
+    }
+
+    let map = world.resource::<Map>();
+    let here = map.xy_idx(x, y);
+    let goal = if map.depth >= PROGRESSION.final_depth {
+        items
+            .iter(world)
+            .find(|(_, wins)| wins.is_some())
+            .map(|(amulet, _)| map.xy_idx(amulet.x, amulet.y))
+    } else {
+        map.tiles
+            .iter()
+            .position(|tile| *tile == TileType::DownStairs)
+    };
+    let Some(goal) = goal else {
+        return PlayerAction::Wait;
+    };
+    if goal == here && map.depth < PROGRESSION.final_depth {
+        return PlayerAction::Descend;
+    }
+
+    let (goal_x, goal_y) = (goal as i32 % map.width, goal as i32 / map.width);
+    // rltk's Dijkstra maps never mark the goal itself as zero, so the last step is on us
+    if (goal_x - x).abs() <= 1 && (goal_y - y).abs() <= 1 {
+        return PlayerAction::Move(goal_x - x, goal_y - y);
+    }
+
+    // A Dijkstra map rather than A*, which can flip between equally good routes every turn
+    let distances = DijkstraMap::new(map.width, map.height, &[goal], map, MAX_PATH_LENGTH);
+    if let Some(step) = DijkstraMap::find_lowest_exit(&distances, here, map) {
+        let step = step as i32;
+        return PlayerAction::Move(step % map.width - x, step / map.width - y);
+    }
+
+    PlayerAction::Wait
+}
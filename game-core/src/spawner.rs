@@ -0,0 +1,678 @@
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use bracket_color::prelude::{
+    BLACK, CYAN, CYAN3, GOLD, GREEN, GREY, MAGENTA, ORANGE, PINK, RED, WHEAT, WHITE, YELLOW,
+};
+use bracket_random::prelude::RandomNumberGenerator;
+
+use crate::{
+    affixes,
+    components::{
+        AreaOfEffect, BlocksTile, CombatStats, Confusion, Consumable, Corpse, Cursed, DefenseBonus,
+        EntryTrigger, EquipmentSlot, Equippable, Hidden, HungerClock, InflictsDamage, Item,
+        LifeSteal, LightSource, LootTable, MagicItem, MagicMapper, MeleePowerBonus, Monster, Name,
+        Perception, Player, Position, ProvidesFood, ProvidesHealing, Ranged, Rarity, RemovesCurse,
+        Renderable, RunStats, SingleActivation, SlowsDigestion, Stealth, Thorns, Unaware, Viewshed,
+        WinsGame,
+    },
+    cp437::{to_cp437, FontCharType},
+    loot::LOOT_TABLES,
+    map::{Map, TileType, MAPWIDTH},
+    rect::Rect,
+    theme::Theme,
+};
+
+const MAX_SPAWNS: i32 = 4;
+
+/// Spawns the player and returns his/her entity object.
+pub fn player(world: &mut World, player_x: i32, player_y: i32) {
+    world.spawn((
+        Position {
+            x: player_x,
+            y: player_y,
+        },
+        Renderable {
+            glyph: to_cp437('@'),
+            fg: YELLOW,
+            bg: BLACK,
+            render_order: 1,
+        },
+        Player {},
+        Name {
+            name: "Player".to_string(),
+        },
+        Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        },
+        CombatStats {
+            max_hp: 30,
+            hp: 30,
+            defense: 2,
+            power: 5,
+        },
+        HungerClock::new(4200),
+        RunStats::default(),
+        // A torch, to see by
+        LightSource {
+            color: (255, 190, 120),
+            range: 8,
+        },
+        Stealth { value: 2 },
+    ));
+}
+
+fn orc(world: &mut World, x: i32, y: i32) {
+    let orc = monster(world, x, y, to_cp437('o'), "Orc", "Orc Loot");
+    // Orcs are always hungry, and will go looking for food when they get peckish
+    world.entity_mut(orc).insert(HungerClock::new(3000));
+}
+fn goblin(world: &mut World, x: i32, y: i32) {
+    monster(world, x, y, to_cp437('g'), "Goblin", "Goblin Loot");
+}
+fn myconid(world: &mut World, x: i32, y: i32) {
+    let myconid = monster(world, x, y, to_cp437('m'), "Myconid", "Myconid Loot");
+    world.entity_mut(myconid).insert((
+        CombatStats {
+            max_hp: 20,
+            hp: 20,
+            defense: 0,
+            power: 3,
+        },
+        // Myconids glow faintly, which rather gives them away in the dark
+        LightSource {
+            color: (90, 200, 160),
+            range: 3,
+        },
+    ));
+}
+fn skeleton(world: &mut World, x: i32, y: i32) {
+    let skeleton = monster(world, x, y, to_cp437('s'), "Skeleton", "Skeleton Loot");
+    world.entity_mut(skeleton).insert(CombatStats {
+        max_hp: 12,
+        hp: 12,
+        defense: 2,
+        power: 5,
+    });
+}
+fn giant_crab(world: &mut World, x: i32, y: i32) {
+    let crab = monster(world, x, y, to_cp437('c'), "Giant Crab", "Giant Crab Loot");
+    // Hard to get through the shell, but the claws are clumsy
+    world.entity_mut(crab).insert(CombatStats {
+        max_hp: 18,
+        hp: 18,
+        defense: 3,
+        power: 3,
+    });
+}
+fn kobold(world: &mut World, x: i32, y: i32) {
+    let kobold = monster(world, x, y, to_cp437('k'), "Kobold", "Kobold Loot");
+    world.entity_mut(kobold).insert((
+        CombatStats {
+            max_hp: 8,
+            hp: 8,
+            defense: 0,
+            power: 3,
+        },
+        // Kobolds are jumpy little things, and hard to sneak up on
+        Perception { value: 3 },
+    ));
+}
+fn goblin_warlord(world: &mut World, x: i32, y: i32) {
+    let warlord = monster(world, x, y, to_cp437('G'), "Goblin Warlord", "Orc Loot");
+    world.entity_mut(warlord).insert(CombatStats {
+        max_hp: 40,
+        hp: 40,
+        defense: 3,
+        power: 8,
+    });
+}
+
+fn monster<S: ToString>(
+    world: &mut World,
+    x: i32,
+    y: i32,
+    glyph: FontCharType,
+    name: S,
+    loot_table: &str,
+) -> Entity {
+    let monster = world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph,
+            fg: RED,
+            bg: BLACK,
+            render_order: 5,
+        },
+        Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        },
+        Monster {},
+        Name {
+            name: name.to_string(),
+        },
+        BlocksTile {},
+        CombatStats {
+            max_hp: 16,
+            hp: 16,
+            defense: 1,
+            power: 4,
+        },
+        LootTable {
+            table: loot_table.to_string(),
+        },
+        Perception { value: 0 },
+    ));
+    let monster = monster.id();
+
+    // Most monsters haven't noticed anyone is coming, and some are fast asleep
+    let roll = world
+        .non_send_resource_mut::<RandomNumberGenerator>()
+        .roll_dice(1, 3);
+    match roll {
+        1 => {
+            world.entity_mut(monster).insert(Unaware { asleep: true });
+        }
+        2 => {
+            world.entity_mut(monster).insert(Unaware { asleep: false });
+        }
+        _ => {}
+    }
+
+    monster
+}
+
+fn townsperson<S: ToString>(
+    world: &mut World,
+    x: i32,
+    y: i32,
+    glyph: FontCharType,
+    fg: (u8, u8, u8),
+    name: S,
+) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph,
+            fg,
+            bg: BLACK,
+            render_order: 5,
+        },
+        Name {
+            name: name.to_string(),
+        },
+        BlocksTile {},
+    ));
+}
+
+/// Fills a room with stuff!
+pub fn spawn_room(
+    map: &Map,
+    rng: &mut RandomNumberGenerator,
+    room: &Rect,
+    map_depth: i32,
+    spawn_list: &mut Vec<(usize, String)>,
+) {
+    let mut possible_targets: Vec<usize> = Vec::new();
+    {
+        for y in room.y1 + 1..room.y2 {
+            for x in room.x1 + 1..room.x2 {
+                let idx = map.xy_idx(x, y);
+                if map.tiles[idx] == TileType::Floor {
+                    possible_targets.push(idx);
+                }
+            }
+        }
+    }
+
+    spawn_region(rng, &possible_targets, map_depth, spawn_list);
+}
+
+fn health_potion(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437('¡'),
+            fg: MAGENTA,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name {
+            name: "Health Potion".to_string(),
+        },
+        Item {},
+        Consumable {},
+        ProvidesHealing { heal_amount: 8 },
+    ));
+}
+
+fn amulet_of_the_deep(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437('"'),
+            fg: GOLD,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name {
+            name: "Amulet of the Deep".to_string(),
+        },
+        Item {},
+        WinsGame {},
+    ));
+}
+
+fn magic_missile_scroll(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437(')'),
+            fg: CYAN,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name {
+            name: "Magic Missile Scroll".to_string(),
+        },
+        Item {},
+        Consumable {},
+        Ranged { range: 6 },
+        InflictsDamage { damage: 8 },
+    ));
+}
+
+fn fireball_scroll(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437(')'),
+            fg: ORANGE,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name {
+            name: "Fireball Scroll".to_string(),
+        },
+        Item {},
+        Consumable {},
+        Ranged { range: 6 },
+        InflictsDamage { damage: 20 },
+        AreaOfEffect { radius: 3 },
+    ));
+}
+
+fn confusion_scroll(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437(')'),
+            fg: PINK,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name {
+            name: "Confusion Scroll".to_string(),
+        },
+        Item {},
+        Consumable {},
+        Ranged { range: 6 },
+        Confusion { turns: 4 },
+    ));
+}
+
+/// Spawns a piece of equipment, rolling its rarity and affixes for the current depth.
+fn equipment(
+    world: &mut World,
+    x: i32,
+    y: i32,
+    glyph: FontCharType,
+    fg: (u8, u8, u8),
+    name: &str,
+    slot: EquipmentSlot,
+    power: i32,
+    defense: i32,
+) -> Entity {
+    let depth = world.get_resource::<Map>().map_or(1, |map| map.depth);
+    let magic = {
+        let mut rng = world.non_send_resource_mut::<RandomNumberGenerator>();
+        affixes::roll_magic(&mut rng, depth, name, slot, power, defense)
+    };
+
+    let mut item = world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph,
+            fg,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name { name: magic.name },
+        Item {},
+        Equippable { slot },
+    ));
+
+    if magic.power != 0 {
+        item.insert(MeleePowerBonus { power: magic.power });
+    }
+    if magic.defense != 0 {
+        item.insert(DefenseBonus {
+            defense: magic.defense,
+        });
+    }
+    if magic.life_steal > 0 {
+        item.insert(LifeSteal {
+            amount: magic.life_steal,
+        });
+    }
+    if magic.thorns > 0 {
+        item.insert(Thorns {
+            damage: magic.thorns,
+        });
+    }
+    if magic.rarity != Rarity::Common {
+        item.insert(MagicItem {
+            rarity: magic.rarity,
+        });
+    }
+    if magic.cursed {
+        item.insert(Cursed {});
+    }
+
+    item.id()
+}
+
+fn dagger(world: &mut World, x: i32, y: i32) {
+    let slot = EquipmentSlot::Melee;
+    equipment(world, x, y, to_cp437('/'), CYAN, "Dagger", slot, 2, 0);
+}
+
+fn longsword(world: &mut World, x: i32, y: i32) {
+    let slot = EquipmentSlot::Melee;
+    equipment(world, x, y, to_cp437('/'), YELLOW, "Longsword", slot, 4, 0);
+}
+
+fn shield(world: &mut World, x: i32, y: i32) {
+    let slot = EquipmentSlot::Shield;
+    equipment(world, x, y, to_cp437('('), CYAN, "Shield", slot, 0, 1);
+}
+
+fn tower_shield(world: &mut World, x: i32, y: i32) {
+    let slot = EquipmentSlot::Shield;
+    equipment(
+        world,
+        x,
+        y,
+        to_cp437('('),
+        YELLOW,
+        "Tower Shield",
+        slot,
+        0,
+        3,
+    );
+}
+
+fn remove_curse_scroll(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437(')'),
+            fg: WHITE,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name {
+            name: "Remove Curse Scroll".to_string(),
+        },
+        Item {},
+        Consumable {},
+        RemovesCurse {},
+    ));
+}
+
+fn ring_of_slow_digestion(world: &mut World, x: i32, y: i32) {
+    let slot = EquipmentSlot::Ring;
+    let ring = equipment(
+        world,
+        x,
+        y,
+        to_cp437('='),
+        GOLD,
+        "Ring of Slow Digestion",
+        slot,
+        0,
+        0,
+    );
+    world
+        .entity_mut(ring)
+        .insert(SlowsDigestion { percent: 50 });
+}
+
+fn rations(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437('%'),
+            fg: GREEN,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name {
+            name: "Rations".to_string(),
+        },
+        Item {},
+        ProvidesFood { nutrition: 1500 },
+        Consumable {},
+    ));
+}
+
+/// What's left behind when a monster dies; edible, for a while.
+pub fn corpse(world: &mut World, x: i32, y: i32, name: &str) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437('%'),
+            fg: RED,
+            bg: BLACK,
+            render_order: 11,
+        },
+        Name {
+            name: format!("{} corpse", name),
+        },
+        Item {},
+        ProvidesFood { nutrition: 1000 },
+        Corpse { turns_left: 200 },
+        Consumable {},
+    ));
+}
+
+fn magic_mapping_scroll(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437(')'),
+            fg: CYAN3,
+            bg: BLACK,
+            render_order: 10,
+        },
+        Name {
+            name: "Scroll of Magic Mapping".to_string(),
+        },
+        Item {},
+        MagicMapper {},
+        Consumable {},
+    ));
+}
+
+fn bear_trap(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437('^'),
+            fg: RED,
+            bg: BLACK,
+            render_order: 9,
+        },
+        Name {
+            name: "Bear Trap".to_string(),
+        },
+        Hidden {},
+        EntryTrigger {},
+        SingleActivation {},
+        InflictsDamage { damage: 6 },
+    ));
+}
+
+fn brazier(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437('☼'),
+            fg: ORANGE,
+            bg: BLACK,
+            render_order: 8,
+        },
+        Name {
+            name: "Brazier".to_string(),
+        },
+        LightSource {
+            color: (255, 150, 60),
+            range: 10,
+        },
+    ));
+}
+
+fn glowing_fungus(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: to_cp437('♣'),
+            fg: (90, 230, 190),
+            bg: BLACK,
+            render_order: 8,
+        },
+        Name {
+            name: "Glowing Fungus".to_string(),
+        },
+        LightSource {
+            color: (60, 180, 150),
+            range: 5,
+        },
+    ));
+}
+
+/// Everything the spawner can make, by name.
+const SPAWNERS: &[(&str, fn(&mut World, i32, i32))] = &[
+    ("Goblin", goblin),
+    ("Orc", orc),
+    ("Myconid", myconid),
+    ("Skeleton", skeleton),
+    ("Giant Crab", giant_crab),
+    ("Kobold", kobold),
+    ("Goblin Warlord", goblin_warlord),
+    ("Health Potion", health_potion),
+    ("Fireball Scroll", fireball_scroll),
+    ("Confusion Scroll", confusion_scroll),
+    ("Magic Missile Scroll", magic_missile_scroll),
+    ("Dagger", dagger),
+    ("Shield", shield),
+    ("Longsword", longsword),
+    ("Tower Shield", tower_shield),
+    ("Ring of Slow Digestion", ring_of_slow_digestion),
+    ("Remove Curse Scroll", remove_curse_scroll),
+    ("Rations", rations),
+    ("Magic Mapping Scroll", magic_mapping_scroll),
+    ("Bear Trap", bear_trap),
+    ("Brazier", brazier),
+    ("Glowing Fungus", glowing_fungus),
+    ("Amulet of the Deep", amulet_of_the_deep),
+    ("Barkeep", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), ORANGE, "Barkeep")
+    }),
+    ("Patron", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), WHEAT, "Patron")
+    }),
+    ("Priest", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), WHITE, "Priest")
+    }),
+    ("Parishioner", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), GREY, "Parishioner")
+    }),
+    ("Blacksmith", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), RED, "Blacksmith")
+    }),
+    ("Alchemist", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), MAGENTA, "Alchemist")
+    }),
+    ("Clothier", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), CYAN, "Clothier")
+    }),
+    ("Peasant", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), GREEN, "Peasant")
+    }),
+    ("Townsperson", |ecs, x, y| {
+        townsperson(ecs, x, y, to_cp437('☺'), YELLOW, "Townsperson")
+    }),
+];
+
+/// Whether `spawn_entity` knows how to make something by this name.
+pub fn is_spawnable(name: &str) -> bool {
+    SPAWNERS.iter().any(|(spawnable, _)| *spawnable == name)
+}
+
+/// Spawns a named entity (name in tuple.1) at the location in (tuple.0)
+pub fn spawn_entity(ecs: &mut World, (spawn_idx, spawn_name): &(&usize, &String)) {
+    let x = (*spawn_idx % MAPWIDTH) as i32;
+    let y = (*spawn_idx / MAPWIDTH) as i32;
+
+    if let Some((_, spawn)) = SPAWNERS.iter().find(|(name, _)| name == spawn_name) {
+        spawn(ecs, x, y);
+    }
+}
+
+/// Fills a region with stuff!
+pub fn spawn_region(
+    rng: &mut RandomNumberGenerator,
+    area: &[usize],
+    map_depth: i32,
+    spawn_list: &mut Vec<(usize, String)>,
+) {
+    let spawn_table = Theme::for_depth(map_depth).spawn_table(map_depth);
+    let mut spawn_points: BTreeMap<usize, String> = BTreeMap::new();
+    let mut areas: Vec<usize> = Vec::from(area);
+
+    // Scope to keep the borrow checker happy
+    {
+        let num_spawns = i32::min(
+            areas.len() as i32,
+            rng.roll_dice(1, MAX_SPAWNS + 3) + (map_depth - 1) - 3,
+        );
+        if num_spawns == 0 {
+            return;
+        }
+
+        for _i in 0..num_spawns {
+            let array_index = if areas.len() == 1 {
+                0usize
+            } else {
+                (rng.roll_dice(1, areas.len() as i32) - 1) as usize
+            };
+
+            let map_idx = areas[array_index];
+            let roll = spawn_table.roll(rng);
+            if let Some(name) = LOOT_TABLES.resolve(roll, map_depth, rng) {
+                spawn_points.insert(map_idx, name);
+            }
+            areas.remove(array_index);
+        }
+    }
+
+    // Actually spawn the monsters
+    for (spawn_idx, spawn_name) in spawn_points.iter() {
+        spawn_list.push((*spawn_idx, spawn_name.to_string()));
+    }
+}
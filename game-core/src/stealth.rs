@@ -0,0 +1,34 @@
+use bracket_random::prelude::RandomNumberGenerator;
+
+use crate::components::{Perception, Stealth, Unaware};
+
+/// What a monster has to beat to notice an entirely unstealthy player.
+const NOTICE_DIFFICULTY: i32 = 10;
+/// How much harder it is to notice anything while fast asleep.
+const SLEEP_PENALTY: i32 = 8;
+/// Damage multipliers for hitting a monster that hasn't noticed you.
+const SNEAK_ATTACK_MULTIPLIER: i32 = 2;
+const SLEEPING_ATTACK_MULTIPLIER: i32 = 3;
+
+/// Rolls a monster's perception against the player's stealth. `bonus` is anything that gives
+/// the player away, such as how loud a noise still is when it reaches the monster.
+pub fn perception_check(
+    rng: &mut RandomNumberGenerator,
+    perception: Option<&Perception>,
+    unaware: &Unaware,
+    stealth: Option<&Stealth>,
+    bonus: i32,
+) -> bool {
+    let penalty = if unaware.asleep { SLEEP_PENALTY } else { 0 };
+    let roll = rng.roll_dice(1, 20) + perception.map_or(0, |p| p.value) + bonus - penalty;
+    roll >= NOTICE_DIFFICULTY + stealth.map_or(0, |s| s.value)
+}
+
+/// How many times over a hit on an unaware monster counts.
+pub fn sneak_attack_multiplier(unaware: &Unaware) -> i32 {
+    if unaware.asleep {
+        SLEEPING_ATTACK_MULTIPLIER
+    } else {
+        SNEAK_ATTACK_MULTIPLIER
+    }
+}
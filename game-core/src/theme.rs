@@ -0,0 +1,117 @@
+use bracket_color::prelude::RGB;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cp437::{to_cp437, FontCharType},
+    {progression::PROGRESSION, random_table::RandomTable},
+};
+
+/// The overall flavour of a level: how it's laid out, how it looks, and what lives there.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Default, Debug, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dungeon,
+    FungalCave,
+    Crypt,
+    FloodedRuins,
+    DwarvenFort,
+}
+
+const ALL_THEMES: [Theme; 5] = [
+    Theme::Dungeon,
+    Theme::FungalCave,
+    Theme::Crypt,
+    Theme::FloodedRuins,
+    Theme::DwarvenFort,
+];
+
+/// How a theme draws the tiles every level has.
+pub struct Palette {
+    pub floor_glyph: FontCharType,
+    pub floor: RGB,
+    /// A fixed glyph for walls; `None` joins them up with line-drawing characters.
+    pub wall_glyph: Option<FontCharType>,
+    pub wall: RGB,
+    pub stairs: RGB,
+}
+
+impl Theme {
+    /// The theme the progression gives a depth.
+    pub fn for_depth(depth: i32) -> Theme {
+        PROGRESSION.theme(depth)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Dungeon => "dungeon",
+            Theme::FungalCave => "fungal cave",
+            Theme::Crypt => "crypt",
+            Theme::FloodedRuins => "flooded ruins",
+            Theme::DwarvenFort => "dwarven fort",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Theme> {
+        ALL_THEMES.into_iter().find(|t| t.name() == name)
+    }
+
+    pub fn palette(&self) -> Palette {
+        match self {
+            Theme::Dungeon => Palette {
+                floor_glyph: to_cp437('.'),
+                floor: RGB::from_f32(0.0, 0.5, 0.5),
+                wall_glyph: None,
+                wall: RGB::from_f32(0., 1.0, 0.),
+                stairs: RGB::from_f32(0., 1.0, 1.0),
+            },
+            Theme::FungalCave => Palette {
+                floor_glyph: to_cp437(','),
+                floor: RGB::from_f32(0.5, 0.7, 0.3),
+                wall_glyph: Some(to_cp437('#')),
+                wall: RGB::from_f32(0.6, 0.3, 0.7),
+                stairs: RGB::from_f32(1.0, 0.8, 0.3),
+            },
+            Theme::Crypt => Palette {
+                floor_glyph: to_cp437('.'),
+                floor: RGB::from_f32(0.5, 0.5, 0.55),
+                wall_glyph: None,
+                wall: RGB::from_f32(0.85, 0.85, 0.75),
+                stairs: RGB::from_f32(0.8, 0.2, 0.9),
+            },
+            Theme::FloodedRuins => Palette {
+                floor_glyph: to_cp437('~'),
+                floor: RGB::from_f32(0.2, 0.4, 0.9),
+                wall_glyph: None,
+                wall: RGB::from_f32(0.5, 0.6, 0.7),
+                stairs: RGB::from_f32(0.9, 0.9, 0.2),
+            },
+            Theme::DwarvenFort => Palette {
+                floor_glyph: to_cp437('.'),
+                floor: RGB::from_f32(0.7, 0.5, 0.2),
+                wall_glyph: None,
+                wall: RGB::from_f32(0.8, 0.6, 0.3),
+                stairs: RGB::from_f32(1.0, 1.0, 1.0),
+            },
+        }
+    }
+
+    /// What turns up lying around (or wandering about) on a level of this theme.
+    pub fn spawn_table(&self, depth: i32) -> RandomTable {
+        let table = RandomTable::new()
+            .add("Bear Trap", 2)
+            .add_table("Floor Loot", 31 + depth * 4);
+        match self {
+            Theme::Dungeon => table.add("Goblin", 10).add("Orc", 1 + depth),
+            Theme::FungalCave => table
+                .add("Goblin", 6)
+                .add("Myconid", 6 + depth)
+                .add("Glowing Fungus", 12),
+            Theme::Crypt => table.add("Skeleton", 8 + depth).add("Orc", 1 + depth / 2),
+            Theme::FloodedRuins => table.add("Goblin", 4).add("Giant Crab", 6 + depth),
+            Theme::DwarvenFort => table
+                .add("Kobold", 8)
+                .add("Orc", 2 + depth)
+                .add("Brazier", 4),
+        }
+    }
+}
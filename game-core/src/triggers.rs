@@ -1,13 +1,16 @@
 use bevy_ecs::prelude::*;
-use rltk::{to_cp437, BLACK, ORANGE};
+use bracket_color::prelude::{BLACK, ORANGE};
 
 use crate::{
     components::{
-        EntityMoved, EntryTrigger, Hidden, InflictsDamage, Name, Position, SingleActivation,
+        DamageCause, EntityMoved, EntryTrigger, Hidden, InflictsDamage, Name, Position,
+        SingleActivation,
     },
+    cp437::to_cp437,
     damage::DamageEvent,
     gamelog::GameLog,
     map::Map,
+    noise::{NoiseEvent, TRAP_NOISE},
     particle::ParticleBuilder,
 };
 
@@ -22,6 +25,7 @@ pub fn trigger_system(
     mut log: ResMut<GameLog>,
     mut particle: ResMut<ParticleBuilder>,
     mut damage_writer: EventWriter<DamageEvent>,
+    mut noise_writer: EventWriter<NoiseEvent>,
 ) {
     for (entity, pos) in movers.iter_mut() {
         let idx = map.xy_idx(pos.x, pos.y);
@@ -30,16 +34,22 @@ pub fn trigger_system(
                 if let Ok((name, inflict, single)) = triggers.get(*other) {
                     log.entries.push(format!("{} triggers!", &name.name));
                     commands.entity(*other).remove::<Hidden>();
+                    noise_writer.send(NoiseEvent {
+                        x: pos.x,
+                        y: pos.y,
+                        loudness: TRAP_NOISE,
+                    });
 
                     if let Some(inflict) = inflict {
                         particle.request(pos.x, pos.y, ORANGE, BLACK, to_cp437('‼'), 200.0);
                         damage_writer.send(DamageEvent {
                             who: entity,
                             value: inflict.damage,
+                            cause: DamageCause::new(None, &name.name),
                         });
                     }
 
-                    if let Some(_) = single {
+                    if single.is_some() {
                         commands.entity(*other).despawn();
                     }
                 }
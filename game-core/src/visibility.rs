@@ -1,5 +1,6 @@
 use bevy_ecs::prelude::*;
-use rltk::{field_of_view, RandomNumberGenerator};
+use bracket_pathfinding::prelude::field_of_view;
+use bracket_random::prelude::RandomNumberGenerator;
 
 use crate::{
     components::{AsPoint, Hidden, Name, Player, Position, Viewshed},
@@ -24,6 +25,9 @@ pub fn visibility_system(
         viewshed.visible_tiles.clear();
         viewshed.visible_tiles = field_of_view(pos.as_point(), viewshed.range, &*map);
         viewshed.visible_tiles.retain(|p| map.contains_point(*p));
+        // The field of view comes back in no particular order, which would change who spots what
+        // from one run of the same seed to the next
+        viewshed.visible_tiles.sort_by_key(|p| (p.y, p.x));
 
         // Reveal player sight
         if player.is_some() {
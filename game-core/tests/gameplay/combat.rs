@@ -0,0 +1,116 @@
+use game_core::{
+    actions::{PendingAction, PlayerAction},
+    combat::melee_combat_system,
+    components::{EquipmentSlot, Equipped, MeleePowerBonus, Unaware, WantsToMelee},
+    player::player_action_system,
+};
+
+use super::*;
+
+const ROOM: &[&str] = &[
+    "#######", //
+    "#.....#", //
+    "#.....#", //
+    "#######",
+];
+
+#[test]
+fn a_hit_deals_power_less_defense() {
+    let mut world = world_with_map(ROOM);
+    let attacker = spawn_fighter(&mut world, "Orc", 1, 1, stats(16, 1, 6));
+    let victim = spawn_fighter(&mut world, "Goblin", 2, 1, stats(16, 2, 4));
+    world
+        .entity_mut(attacker)
+        .insert(WantsToMelee { target: victim });
+
+    run(&mut world, melee_combat_system);
+
+    assert_eq!(damage_sent(&world), vec![(victim, 4)]);
+    assert_eq!(log(&world), ["Orc hits Goblin, for 4 hp."]);
+    assert!(world.get::<WantsToMelee>(attacker).is_none());
+
+    apply_damage(&mut world);
+    assert_eq!(hp(&world, victim), 12);
+}
+
+#[test]
+fn a_blow_that_cannot_get_through_armour_does_nothing() {
+    let mut world = world_with_map(ROOM);
+    let attacker = spawn_fighter(&mut world, "Kobold", 1, 1, stats(8, 0, 2));
+    let victim = spawn_fighter(&mut world, "Knight", 2, 1, stats(30, 3, 5));
+    world
+        .entity_mut(attacker)
+        .insert(WantsToMelee { target: victim });
+
+    run(&mut world, melee_combat_system);
+
+    assert!(damage_sent(&world).is_empty());
+    assert_eq!(log(&world), ["Kobold is unable to hurt Knight"]);
+}
+
+#[test]
+fn equipped_weapons_add_their_power() {
+    let mut world = world_with_map(ROOM);
+    let attacker = spawn_fighter(&mut world, "Orc", 1, 1, stats(16, 1, 4));
+    let victim = spawn_fighter(&mut world, "Goblin", 2, 1, stats(16, 1, 4));
+    world.spawn((
+        MeleePowerBonus { power: 3 },
+        Equipped {
+            owner: attacker,
+            slot: EquipmentSlot::Melee,
+        },
+    ));
+    world
+        .entity_mut(attacker)
+        .insert(WantsToMelee { target: victim });
+
+    run(&mut world, melee_combat_system);
+
+    assert_eq!(damage_sent(&world), vec![(victim, 6)]);
+}
+
+#[test]
+fn sleeping_targets_take_a_sneak_attack_and_wake_up() {
+    let mut world = world_with_map(ROOM);
+    let attacker = spawn_fighter(&mut world, "Orc", 1, 1, stats(16, 1, 4));
+    let victim = spawn_fighter(&mut world, "Goblin", 2, 1, stats(16, 1, 4));
+    world.entity_mut(victim).insert(Unaware { asleep: true });
+    world
+        .entity_mut(attacker)
+        .insert(WantsToMelee { target: victim });
+
+    run(&mut world, melee_combat_system);
+
+    assert_eq!(damage_sent(&world), vec![(victim, 9)]);
+    assert_eq!(log(&world)[0], "Orc catches Goblin unawares!");
+    assert!(world.get::<Unaware>(victim).is_none());
+}
+
+#[test]
+fn the_dead_are_left_alone() {
+    let mut world = world_with_map(ROOM);
+    let attacker = spawn_fighter(&mut world, "Orc", 1, 1, stats(16, 1, 4));
+    let victim = spawn_fighter(&mut world, "Goblin", 2, 1, stats(16, 1, 4));
+    world.get_mut::<CombatStats>(victim).unwrap().hp = 0;
+    world
+        .entity_mut(attacker)
+        .insert(WantsToMelee { target: victim });
+
+    run(&mut world, melee_combat_system);
+
+    assert!(damage_sent(&world).is_empty());
+    assert!(log(&world).is_empty());
+    assert!(world.get::<WantsToMelee>(attacker).is_none());
+}
+
+#[test]
+fn moving_nowhere_is_not_an_attack_on_yourself() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 2, 1);
+    index_map(&mut world);
+    world.insert_resource(PendingAction(Some(PlayerAction::Move(0, 0))));
+
+    run(&mut world, player_action_system);
+
+    assert!(world.get::<WantsToMelee>(player).is_none());
+}
@@ -0,0 +1,82 @@
+use game_core::components::{Corpse, DamageCause, RunStats};
+
+use super::*;
+
+const ROOM: &[&str] = &[
+    "#######", //
+    "#.....#", //
+    "#.....#", //
+    "#######",
+];
+
+fn hurt(world: &mut World, who: Entity, value: i32, source: Option<Entity>, description: &str) {
+    world.send_event(DamageEvent {
+        who,
+        value,
+        cause: DamageCause::new(source, description),
+    });
+}
+
+#[test]
+fn hits_in_the_same_turn_add_up() {
+    let mut world = world_with_map(ROOM);
+    let goblin = spawn_awake(&mut world, "Goblin", 2, 1);
+    hurt(&mut world, goblin, 3, None, "Orc");
+    hurt(&mut world, goblin, 4, None, "Orc");
+
+    apply_damage(&mut world);
+
+    assert_eq!(hp(&world, goblin), 9);
+    let map = world.resource::<Map>();
+    assert!(map.bloodstains.contains(&map.xy_idx(2, 1)));
+}
+
+#[test]
+fn monsters_die_leaving_a_corpse_and_credit_the_killer() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    let goblin = spawn_awake(&mut world, "Goblin", 2, 1);
+    hurt(&mut world, goblin, 20, Some(player), "Player");
+
+    apply_damage(&mut world);
+
+    assert!(world.get_entity(goblin).is_none());
+    assert_eq!(log(&world), ["Goblin dies horribly!"]);
+    let corpses: Vec<(String, i32, i32)> = world
+        .query_filtered::<(&Name, &Position), With<Corpse>>()
+        .iter(&world)
+        .map(|(name, pos)| (name.name.clone(), pos.x, pos.y))
+        .collect();
+    assert_eq!(corpses, [("Goblin corpse".to_string(), 2, 1)]);
+    let stats = world.get::<RunStats>(player).unwrap();
+    assert_eq!(stats.kills.get("Goblin"), Some(&1));
+    assert!(run_state(&world) == RunState::AwaitingInput);
+}
+
+#[test]
+fn the_player_dying_ends_the_game() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    hurt(&mut world, player, 35, None, "Orc");
+
+    apply_damage(&mut world);
+
+    assert!(run_state(&world) == RunState::GameOver);
+    assert!(world.get_entity(player).is_some());
+    let stats = world.get::<RunStats>(player).unwrap();
+    assert_eq!(stats.killed_by.as_deref(), Some("Orc"));
+    assert_eq!(stats.damage_taken.get(&1), Some(&35));
+}
+
+#[test]
+fn surviving_a_hit_keeps_the_game_going() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    hurt(&mut world, player, 29, None, "Orc");
+
+    apply_damage(&mut world);
+
+    assert_eq!(hp(&world, player), 1);
+    assert!(run_state(&world) == RunState::AwaitingInput);
+    assert!(world.get::<RunStats>(player).unwrap().killed_by.is_none());
+}
@@ -0,0 +1,53 @@
+use game_core::{loot::LootTables, progression::Progression};
+
+/// A progression with one fixed level at depth 2, laid out by `layout`.
+fn progression_with_fixed(layout: &str) -> String {
+    format!(
+        r#"{{
+            "final_depth": 3,
+            "bands": [{{ "first": 0, "last": 3, "theme": "dungeon" }}],
+            "fixed": [{{ "depth": 2, {} }}]
+        }}"#,
+        layout
+    )
+}
+
+#[test]
+fn fixed_levels_can_use_embedded_rex_maps() {
+    let json = progression_with_fixed(r#""rex": "../resources/wfc-populated.xp""#);
+
+    let progression = Progression::from_json(&json).unwrap();
+
+    assert!(progression.fixed_level(2).is_some());
+}
+
+#[test]
+fn a_rex_map_that_isnt_embedded_is_rejected_at_load() {
+    let json = progression_with_fixed(r#""rex": "../resources/wfc-populatd.xp""#);
+
+    let error = Progression::from_json(&json).err().unwrap();
+
+    assert_eq!(
+        error,
+        "Fixed level at depth 2 uses REX map ../resources/wfc-populatd.xp, which isn't embedded"
+    );
+}
+
+#[test]
+fn a_loot_table_item_the_spawner_doesnt_know_is_rejected_at_load() {
+    let json = r#"{
+        "tables": [
+            {
+                "name": "Floor Loot",
+                "entries": [
+                    { "item": "Health Potion", "weight": 1 },
+                    { "item": "Helth Potion", "weight": 1 }
+                ]
+            }
+        ]
+    }"#;
+
+    let error = LootTables::from_json(json).err().unwrap();
+
+    assert_eq!(error, "Loot table Floor Loot has unknown item Helth Potion");
+}
@@ -0,0 +1,119 @@
+use game_core::{
+    components::{EquipmentSlot, Equipped, HungerClock, HungerState, RunStats, SlowsDigestion},
+    hunger::hunger_system,
+};
+
+use super::*;
+
+const ROOM: &[&str] = &[
+    "#####", //
+    "#...#", //
+    "#####",
+];
+
+fn nutrition(world: &World, entity: Entity) -> (i32, HungerState) {
+    let clock = world.get::<HungerClock>(entity).unwrap();
+    (clock.nutrition, clock.state)
+}
+
+#[test]
+fn the_players_turn_burns_nutrition() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    world.insert_resource(RunState::PlayerTurn);
+
+    run(&mut world, hunger_system);
+
+    assert!(nutrition(&world, player) == (4190, HungerState::WellFed));
+    assert!(log(&world).is_empty());
+}
+
+#[test]
+fn the_player_does_not_get_hungry_on_the_monsters_turn() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    world.insert_resource(RunState::MonsterTurn);
+
+    run(&mut world, hunger_system);
+
+    assert_eq!(nutrition(&world, player).0, 4200);
+}
+
+#[test]
+fn crossing_into_hunger_is_announced() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    world.entity_mut(player).insert(HungerClock::new(2005));
+    world.insert_resource(RunState::PlayerTurn);
+
+    run(&mut world, hunger_system);
+
+    assert!(nutrition(&world, player) == (1995, HungerState::Hungry));
+    assert_eq!(log(&world), ["You are hungry."]);
+}
+
+#[test]
+fn slow_digestion_halves_the_burn() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    world.spawn((
+        SlowsDigestion { percent: 50 },
+        Equipped {
+            owner: player,
+            slot: EquipmentSlot::Ring,
+        },
+    ));
+    world.insert_resource(RunState::PlayerTurn);
+
+    run(&mut world, hunger_system);
+
+    assert_eq!(nutrition(&world, player).0, 4195);
+}
+
+#[test]
+fn starving_hurts() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    world.entity_mut(player).insert(HungerClock::new(0));
+    world.insert_resource(RunState::PlayerTurn);
+
+    run(&mut world, hunger_system);
+
+    assert_eq!(damage_sent(&world), vec![(player, 1)]);
+    assert_eq!(
+        log(&world),
+        ["Your hunger pangs are getting painful! You suffer 1 hp damage."]
+    );
+    apply_damage(&mut world);
+    assert_eq!(hp(&world, player), 29);
+}
+
+#[test]
+fn starvation_can_kill() {
+    let mut world = world_with_map(ROOM);
+    let player = spawn_player(&mut world, 1, 1);
+    world.entity_mut(player).insert(HungerClock::new(0));
+    world.get_mut::<CombatStats>(player).unwrap().hp = 1;
+    world.insert_resource(RunState::PlayerTurn);
+
+    run(&mut world, hunger_system);
+    apply_damage(&mut world);
+
+    assert!(run_state(&world) == RunState::GameOver);
+    let stats = world.get::<RunStats>(player).unwrap();
+    assert_eq!(stats.killed_by.as_deref(), Some("starvation"));
+}
+
+#[test]
+fn monsters_go_hungry_but_never_starve() {
+    let mut world = world_with_map(ROOM);
+    let orc = spawn_awake(&mut world, "Orc", 1, 1);
+    world.entity_mut(orc).insert(HungerClock::new(5));
+    world.insert_resource(RunState::MonsterTurn);
+
+    run(&mut world, hunger_system);
+    run(&mut world, hunger_system);
+
+    assert!(nutrition(&world, orc) == (1, HungerState::Hungry));
+    assert!(damage_sent(&world).is_empty());
+}
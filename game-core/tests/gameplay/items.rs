@@ -0,0 +1,206 @@
+use bracket_geometry::prelude::Point;
+
+use game_core::{
+    components::{InBackpack, RunStats, WantsToPickupItem, WantsToUseItem},
+    inventory::{item_collection_system, item_use_system},
+};
+
+use super::*;
+
+const ARENA: &[&str] = &[
+    "####################",
+    "#..................#",
+    "#..................#",
+    "#..................#",
+    "#..................#",
+    "#..................#",
+    "#..................#",
+    "#..................#",
+    "####################",
+];
+
+/// Spawns an item straight into the backpack.
+fn give(world: &mut World, owner: Entity, name: &str) -> Entity {
+    let item = spawn_named(world, name, 0, 0);
+    world
+        .entity_mut(item)
+        .remove::<Position>()
+        .insert(InBackpack { owner });
+    item
+}
+
+fn use_item(world: &mut World, user: Entity, item: Entity, target: Option<Point>) {
+    world
+        .entity_mut(user)
+        .insert(WantsToUseItem { item, target });
+    run(world, item_use_system);
+}
+
+#[test]
+fn potions_heal_and_are_used_up() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 5, 5);
+    world.get_mut::<CombatStats>(player).unwrap().hp = 10;
+    let potion = give(&mut world, player, "Health Potion");
+
+    use_item(&mut world, player, potion, None);
+
+    assert_eq!(hp(&world, player), 18);
+    assert_eq!(log(&world), ["You drink the Health Potion, healing 8 hp."]);
+    assert!(world.get_entity(potion).is_none());
+    assert!(world.get::<WantsToUseItem>(player).is_none());
+    let stats = world.get::<RunStats>(player).unwrap();
+    assert_eq!(stats.items_used.get("Health Potion"), Some(&1));
+}
+
+#[test]
+fn healing_stops_at_max_hp() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 5, 5);
+    world.get_mut::<CombatStats>(player).unwrap().hp = 27;
+    let potion = give(&mut world, player, "Health Potion");
+
+    use_item(&mut world, player, potion, None);
+
+    assert_eq!(hp(&world, player), 30);
+    assert_eq!(log(&world), ["You drink the Health Potion, healing 3 hp."]);
+}
+
+#[test]
+fn healing_at_full_hp_does_not_overheal() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 5, 5);
+    let potion = give(&mut world, player, "Health Potion");
+
+    use_item(&mut world, player, potion, None);
+
+    assert_eq!(hp(&world, player), 30);
+    assert_eq!(world.get::<CombatStats>(player).unwrap().max_hp, 30);
+    assert_eq!(log(&world), ["You drink the Health Potion, healing 0 hp."]);
+}
+
+#[test]
+fn magic_missiles_hit_only_the_tile_aimed_at() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 2, 2);
+    let target = spawn_awake(&mut world, "Goblin", 6, 4);
+    spawn_awake(&mut world, "Goblin", 7, 4);
+    let scroll = give(&mut world, player, "Magic Missile Scroll");
+    index_map(&mut world);
+
+    use_item(&mut world, player, scroll, Some(Point::new(6, 4)));
+
+    assert_eq!(damage_sent(&world), vec![(target, 8)]);
+    assert_eq!(
+        log(&world),
+        ["You use Magic Missile Scroll on Goblin, inflicting 8 hp."]
+    );
+    assert!(world.get_entity(scroll).is_none());
+}
+
+#[test]
+fn missing_with_a_magic_missile_keeps_the_scroll() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 2, 2);
+    let scroll = give(&mut world, player, "Magic Missile Scroll");
+    index_map(&mut world);
+
+    use_item(&mut world, player, scroll, Some(Point::new(6, 4)));
+
+    assert!(damage_sent(&world).is_empty());
+    assert!(world.get_entity(scroll).is_some());
+}
+
+#[test]
+fn fireballs_hit_everything_in_the_blast() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 2, 2);
+    let centre = spawn_awake(&mut world, "Goblin", 10, 4);
+    let near = spawn_awake(&mut world, "Orc", 12, 4);
+    let far = spawn_awake(&mut world, "Kobold", 15, 4);
+    let scroll = give(&mut world, player, "Fireball Scroll");
+    index_map(&mut world);
+
+    use_item(&mut world, player, scroll, Some(Point::new(10, 4)));
+
+    let mut hit: Vec<Entity> = damage_sent(&world).iter().map(|(who, _)| *who).collect();
+    hit.sort();
+    let mut expected = vec![centre, near];
+    expected.sort();
+    assert_eq!(hit, expected);
+    assert!(damage_sent(&world).iter().all(|(_, damage)| *damage == 20));
+
+    apply_damage(&mut world);
+    assert!(world.get_entity(centre).is_none());
+    assert!(world.get_entity(near).is_none());
+    assert_eq!(hp(&world, far), 8);
+}
+
+#[test]
+fn fireballs_catch_the_thrower_too() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 5, 4);
+    let scroll = give(&mut world, player, "Fireball Scroll");
+    index_map(&mut world);
+
+    use_item(&mut world, player, scroll, Some(Point::new(6, 4)));
+
+    assert_eq!(damage_sent(&world), vec![(player, 20)]);
+}
+
+#[test]
+fn fireballs_at_the_edge_of_the_map_stop_short_of_it() {
+    // Open to the north and west, the way the edge of a town is, so there's floor right up to
+    // the border for something to stand on
+    let mut world = world_with_map(&[
+        "..........",
+        "..........",
+        "..........",
+        "..........",
+        ".........#",
+        "##########",
+    ]);
+    let player = spawn_player(&mut world, 8, 3);
+    let on_the_edge = spawn_awake(&mut world, "Goblin", 0, 0);
+    let inside = spawn_awake(&mut world, "Goblin", 1, 1);
+    let scroll = give(&mut world, player, "Fireball Scroll");
+    index_map(&mut world);
+
+    use_item(&mut world, player, scroll, Some(Point::new(0, 0)));
+
+    assert_eq!(damage_sent(&world), vec![(inside, 20)]);
+    apply_damage(&mut world);
+    assert!(world.get_entity(inside).is_none());
+    assert_eq!(hp(&world, on_the_edge), 16);
+}
+
+#[test]
+fn magic_mapping_starts_the_reveal() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 5, 5);
+    let scroll = give(&mut world, player, "Magic Mapping Scroll");
+
+    use_item(&mut world, player, scroll, None);
+
+    assert!(run_state(&world) == RunState::MagicMapReveal { row: 0 });
+    assert_eq!(log(&world), ["The map is revealed to you!"]);
+    assert!(world.get_entity(scroll).is_none());
+}
+
+#[test]
+fn picking_up_the_amulet_wins_the_game() {
+    let mut world = world_with_map(ARENA);
+    let player = spawn_player(&mut world, 5, 5);
+    let amulet = spawn_named(&mut world, "Amulet of the Deep", 5, 5);
+    world.entity_mut(player).insert(WantsToPickupItem {
+        collected_by: player,
+        item: amulet,
+    });
+
+    run(&mut world, item_collection_system);
+
+    assert!(run_state(&world) == RunState::Victory);
+    assert_eq!(log(&world), ["You pick up the Amulet of the Deep."]);
+    assert!(world.get::<Position>(amulet).is_none());
+    assert_eq!(world.get::<InBackpack>(amulet).unwrap().owner, player);
+}
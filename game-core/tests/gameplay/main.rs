@@ -0,0 +1,169 @@
+//! Gameplay tests: a small hand-drawn map, a few entities, one system at a time.
+
+mod combat;
+mod damage;
+mod data;
+mod hunger;
+mod items;
+mod saves;
+mod triggers;
+mod visibility;
+
+use std::collections::HashSet;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::RunSystemOnce;
+use bracket_random::prelude::RandomNumberGenerator;
+
+use game_core::{
+    components::{CombatStats, Name, Player, Position, Unaware},
+    damage::{damage_event_reader, damage_system, DamageEvent},
+    gamelog::GameLog,
+    map::{self, Map, TileType, MAPWIDTH},
+    noise::NoiseEvent,
+    particle::ParticleBuilder,
+    spawner, RunState,
+};
+
+/// The same dice every time, so a test can't pass on one run and fail on the next.
+const TEST_SEED: u64 = 1;
+
+/// A world with everything the gameplay systems expect to find, on a map drawn as rows of
+/// `#` for walls, `.` for floor and `>` for the stairs down.
+pub fn world_with_map(rows: &[&str]) -> World {
+    let height = rows.len();
+    let width = rows[0].len();
+    let count = width * height;
+
+    let mut map = Map {
+        tiles: Vec::with_capacity(count),
+        width: width as i32,
+        height: height as i32,
+        revealed_tiles: vec![false; count],
+        visible_tiles: vec![false; count],
+        blocked: vec![false; count],
+        tile_content: vec![Vec::new(); count],
+        depth: 1,
+        ..Default::default()
+    };
+    for row in rows {
+        assert_eq!(row.len(), width, "every row of the map must be as wide");
+        map.tiles.extend(row.chars().map(|glyph| match glyph {
+            '#' => TileType::Wall,
+            '.' => TileType::Floor,
+            '>' => TileType::DownStairs,
+            _ => panic!("no tile is drawn as {:?}", glyph),
+        }));
+    }
+    map.populate_blocked();
+
+    let mut world = World::new();
+    world.insert_resource(map);
+    world.insert_resource(GameLog {
+        entries: Vec::new(),
+    });
+    world.insert_resource(RunState::AwaitingInput);
+    world.insert_resource(ParticleBuilder::new());
+    world.insert_resource(Events::<DamageEvent>::default());
+    world.insert_resource(Events::<NoiseEvent>::default());
+    world.insert_non_send_resource(RandomNumberGenerator::seeded(TEST_SEED));
+    world
+}
+
+/// Runs one system over the world, applying its commands before returning.
+pub fn run<T, Marker>(world: &mut World, system: T)
+where
+    T: IntoSystem<(), (), Marker>,
+{
+    world.run_system_once(system);
+}
+
+/// Brings the map's record of what stands where up to date.
+pub fn index_map(world: &mut World) {
+    run(world, map::map_indexing_system);
+}
+
+/// Deals out whatever damage has been sent, as the schedule would after the combat systems.
+pub fn apply_damage(world: &mut World) {
+    run(world, damage_event_reader);
+    run(world, damage_system);
+    // Every run of a system starts a new reader, which would deal the same damage again
+    world.resource_mut::<Events<DamageEvent>>().clear();
+}
+
+/// Spawns something from the spawn tables by name, returning its entity.
+pub fn spawn_named(world: &mut World, name: &str, x: i32, y: i32) -> Entity {
+    let before: HashSet<Entity> = world.iter_entities().map(|e| e.id()).collect();
+    let idx = y as usize * MAPWIDTH + x as usize;
+    spawner::spawn_entity(world, &(&idx, &name.to_string()));
+
+    let mut spawned = world
+        .iter_entities()
+        .map(|e| e.id())
+        .filter(|e| !before.contains(e));
+    let entity = spawned
+        .next()
+        .unwrap_or_else(|| panic!("nothing called {} to spawn", name));
+    assert!(
+        spawned.next().is_none(),
+        "{} spawned more than one entity",
+        name
+    );
+    entity
+}
+
+/// Spawns a monster that's wide awake, so nobody gets a sneak attack on it by accident.
+pub fn spawn_awake(world: &mut World, name: &str, x: i32, y: i32) -> Entity {
+    let monster = spawn_named(world, name, x, y);
+    world.entity_mut(monster).remove::<Unaware>();
+    monster
+}
+
+/// Spawns the player, returning their entity.
+pub fn spawn_player(world: &mut World, x: i32, y: i32) -> Entity {
+    spawner::player(world, x, y);
+    world.query_filtered::<Entity, With<Player>>().single(world)
+}
+
+/// Spawns a bare fighter with the given stats, and nothing else to get in the way.
+pub fn spawn_fighter(world: &mut World, name: &str, x: i32, y: i32, stats: CombatStats) -> Entity {
+    world
+        .spawn((
+            Position { x, y },
+            Name {
+                name: name.to_string(),
+            },
+            stats,
+        ))
+        .id()
+}
+
+pub fn stats(hp: i32, defense: i32, power: i32) -> CombatStats {
+    CombatStats {
+        max_hp: hp,
+        hp,
+        defense,
+        power,
+    }
+}
+
+pub fn hp(world: &World, entity: Entity) -> i32 {
+    world.get::<CombatStats>(entity).unwrap().hp
+}
+
+pub fn log(world: &World) -> &[String] {
+    &world.resource::<GameLog>().entries
+}
+
+/// Who has been sent damage, and how much, in the order it was sent.
+pub fn damage_sent(world: &World) -> Vec<(Entity, i32)> {
+    world
+        .resource::<Events<DamageEvent>>()
+        .iter_current_update_events()
+        .map(|event| (event.who, event.value))
+        .collect()
+}
+
+pub fn run_state(world: &World) -> RunState {
+    *world.resource::<RunState>()
+}
@@ -0,0 +1,130 @@
+use std::io::Read;
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use game_core::{
+    save_format::{decode, encode, SaveFormat},
+    saveload::{save_game, upgrade, SaveError, SaveKind},
+};
+use serde_json::{json, Value};
+
+use super::*;
+
+const ROOM: &[&str] = &[
+    "#####", //
+    "#...#", //
+    "#####",
+];
+
+#[test]
+fn a_game_without_a_slot_is_never_saved() {
+    let mut world = world_with_map(ROOM);
+    spawn_player(&mut world, 1, 1);
+
+    let saved = save_game(&mut world, SaveKind::Manual);
+
+    assert!(matches!(saved, Err(SaveError::NoSlot)));
+}
+
+/// A map with a bit of everything a save has to remember about it.
+fn explored_map() -> Map {
+    let mut map = Map::new(4);
+    for x in 10..30 {
+        let idx = map.xy_idx(x, 12);
+        map.tiles[idx] = TileType::Floor;
+        map.revealed_tiles[idx] = true;
+    }
+    let stairs = map.xy_idx(29, 12);
+    map.tiles[stairs] = TileType::DownStairs;
+    map.bloodstains.insert(map.xy_idx(15, 12));
+    map
+}
+
+fn save_json(map: &Map) -> Value {
+    json!({
+        "header": {
+            "version": 2,
+            "timestamp": 0,
+            "character": "Player",
+            "depth": map.depth,
+        },
+        "game": { "map": map, "entities": [] },
+    })
+}
+
+/// Digs the JSON back out of a binary save, for tampering with.
+fn unwrap_binary(bytes: &[u8]) -> Value {
+    let compressed = bytes.strip_prefix(b"RRSAVE\0").unwrap();
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed)
+        .read_to_end(&mut json)
+        .unwrap();
+    serde_json::from_slice(&json).unwrap()
+}
+
+fn wrap_binary(save: &Value) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(b"RRSAVE\0".to_vec(), Compression::default());
+    serde_json::to_writer(&mut encoder, save).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn a_packed_map_unpacks_the_same() {
+    let map = explored_map();
+
+    let bytes = encode(save_json(&map), SaveFormat::Binary).unwrap();
+    let save = decode(&bytes).unwrap();
+    let loaded: Map = serde_json::from_value(save["game"]["map"].clone()).unwrap();
+
+    assert!(loaded.tiles == map.tiles);
+    assert_eq!(loaded.revealed_tiles, map.revealed_tiles);
+    assert_eq!(loaded.bloodstains, map.bloodstains);
+    assert_eq!(
+        (loaded.width, loaded.height, loaded.depth),
+        (map.width, map.height, 4)
+    );
+    assert!(loaded.theme == map.theme);
+}
+
+#[test]
+fn an_impossible_run_length_is_corrupt_rather_than_fatal() {
+    let bytes = encode(save_json(&explored_map()), SaveFormat::Binary).unwrap();
+    let mut save = unwrap_binary(&bytes);
+    save["game"]["map"]["tiles"][0][1] = json!(usize::MAX);
+
+    let decoded = decode(&wrap_binary(&save));
+
+    assert!(matches!(decoded, Err(SaveError::Corrupt(_))));
+}
+
+#[test]
+fn the_oldest_saves_are_brought_up_to_date() {
+    let map = explored_map();
+    let v0 = json!({
+        "map": map,
+        "entities": [
+            {
+                "id": 7,
+                "position": { "x": 12, "y": 12 },
+                "name": { "name": "Player" },
+                "confused": null,
+            },
+        ],
+    });
+
+    let save = upgrade(v0).unwrap();
+
+    assert_eq!(save["header"]["version"], 2);
+    assert_eq!(save["header"]["depth"], 4);
+    assert_eq!(
+        save["game"]["entities"],
+        json!([{
+            "id": 7,
+            "components": {
+                "position": { "x": 12, "y": 12 },
+                "name": { "name": "Player" },
+            },
+        }])
+    );
+    let loaded: Map = serde_json::from_value(save["game"]["map"].clone()).unwrap();
+    assert!(loaded.tiles == map.tiles);
+}
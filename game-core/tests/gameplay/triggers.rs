@@ -0,0 +1,76 @@
+use game_core::{
+    components::{EntityMoved, Hidden, RunStats},
+    triggers::trigger_system,
+};
+
+use super::*;
+
+const CORRIDOR: &[&str] = &[
+    "#######", //
+    "#.....#", //
+    "#######",
+];
+
+#[test]
+fn stepping_on_a_bear_trap_springs_it() {
+    let mut world = world_with_map(CORRIDOR);
+    let player = spawn_player(&mut world, 2, 1);
+    let trap = spawn_named(&mut world, "Bear Trap", 2, 1);
+    world.entity_mut(player).insert(EntityMoved {});
+    index_map(&mut world);
+
+    run(&mut world, trigger_system);
+
+    assert_eq!(damage_sent(&world), vec![(player, 6)]);
+    assert_eq!(log(&world), ["Bear Trap triggers!"]);
+    assert!(world.get_entity(trap).is_none());
+    assert!(world.get::<EntityMoved>(player).is_none());
+
+    apply_damage(&mut world);
+    assert_eq!(hp(&world, player), 24);
+}
+
+#[test]
+fn standing_still_on_a_trap_does_not_spring_it() {
+    let mut world = world_with_map(CORRIDOR);
+    spawn_player(&mut world, 2, 1);
+    let trap = spawn_named(&mut world, "Bear Trap", 2, 1);
+    index_map(&mut world);
+
+    run(&mut world, trigger_system);
+
+    assert!(damage_sent(&world).is_empty());
+    assert!(world.get::<Hidden>(trap).is_some());
+}
+
+#[test]
+fn traps_only_catch_whoever_is_on_them() {
+    let mut world = world_with_map(CORRIDOR);
+    let player = spawn_player(&mut world, 1, 1);
+    let trap = spawn_named(&mut world, "Bear Trap", 2, 1);
+    world.entity_mut(player).insert(EntityMoved {});
+    index_map(&mut world);
+
+    run(&mut world, trigger_system);
+
+    assert!(damage_sent(&world).is_empty());
+    assert!(world.get_entity(trap).is_some());
+    assert!(world.get::<EntityMoved>(player).is_none());
+}
+
+#[test]
+fn a_trap_can_finish_off_the_player() {
+    let mut world = world_with_map(CORRIDOR);
+    let player = spawn_player(&mut world, 2, 1);
+    world.get_mut::<CombatStats>(player).unwrap().hp = 5;
+    spawn_named(&mut world, "Bear Trap", 2, 1);
+    world.entity_mut(player).insert(EntityMoved {});
+    index_map(&mut world);
+
+    run(&mut world, trigger_system);
+    apply_damage(&mut world);
+
+    assert!(run_state(&world) == RunState::GameOver);
+    let stats = world.get::<RunStats>(player).unwrap();
+    assert_eq!(stats.killed_by.as_deref(), Some("Bear Trap"));
+}
@@ -0,0 +1,106 @@
+use bracket_geometry::prelude::Point;
+
+use game_core::{components::Viewshed, visibility::visibility_system};
+
+use super::*;
+
+/// Two rooms with a solid wall between them.
+const TWO_ROOMS: &[&str] = &[
+    "###########",
+    "#....#....#",
+    "#....#....#",
+    "#....#....#",
+    "###########",
+];
+
+fn revealed(world: &World, x: i32, y: i32) -> bool {
+    let map = world.resource::<Map>();
+    map.revealed_tiles[map.xy_idx(x, y)]
+}
+
+fn visible(world: &World, x: i32, y: i32) -> bool {
+    let map = world.resource::<Map>();
+    map.visible_tiles[map.xy_idx(x, y)]
+}
+
+#[test]
+fn the_player_sees_the_room_they_are_in() {
+    let mut world = world_with_map(TWO_ROOMS);
+    let player = spawn_player(&mut world, 2, 2);
+
+    run(&mut world, visibility_system);
+
+    assert!(revealed(&world, 4, 1) && visible(&world, 4, 1));
+    assert!(
+        revealed(&world, 5, 2),
+        "the dividing wall itself is in sight"
+    );
+    let viewshed = world.get::<Viewshed>(player).unwrap();
+    assert!(!viewshed.dirty);
+    assert!(viewshed.visible_tiles.contains(&Point::new(1, 3)));
+}
+
+#[test]
+fn walls_block_sight() {
+    let mut world = world_with_map(TWO_ROOMS);
+    let player = spawn_player(&mut world, 2, 2);
+
+    run(&mut world, visibility_system);
+
+    assert!(!revealed(&world, 7, 2));
+    let viewshed = world.get::<Viewshed>(player).unwrap();
+    assert!(!viewshed.visible_tiles.contains(&Point::new(7, 2)));
+}
+
+#[test]
+fn sight_stays_within_the_map() {
+    let mut world = world_with_map(TWO_ROOMS);
+    let player = spawn_player(&mut world, 1, 1);
+
+    run(&mut world, visibility_system);
+
+    let map = world.resource::<Map>();
+    let viewshed = world.get::<Viewshed>(player).unwrap();
+    assert!(viewshed
+        .visible_tiles
+        .iter()
+        .all(|p| map.contains_point(*p)));
+}
+
+#[test]
+fn leaving_a_room_keeps_it_revealed_but_not_visible() {
+    let mut world = world_with_map(TWO_ROOMS);
+    let player = spawn_player(&mut world, 2, 2);
+    run(&mut world, visibility_system);
+
+    *world.get_mut::<Position>(player).unwrap() = Position { x: 7, y: 2 };
+    world.get_mut::<Viewshed>(player).unwrap().dirty = true;
+    run(&mut world, visibility_system);
+
+    assert!(revealed(&world, 2, 2) && !visible(&world, 2, 2));
+    assert!(revealed(&world, 8, 2) && visible(&world, 8, 2));
+}
+
+#[test]
+fn an_unchanged_viewshed_is_not_recalculated() {
+    let mut world = world_with_map(TWO_ROOMS);
+    let player = spawn_player(&mut world, 2, 2);
+    run(&mut world, visibility_system);
+
+    *world.get_mut::<Position>(player).unwrap() = Position { x: 7, y: 2 };
+    run(&mut world, visibility_system);
+
+    assert!(!revealed(&world, 8, 2));
+}
+
+#[test]
+fn monsters_do_not_reveal_the_map() {
+    let mut world = world_with_map(TWO_ROOMS);
+    let goblin = spawn_awake(&mut world, "Goblin", 7, 2);
+
+    run(&mut world, visibility_system);
+
+    assert!(!revealed(&world, 8, 2));
+    let viewshed = world.get::<Viewshed>(goblin).unwrap();
+    assert!(viewshed.visible_tiles.contains(&Point::new(8, 2)));
+}
@@ -1,25 +1,38 @@
 use bevy_ecs::prelude::*;
 use rltk::{
-    to_cp437, DistanceAlg, FontCharType, Point, Rltk, VirtualKeyCode, BLACK, BLUE, CYAN, GREEN,
-    GREY, MAGENTA, ORANGE, RED, WHEAT, WHITE, YELLOW,
+    to_cp437, DistanceAlg, FontCharType, Point, Rltk, VirtualKeyCode, BLACK, BLUE, CYAN, GOLD,
+    GREEN, GREY, MAGENTA, ORANGE, RED, SKY_BLUE, WHEAT, WHITE, YELLOW,
 };
 
-use crate::{
+use game_core::{
     components::{
-        AsPoint, CombatStats, Equipped, Hidden, HungerClock, HungerState, InBackpack, Name, Player,
-        Position, Viewshed,
+        AsPoint, CombatStats, Equipped, Hidden, HungerClock, HungerState, InBackpack, MagicItem,
+        Name, Player, Position, Rarity, Unaware, Viewshed,
     },
     gamelog::GameLog,
     map::Map,
+    morgue::{HighScores, RunSummary},
     rex_assets::RexAssets,
-    saveload,
+    saveload::{self, SlotStatus},
 };
+pub use game_core::{MainMenuSelection, SlotPurpose};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::input::{KeyBindings, MenuInput, KEYBINDINGS_FILE_NAME};
+
+/// What the slot menu shows: the slots as they were when it opened, and whatever went wrong
+/// last time the player picked one.
+#[derive(Resource)]
+pub struct SlotMenu {
+    pub slots: Vec<SlotStatus>,
+    pub message: Option<String>,
+}
 
 #[derive(PartialEq, Copy, Clone)]
-pub enum MainMenuSelection {
-    NewGame,
-    LoadGame,
-    Quit,
+pub enum SlotMenuResult {
+    NoSelection { selected: usize },
+    Selected { slot: usize },
+    Cancel,
 }
 
 #[derive(PartialEq, Copy, Clone)]
@@ -33,7 +46,13 @@ pub fn draw_ui(world: &mut World, ctx: &mut Rltk) {
 
     {
         let map = world.resource::<Map>();
-        ctx.print_color(2, 43, YELLOW, BLACK, format!("Depth: {}", map.depth));
+        ctx.print_color(
+            2,
+            43,
+            YELLOW,
+            BLACK,
+            format!("Depth: {} ({})", map.depth, map.theme.name()),
+        );
     }
 
     for (stats, hunger) in world
@@ -45,6 +64,7 @@ pub fn draw_ui(world: &mut World, ctx: &mut Rltk) {
         ctx.draw_bar_horizontal(28, 43, 51, stats.hp, stats.max_hp, RED, BLACK);
 
         match hunger.state {
+            HungerState::Bloated => ctx.print_color(71, 42, YELLOW, BLACK, "Bloated"),
             HungerState::WellFed => ctx.print_color(71, 42, GREEN, BLACK, "Well Fed"),
             HungerState::Normal => {}
             HungerState::Hungry => ctx.print_color(71, 42, ORANGE, BLACK, "Hungry"),
@@ -53,16 +73,12 @@ pub fn draw_ui(world: &mut World, ctx: &mut Rltk) {
     }
 
     let log = world.resource::<GameLog>();
-    let mut y = 44;
-    for s in log.entries.iter().rev() {
-        if y < 49 {
-            ctx.print(2, y, s);
-        }
-        y += 1;
+    for (y, s) in (44..49).zip(log.entries.iter().rev()) {
+        ctx.print(2, y, s);
     }
 
     let named_entities = world
-        .query_filtered::<(&Name, &Position), Without<Hidden>>()
+        .query_filtered::<(&Name, &Position, Option<&Unaware>), Without<Hidden>>()
         .iter(world)
         .collect();
     draw_tooltips(world, ctx, named_entities);
@@ -72,7 +88,11 @@ const SPACE: &str = " ";
 const LEFT_ARROW: &str = "<-";
 const RIGHT_ARROW: &str = "->";
 
-fn draw_tooltips(world: &World, ctx: &mut Rltk, named_entities: Vec<(&Name, &Position)>) {
+fn draw_tooltips(
+    world: &World,
+    ctx: &mut Rltk,
+    named_entities: Vec<(&Name, &Position, Option<&Unaware>)>,
+) {
     let map = world.resource::<Map>();
 
     let (mouse_x, mouse_y) = ctx.mouse_pos();
@@ -83,10 +103,18 @@ fn draw_tooltips(world: &World, ctx: &mut Rltk, named_entities: Vec<(&Name, &Pos
     ctx.set_bg(mouse_x, mouse_y, MAGENTA);
 
     let mut tooltip: Vec<String> = Vec::new();
-    for (name, position) in named_entities {
+    for (name, position, unaware) in named_entities {
         let idx = map.xy_idx(position.x, position.y);
-        if position.x == mouse_x && position.y == mouse_y && map.visible_tiles[idx] {
-            tooltip.push(name.name.to_string());
+        if position.x == mouse_x
+            && position.y == mouse_y
+            && map.visible_tiles[idx]
+            && map.is_lit(idx)
+        {
+            match unaware {
+                Some(Unaware { asleep: true }) => tooltip.push(format!("{} (asleep)", name.name)),
+                Some(Unaware { asleep: false }) => tooltip.push(format!("{} (unaware)", name.name)),
+                None => tooltip.push(name.name.to_string()),
+            }
         }
     }
 
@@ -102,34 +130,39 @@ fn draw_tooltips(world: &World, ctx: &mut Rltk, named_entities: Vec<(&Name, &Pos
         if mouse_x > 40 {
             let arrow_pos = Point::new(mouse_x - 2, mouse_y);
             let left_x = mouse_x - width;
-            let mut y = mouse_y;
 
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_y..).zip(tooltip.iter()) {
                 ctx.print_color(left_x, y, WHITE, GREY, s);
                 let padding = (width - s.len() as i32) - 1;
                 for i in 0..padding {
                     ctx.print_color(arrow_pos.x - i, y, WHITE, GREY, SPACE);
                 }
-                y += 1;
             }
             ctx.print_color(arrow_pos.x, arrow_pos.y, WHITE, GREY, RIGHT_ARROW);
         } else {
             let arrow_pos = Point::new(mouse_x + 1, mouse_y);
             let left_x = mouse_x + 3;
-            let mut y = mouse_y;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_y..).zip(tooltip.iter()) {
                 ctx.print_color(left_x + 1, y, WHITE, GREY, s);
                 let padding = (width - s.len() as i32) - 1;
                 for i in 0..padding {
                     ctx.print_color(arrow_pos.x + 1 + i, y, WHITE, GREY, SPACE);
                 }
-                y += 1;
             }
             ctx.print_color(arrow_pos.x, arrow_pos.y, WHITE, GREY, LEFT_ARROW);
         }
     }
 }
 
+/// Item names are colour-coded by rarity in menus.
+pub fn item_name_colour(magic: Option<&MagicItem>) -> (u8, u8, u8) {
+    match magic.map(|m| m.rarity) {
+        None | Some(Rarity::Common) => WHITE,
+        Some(Rarity::Magic) => SKY_BLUE,
+        Some(Rarity::Rare) => GOLD,
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum ItemMenuResult {
     Cancel,
@@ -137,102 +170,125 @@ pub enum ItemMenuResult {
     Selected,
 }
 
-pub fn show_inventory(world: &mut World, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+pub fn show_inventory(
+    world: &mut World,
+    ctx: &mut Rltk,
+    input: Option<MenuInput>,
+) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = world.query_filtered::<Entity, With<Player>>().single(world);
-    let mut held_items = world.query::<(&InBackpack, &Name, Entity)>();
+    let mut held_items = world.query::<(&InBackpack, &Name, Entity, Option<&MagicItem>)>();
 
     let inventory = held_items
         .iter(world)
-        .filter(|(pack, _, _)| pack.owner == player_entity);
+        .filter(|(pack, _, _, _)| pack.owner == player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(15, y - 2, 31, (count + 3) as i32, WHITE, BLACK);
     ctx.print_color(18, y - 2, YELLOW, BLACK, "Inventory");
     ctx.print_color(18, y + count as i32 + 1, YELLOW, BLACK, "ESCAPE to cancel");
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (_pack, name, entity) in held_items
+    for (j, (_pack, name, entity, magic)) in held_items
         .iter(world)
-        .filter(|(pack, _, _)| pack.owner == player_entity)
+        .filter(|(pack, _, _, _)| pack.owner == player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(17, y, WHITE, BLACK, to_cp437('('));
         ctx.set(18, y, YELLOW, BLACK, 97 + j as FontCharType);
         ctx.set(19, y, WHITE, BLACK, to_cp437(')'));
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print_color(21, y, item_name_colour(magic), BLACK, &name.name);
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
-    match ctx.key {
+    match input {
         None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
+        Some(MenuInput::Cancel) => (ItemMenuResult::Cancel, None),
+        Some(MenuInput::Choose(selection)) => match equippable.get(selection) {
+            Some(item) => (ItemMenuResult::Selected, Some(*item)),
+            None => (ItemMenuResult::NoResponse, None),
         },
     }
 }
 
-pub fn drop_menu_item(world: &mut World, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+pub fn drop_menu_item(
+    world: &mut World,
+    ctx: &mut Rltk,
+    input: Option<MenuInput>,
+) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = world.query_filtered::<Entity, With<Player>>().single(world);
-    let mut held_items = world.query::<(&InBackpack, &Name, Entity)>();
+    let mut held_items = world.query::<(&InBackpack, &Name, Entity, Option<&MagicItem>)>();
 
     let inventory = held_items
         .iter(world)
-        .filter(|(pack, _, _)| pack.owner == player_entity);
+        .filter(|(pack, _, _, _)| pack.owner == player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(15, y - 2, 31, (count + 3) as i32, WHITE, BLACK);
     ctx.print_color(18, y - 2, YELLOW, BLACK, "Drop Which Item?");
     ctx.print_color(18, y + count as i32 + 1, YELLOW, BLACK, "ESCAPE to cancel");
 
     let mut droppable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (_pack, name, entity) in held_items
+    for (j, (_pack, name, entity, magic)) in held_items
         .iter(world)
-        .filter(|(pack, _, _)| pack.owner == player_entity)
+        .filter(|(pack, _, _, _)| pack.owner == player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(17, y, WHITE, BLACK, to_cp437('('));
         ctx.set(18, y, YELLOW, BLACK, 97 + j as FontCharType);
         ctx.set(19, y, WHITE, BLACK, to_cp437(')'));
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print_color(21, y, item_name_colour(magic), BLACK, &name.name);
         droppable.push(entity);
-        y += 1;
-        j += 1;
     }
 
-    match ctx.key {
+    match input {
         None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(droppable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
+        Some(MenuInput::Cancel) => (ItemMenuResult::Cancel, None),
+        Some(MenuInput::Choose(selection)) => match droppable.get(selection) {
+            Some(item) => (ItemMenuResult::Selected, Some(*item)),
+            None => (ItemMenuResult::NoResponse, None),
         },
     }
 }
 
+/// Lists what every key does, as the bindings have it.
+pub fn help_screen(
+    ctx: &mut Rltk,
+    bindings: &KeyBindings,
+    input: Option<MenuInput>,
+) -> ItemMenuResult {
+    let actions = bindings.by_action();
+    let count = actions.len() as i32;
+
+    let y = 25 - count / 2;
+    ctx.draw_box(10, y - 2, 59, count + 5, WHITE, BLACK);
+    ctx.print_color(13, y - 2, YELLOW, BLACK, "Keys");
+    for (i, (action, keys)) in actions.iter().enumerate() {
+        let y = y + i as i32;
+        let keys: Vec<String> = keys.iter().map(|key| format!("{:?}", key)).collect();
+        ctx.print_color(12, y, WHITE, BLACK, action.description());
+        ctx.print_color(34, y, YELLOW, BLACK, keys.join(", "));
+    }
+    ctx.print_color(
+        12,
+        y + count + 1,
+        GREY,
+        BLACK,
+        format!("Rebind them in {}", KEYBINDINGS_FILE_NAME),
+    );
+    ctx.print_color(13, y + count + 3, YELLOW, BLACK, "ESCAPE to close");
+
+    match input {
+        Some(MenuInput::Cancel) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse,
+    }
+}
+
 pub fn ranged_target(
     world: &mut World,
     ctx: &mut Rltk,
@@ -310,6 +366,13 @@ pub fn main_menu(
         y += 1;
     }
 
+    if selection == MainMenuSelection::HighScores {
+        ctx.print_color_centered(y, rltk::MAGENTA, rltk::BLACK, "High Scores");
+    } else {
+        ctx.print_color_centered(y, rltk::WHITE, rltk::BLACK, "High Scores");
+    }
+    y += 1;
+
     if selection == MainMenuSelection::Quit {
         ctx.print_color_centered(y, rltk::MAGENTA, rltk::BLACK, "Quit");
     } else {
@@ -317,105 +380,189 @@ pub fn main_menu(
     }
 
     match ctx.key {
-        None => {
-            return MainMenuResult::NoSelection {
-                selected: selection,
-            }
-        }
+        None => MainMenuResult::NoSelection {
+            selected: selection,
+        },
         Some(key) => match key {
-            VirtualKeyCode::Escape => {
-                return MainMenuResult::NoSelection {
-                    selected: MainMenuSelection::Quit,
-                }
-            }
+            VirtualKeyCode::Escape => MainMenuResult::NoSelection {
+                selected: MainMenuSelection::Quit,
+            },
             VirtualKeyCode::Up => {
-                let mut new_selection;
-                match selection {
-                    MainMenuSelection::NewGame => new_selection = MainMenuSelection::Quit,
-                    MainMenuSelection::LoadGame => new_selection = MainMenuSelection::NewGame,
-                    MainMenuSelection::Quit => new_selection = MainMenuSelection::LoadGame,
-                }
+                let mut new_selection = match selection {
+                    MainMenuSelection::NewGame => MainMenuSelection::Quit,
+                    MainMenuSelection::LoadGame => MainMenuSelection::NewGame,
+                    MainMenuSelection::HighScores => MainMenuSelection::LoadGame,
+                    MainMenuSelection::Quit => MainMenuSelection::HighScores,
+                };
                 if new_selection == MainMenuSelection::LoadGame && !save_exists {
                     new_selection = MainMenuSelection::NewGame;
                 }
 
-                return MainMenuResult::NoSelection {
+                MainMenuResult::NoSelection {
                     selected: new_selection,
-                };
+                }
             }
             VirtualKeyCode::Down => {
-                let mut new_selection;
-                match selection {
-                    MainMenuSelection::NewGame => new_selection = MainMenuSelection::LoadGame,
-                    MainMenuSelection::LoadGame => new_selection = MainMenuSelection::Quit,
-                    MainMenuSelection::Quit => new_selection = MainMenuSelection::NewGame,
-                }
+                let mut new_selection = match selection {
+                    MainMenuSelection::NewGame => MainMenuSelection::LoadGame,
+                    MainMenuSelection::LoadGame => MainMenuSelection::HighScores,
+                    MainMenuSelection::HighScores => MainMenuSelection::Quit,
+                    MainMenuSelection::Quit => MainMenuSelection::NewGame,
+                };
                 if new_selection == MainMenuSelection::LoadGame && !save_exists {
-                    new_selection = MainMenuSelection::Quit;
+                    new_selection = MainMenuSelection::HighScores;
                 }
 
-                return MainMenuResult::NoSelection {
+                MainMenuResult::NoSelection {
                     selected: new_selection,
-                };
-            }
-            VirtualKeyCode::Return => {
-                return MainMenuResult::Selected {
-                    selected: selection,
-                }
-            }
-            _ => {
-                return MainMenuResult::NoSelection {
-                    selected: selection,
                 }
             }
+            VirtualKeyCode::Return => MainMenuResult::Selected {
+                selected: selection,
+            },
+            _ => MainMenuResult::NoSelection {
+                selected: selection,
+            },
+        },
+    }
+}
+
+pub fn save_slot_menu(
+    ctx: &mut Rltk,
+    purpose: SlotPurpose,
+    selection: usize,
+    menu: &SlotMenu,
+) -> SlotMenuResult {
+    let title = match purpose {
+        SlotPurpose::NewGame => "Choose a slot for your new game",
+        SlotPurpose::LoadGame => "Choose a game to load",
+    };
+    ctx.print_color_centered(15, YELLOW, BLACK, title);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.as_secs());
+    let mut y = 18;
+    for (slot, status) in menu.slots.iter().enumerate() {
+        let (description, color) = match status {
+            SlotStatus::Empty => ("(empty)".to_string(), GREY),
+            SlotStatus::Saved(header) => (
+                format!(
+                    "{}, depth {}, {} {}",
+                    header.character,
+                    header.depth,
+                    if header.autosave {
+                        "autosaved"
+                    } else {
+                        "saved"
+                    },
+                    describe_age(now, header.timestamp)
+                ),
+                WHITE,
+            ),
+            SlotStatus::Unreadable(problem) => (format!("unreadable: {}", problem), RED),
+        };
+        let label_color = if slot == selection { MAGENTA } else { WHITE };
+        ctx.print_color(10, y, label_color, BLACK, format!("Slot {}", slot + 1));
+        ctx.print_color(18, y, color, BLACK, description);
+        y += 2;
+    }
+
+    if purpose == SlotPurpose::NewGame
+        && matches!(menu.slots.get(selection), Some(SlotStatus::Saved(_)))
+    {
+        ctx.print_color_centered(
+            y + 1,
+            ORANGE,
+            BLACK,
+            "Starting here will replace the game in this slot.",
+        );
+    }
+    if let Some(message) = &menu.message {
+        ctx.print_color_centered(y + 3, RED, BLACK, message);
+    }
+    ctx.print_color_centered(
+        y + 5,
+        GREY,
+        BLACK,
+        "Up/Down to choose, Enter to select, Escape to go back",
+    );
+
+    let count = menu.slots.len();
+    match ctx.key {
+        None => SlotMenuResult::NoSelection {
+            selected: selection,
+        },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => SlotMenuResult::Cancel,
+            VirtualKeyCode::Up => SlotMenuResult::NoSelection {
+                selected: (selection + count - 1) % count,
+            },
+            VirtualKeyCode::Down => SlotMenuResult::NoSelection {
+                selected: (selection + 1) % count,
+            },
+            VirtualKeyCode::Return => SlotMenuResult::Selected { slot: selection },
+            _ => SlotMenuResult::NoSelection {
+                selected: selection,
+            },
         },
     }
 }
 
-pub fn remove_item_menu(world: &mut World, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+/// Roughly how long ago something happened.
+fn describe_age(now: u64, then: u64) -> String {
+    // Saves from before there were timestamps
+    if then == 0 {
+        return "a long time ago".to_string();
+    }
+    let seconds = now.saturating_sub(then);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} minutes ago", seconds / 60),
+        3600..=86399 => format!("{} hours ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
+
+pub fn remove_item_menu(
+    world: &mut World,
+    ctx: &mut Rltk,
+    input: Option<MenuInput>,
+) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = world.query_filtered::<Entity, With<Player>>().single(world);
-    let mut equipped_items = world.query::<(&Equipped, &Name, Entity)>();
+    let mut equipped_items = world.query::<(&Equipped, &Name, Entity, Option<&MagicItem>)>();
 
     let inventory = equipped_items
         .iter(world)
-        .filter(|(eq, _, _)| eq.owner == player_entity);
+        .filter(|(eq, _, _, _)| eq.owner == player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(15, y - 2, 31, (count + 3) as i32, WHITE, BLACK);
     ctx.print_color(18, y - 2, YELLOW, BLACK, "Remove Which Item?");
     ctx.print_color(18, y + count as i32 + 1, YELLOW, BLACK, "ESCAPE to cancel");
 
     let mut removable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (_pack, name, entity) in equipped_items
+    for (j, (_pack, name, entity, magic)) in equipped_items
         .iter(world)
-        .filter(|(eq, _, _)| eq.owner == player_entity)
+        .filter(|(eq, _, _, _)| eq.owner == player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(17, y, WHITE, BLACK, to_cp437('('));
         ctx.set(18, y, YELLOW, BLACK, 97 + j as FontCharType);
         ctx.set(19, y, WHITE, BLACK, to_cp437(')'));
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print_color(21, y, item_name_colour(magic), BLACK, &name.name);
         removable.push(entity);
-        y += 1;
-        j += 1;
     }
 
-    match ctx.key {
+    match input {
         None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(removable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
+        Some(MenuInput::Cancel) => (ItemMenuResult::Cancel, None),
+        Some(MenuInput::Choose(selection)) => match removable.get(selection) {
+            Some(item) => (ItemMenuResult::Selected, Some(*item)),
+            None => (ItemMenuResult::NoResponse, None),
         },
     }
 }
@@ -426,22 +573,118 @@ pub enum GameOverResult {
     QuitToMenu,
 }
 
-pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
+pub fn game_over(ctx: &mut Rltk, summary: Option<&RunSummary>) -> GameOverResult {
     ctx.print_color_centered(15, YELLOW, BLACK, "Your journey has ended!");
+    let y = draw_run_summary(ctx, 17, summary);
+
+    ctx.print_color_centered(
+        y + 1,
+        MAGENTA,
+        BLACK,
+        "Press any key to return to the menu.",
+    );
+
+    match ctx.key {
+        None => GameOverResult::NoSelection,
+        Some(_) => GameOverResult::QuitToMenu,
+    }
+}
+
+pub fn victory(ctx: &mut Rltk, summary: Option<&RunSummary>) -> GameOverResult {
     ctx.print_color_centered(
-        17,
+        15,
+        GOLD,
+        BLACK,
+        "You have recovered the Amulet of the Deep!",
+    );
+    ctx.print_color_centered(
+        16,
         WHITE,
         BLACK,
-        "One day, we'll tell you all about how you did.",
+        "Its weight settles around your neck, and the dungeon falls silent.",
+    );
+    let y = draw_run_summary(ctx, 18, summary);
+
+    ctx.print_color_centered(
+        y + 1,
+        MAGENTA,
+        BLACK,
+        "Press any key to return to the menu.",
     );
+
+    match ctx.key {
+        None => GameOverResult::NoSelection,
+        Some(_) => GameOverResult::QuitToMenu,
+    }
+}
+
+/// Prints the highlights of a finished run from row `y`, returning the first row left free.
+fn draw_run_summary(ctx: &mut Rltk, y: i32, summary: Option<&RunSummary>) -> i32 {
+    let Some(summary) = summary else {
+        return y;
+    };
+    let mut y = y;
+
     ctx.print_color_centered(
-        18,
+        y,
         WHITE,
         BLACK,
-        "That day, sadly, is not in this chapter...",
+        format!("{}, after {} turns.", summary.fate(), summary.turns),
     );
+    y += 1;
+    let kills: i32 = summary.kills.values().sum();
+    ctx.print_color_centered(
+        y,
+        WHITE,
+        BLACK,
+        format!(
+            "You slew {} monsters, for a score of {}.",
+            kills, summary.score
+        ),
+    );
+    y += 1;
+    if let Some(rank) = summary.rank {
+        ctx.print_color_centered(
+            y,
+            GOLD,
+            BLACK,
+            format!("That's number {} on the high score table!", rank + 1),
+        );
+        y += 1;
+    }
+    if let Some(file_name) = &summary.morgue_file {
+        ctx.print_color_centered(
+            y,
+            GREY,
+            BLACK,
+            format!("The full story is in {}", file_name),
+        );
+        y += 1;
+    }
+
+    y
+}
+
+pub fn high_scores(ctx: &mut Rltk, scores: &HighScores) -> GameOverResult {
+    ctx.print_color_centered(8, YELLOW, BLACK, "High Scores");
+
+    if scores.entries.is_empty() {
+        ctx.print_color_centered(10, WHITE, BLACK, "Nobody has finished a run yet.");
+    }
+    for (i, entry) in scores.entries.iter().enumerate() {
+        let y = 10 + i as i32 * 2;
+        ctx.print_color(
+            4,
+            y,
+            WHITE,
+            BLACK,
+            format!("{:>2}. {:>6}", i + 1, entry.score),
+        );
+        ctx.print_color(16, y, WHEAT, BLACK, &entry.fate);
+        ctx.print_color(16, y + 1, GREY, BLACK, format!("{} turns", entry.turns));
+    }
 
-    ctx.print_color_centered(20, MAGENTA, BLACK, "Press any key to return to the menu.");
+    ctx.print_color_centered(42, MAGENTA, BLACK, "Press any key to return to the menu.");
 
     match ctx.key {
         None => GameOverResult::NoSelection,
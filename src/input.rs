@@ -0,0 +1,226 @@
+use bevy_ecs::prelude::*;
+use rltk::VirtualKeyCode;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use game_core::actions::PlayerAction;
+
+const DEFAULT_BINDINGS_JSON: &str = include_str!("../resources/keybindings.json");
+
+/// The player's own bindings, if they've written any. They replace the defaults entirely.
+pub const KEYBINDINGS_FILE_NAME: &str = "./keybindings.json";
+
+/// Keys the game keeps for itself, whatever the bindings say.
+const RESERVED_KEYS: &[(VirtualKeyCode, &str)] = &[(VirtualKeyCode::F12, "state dumps")];
+
+/// Everything a key can be bound to, named as in the bindings file.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundAction {
+    MoveNorth,
+    MoveSouth,
+    MoveWest,
+    MoveEast,
+    MoveNorthWest,
+    MoveNorthEast,
+    MoveSouthWest,
+    MoveSouthEast,
+    Wait,
+    Pickup,
+    Descend,
+    Inventory,
+    Drop,
+    Remove,
+    Help,
+    SaveAndQuit,
+}
+
+impl BoundAction {
+    pub fn action(self) -> PlayerAction {
+        match self {
+            BoundAction::MoveNorth => PlayerAction::Move(0, -1),
+            BoundAction::MoveSouth => PlayerAction::Move(0, 1),
+            BoundAction::MoveWest => PlayerAction::Move(-1, 0),
+            BoundAction::MoveEast => PlayerAction::Move(1, 0),
+            BoundAction::MoveNorthWest => PlayerAction::Move(-1, -1),
+            BoundAction::MoveNorthEast => PlayerAction::Move(1, -1),
+            BoundAction::MoveSouthWest => PlayerAction::Move(-1, 1),
+            BoundAction::MoveSouthEast => PlayerAction::Move(1, 1),
+            BoundAction::Wait => PlayerAction::Wait,
+            BoundAction::Pickup => PlayerAction::Pickup,
+            BoundAction::Descend => PlayerAction::Descend,
+            BoundAction::Inventory => PlayerAction::ShowInventory,
+            BoundAction::Drop => PlayerAction::ShowDropItem,
+            BoundAction::Remove => PlayerAction::ShowRemoveItem,
+            BoundAction::Help => PlayerAction::ShowHelp,
+            BoundAction::SaveAndQuit => PlayerAction::SaveAndQuit,
+        }
+    }
+
+    /// How the help screen describes it.
+    pub fn description(self) -> &'static str {
+        match self {
+            BoundAction::MoveNorth => "Move north",
+            BoundAction::MoveSouth => "Move south",
+            BoundAction::MoveWest => "Move west",
+            BoundAction::MoveEast => "Move east",
+            BoundAction::MoveNorthWest => "Move north-west",
+            BoundAction::MoveNorthEast => "Move north-east",
+            BoundAction::MoveSouthWest => "Move south-west",
+            BoundAction::MoveSouthEast => "Move south-east",
+            BoundAction::Wait => "Wait a turn",
+            BoundAction::Pickup => "Pick up an item",
+            BoundAction::Descend => "Go down the stairs",
+            BoundAction::Inventory => "Use an item",
+            BoundAction::Drop => "Drop an item",
+            BoundAction::Remove => "Take off equipment",
+            BoundAction::Help => "Show the key bindings",
+            BoundAction::SaveAndQuit => "Save and quit",
+        }
+    }
+}
+
+const ARROW_KEYS: &[(VirtualKeyCode, BoundAction)] = &[
+    (VirtualKeyCode::Up, BoundAction::MoveNorth),
+    (VirtualKeyCode::Down, BoundAction::MoveSouth),
+    (VirtualKeyCode::Left, BoundAction::MoveWest),
+    (VirtualKeyCode::Right, BoundAction::MoveEast),
+];
+
+const NUMPAD_KEYS: &[(VirtualKeyCode, BoundAction)] = &[
+    (VirtualKeyCode::Numpad8, BoundAction::MoveNorth),
+    (VirtualKeyCode::Numpad2, BoundAction::MoveSouth),
+    (VirtualKeyCode::Numpad4, BoundAction::MoveWest),
+    (VirtualKeyCode::Numpad6, BoundAction::MoveEast),
+    (VirtualKeyCode::Numpad7, BoundAction::MoveNorthWest),
+    (VirtualKeyCode::Numpad9, BoundAction::MoveNorthEast),
+    (VirtualKeyCode::Numpad1, BoundAction::MoveSouthWest),
+    (VirtualKeyCode::Numpad3, BoundAction::MoveSouthEast),
+    (VirtualKeyCode::Numpad5, BoundAction::Wait),
+];
+
+const VI_KEYS: &[(VirtualKeyCode, BoundAction)] = &[
+    (VirtualKeyCode::K, BoundAction::MoveNorth),
+    (VirtualKeyCode::J, BoundAction::MoveSouth),
+    (VirtualKeyCode::H, BoundAction::MoveWest),
+    (VirtualKeyCode::L, BoundAction::MoveEast),
+    (VirtualKeyCode::Y, BoundAction::MoveNorthWest),
+    (VirtualKeyCode::U, BoundAction::MoveNorthEast),
+    (VirtualKeyCode::B, BoundAction::MoveSouthWest),
+    (VirtualKeyCode::N, BoundAction::MoveSouthEast),
+];
+
+fn preset(name: &str) -> Option<&'static [(VirtualKeyCode, BoundAction)]> {
+    match name {
+        "arrows" => Some(ARROW_KEYS),
+        "numpad" => Some(NUMPAD_KEYS),
+        "vi" => Some(VI_KEYS),
+        _ => None,
+    }
+}
+
+#[derive(Deserialize)]
+struct KeyBindingsFile {
+    /// Ready-made sets of movement keys to start from.
+    #[serde(default)]
+    presets: Vec<String>,
+    /// Keys for each action, on top of the presets.
+    #[serde(default)]
+    keys: BTreeMap<BoundAction, Vec<VirtualKeyCode>>,
+}
+
+/// Which key does what while the game is waiting on the player's next move.
+#[derive(Resource)]
+pub struct KeyBindings {
+    keys: HashMap<VirtualKeyCode, BoundAction>,
+}
+
+impl KeyBindings {
+    /// The player's bindings file if there is one, or the defaults if not.
+    pub fn load() -> Result<KeyBindings, String> {
+        if !Path::new(KEYBINDINGS_FILE_NAME).exists() {
+            return KeyBindings::from_json(DEFAULT_BINDINGS_JSON);
+        }
+        fs::read_to_string(KEYBINDINGS_FILE_NAME)
+            .map_err(|e| e.to_string())
+            .and_then(|json| KeyBindings::from_json(&json))
+            .map_err(|e| format!("{}: {}", KEYBINDINGS_FILE_NAME, e))
+    }
+
+    /// Builds the bindings, refusing any key that ends up doing two different things.
+    fn from_json(json: &str) -> Result<KeyBindings, String> {
+        let file: KeyBindingsFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut bindings: Vec<(VirtualKeyCode, BoundAction)> = Vec::new();
+        for name in file.presets.iter() {
+            let preset = preset(name).ok_or_else(|| format!("Unknown key preset {}", name))?;
+            bindings.extend_from_slice(preset);
+        }
+        for (action, keys) in file.keys.iter() {
+            bindings.extend(keys.iter().map(|key| (*key, *action)));
+        }
+
+        let mut keys: HashMap<VirtualKeyCode, BoundAction> = HashMap::new();
+        let mut conflicts = Vec::new();
+        for (key, action) in bindings {
+            if let Some((_, reason)) = RESERVED_KEYS.iter().find(|(k, _)| *k == key) {
+                return Err(format!("{:?} is kept for {}", key, reason));
+            }
+            match keys.get(&key) {
+                Some(bound) if *bound != action => conflicts.push(format!(
+                    "{:?} is bound to both {} and {}",
+                    key,
+                    bound.description().to_lowercase(),
+                    action.description().to_lowercase()
+                )),
+                Some(_) => {}
+                None => {
+                    keys.insert(key, action);
+                }
+            }
+        }
+        if !conflicts.is_empty() {
+            return Err(conflicts.join("; "));
+        }
+
+        Ok(KeyBindings { keys })
+    }
+
+    pub fn bound_action(&self, key: Option<VirtualKeyCode>) -> Option<BoundAction> {
+        self.keys.get(&key?).copied()
+    }
+
+    /// Every bound action with its keys, in the order the help screen lists them.
+    pub fn by_action(&self) -> BTreeMap<BoundAction, Vec<VirtualKeyCode>> {
+        let mut actions: BTreeMap<BoundAction, Vec<VirtualKeyCode>> = BTreeMap::new();
+        for (key, action) in self.keys.iter() {
+            actions.entry(*action).or_default().push(*key);
+        }
+        for keys in actions.values_mut() {
+            keys.sort();
+        }
+        actions
+    }
+}
+
+/// An answer to one of the item menus.
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum MenuInput {
+    /// The item at this position in the list.
+    Choose(usize),
+    Cancel,
+}
+
+/// What a key means while a menu is open: Escape backs out, and the letters pick items.
+pub fn menu_input(key: Option<VirtualKeyCode>) -> Option<MenuInput> {
+    match key? {
+        VirtualKeyCode::Escape => Some(MenuInput::Cancel),
+        key => usize::try_from(rltk::letter_to_option(key))
+            .ok()
+            .map(MenuInput::Choose),
+    }
+}
@@ -1,222 +1,152 @@
-mod ai;
-mod combat;
-mod components;
-mod damage;
-mod gamelog;
 mod gui;
-mod hunger;
-mod inventory;
-mod map;
-mod map_builders;
-mod particle;
-mod player;
-mod random_table;
-mod rect;
-mod rex_assets;
-mod saveload;
-mod spawner;
-mod triggers;
-mod visibility;
-
-extern crate serde;
+mod input;
+mod map_render;
+mod replay;
 
 use bevy_ecs::prelude::*;
-use components::{
-    CombatStats, Equipped, Hidden, InBackpack, Player, Position, Ranged, Renderable, Viewshed,
-    WantsToDropItem, WantsToRemoveItem, WantsToUseItem,
+use game_core::{
+    actions::{PendingAction, PlayerAction},
+    components::{Hidden, Position, Ranged, Renderable},
+    gamelog::GameLog,
+    map::{Map, MAPCOUNT, MAPHEIGHT, MAPWIDTH},
+    morgue,
+    rex_assets::RexAssets,
+    save_format::SaveFormat,
+    saveload, simulate, FrameTime, Game, RunState, SHOW_MAPGEN_VISUALIZER,
 };
-use damage::DamageEvent;
-use gamelog::GameLog;
-use map::{Map, TileType, MAPCOUNT, MAPHEIGHT, MAPWIDTH};
-use rex_assets::RexAssets;
+use input::{KeyBindings, MenuInput};
 use rltk::{
     main_loop, BError, GameState, RandomNumberGenerator, Rltk, RltkBuilder, VirtualKeyCode,
 };
 
-const SHOW_MAPGEN_VISUALIZER: bool = true;
-
 struct State {
-    world: World,
-    schedule: Schedule,
-    mapgen_history: Vec<Map>,
+    game: Game,
     mapgen_index: usize,
     mapgen_timer: f32,
+    /// Where the inputs of the run being played go, so it can be replayed.
+    recorder: Option<replay::Recorder>,
+    /// A recorded run being played back in place of the player's input.
+    playback: Option<replay::Playback>,
 }
 
 impl State {
-    fn entities_to_remove_on_level_change(&mut self) -> Vec<Entity> {
-        let mut entities = self.world.query::<Entity>();
-        let player_entity = self
-            .world
-            .query_filtered::<Entity, With<Player>>()
-            .single(&self.world);
-        let mut backpack = self.world.query::<&InBackpack>();
-        let mut equipped = self.world.query::<&Equipped>();
-
-        let mut to_delete: Vec<Entity> = Vec::new();
-        for entity in entities.iter(&self.world) {
-            let mut should_delete = true;
-
-            // Don't delete the player
-            if entity == player_entity {
-                should_delete = false;
-            }
-
-            // Don't delete the player's equipment
-            let bp = backpack.get(&self.world, entity);
-            if let Ok(bp) = bp {
-                if bp.owner == player_entity {
-                    should_delete = false;
-                }
-            }
-
-            let eq = equipped.get(&self.world, entity);
-            if let Ok(eq) = eq {
-                if eq.owner == player_entity {
-                    should_delete = false;
-                }
-            }
-
-            if should_delete {
-                to_delete.push(entity);
-            }
+    fn new() -> State {
+        State {
+            game: Game::new(),
+            mapgen_index: 0,
+            mapgen_timer: 0.0,
+            recorder: None,
+            playback: None,
         }
-
-        to_delete
-    }
-
-    fn init_game(&mut self) {
-        let start = self.create_map(1);
-        spawner::player(&mut self.world, start.x, start.y);
     }
 
-    fn create_map(&mut self, new_depth: i32) -> Position {
-        self.mapgen_history.clear();
-        self.mapgen_index = 0;
-        self.mapgen_timer = 0.0;
-
-        let mut builder = map_builders::random_builder(new_depth);
-        builder.build_map();
-        self.mapgen_history = builder.get_snapshot_history();
-        self.world.insert_resource(builder.get_map());
-
-        builder.spawn_entities(&mut self.world);
-
-        builder.get_starting_position()
+    /// Takes a fresh look at the save slots before letting the player pick one.
+    fn open_slot_menu(&mut self, purpose: gui::SlotPurpose) -> RunState {
+        let slots = saveload::list_slots();
+        let selection = match purpose {
+            gui::SlotPurpose::NewGame => slots
+                .iter()
+                .position(|s| matches!(s, saveload::SlotStatus::Empty)),
+            gui::SlotPurpose::LoadGame => slots
+                .iter()
+                .position(|s| matches!(s, saveload::SlotStatus::Saved(_))),
+        }
+        .unwrap_or(0);
+        self.game.world.insert_resource(gui::SlotMenu {
+            slots,
+            message: None,
+        });
+        RunState::SaveSlots { purpose, selection }
     }
 
-    fn goto_next_level(&mut self) {
-        // Delete entities that aren't the player or his/her equipment
-        let to_delete = self.entities_to_remove_on_level_change();
-        for target in to_delete {
-            self.world.despawn(target);
+    /// Keeps a recording of the input, if the run is being recorded.
+    fn record(&mut self, input: replay::Input) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record(input) {
+                rltk::console::log(format!("Recording stopped: {}", e));
+                self.recorder = None;
+            }
         }
-
-        // Build a new map and place the player
-        let old_map = self.world.resource::<Map>();
-        let current_depth = old_map.depth;
-        let start = self.create_map(current_depth + 1);
-
-        // Find the player
-        let (mut player_position, mut player_viewshed, mut player_stats) = self
-            .world
-            .query_filtered::<(&mut Position, &mut Viewshed, &mut CombatStats), With<Player>>()
-            .single_mut(&mut self.world);
-
-        // Place the player and update resources
-        player_position.x = start.x;
-        player_position.y = start.y;
-
-        // Mark the player's visibility as dirty
-        player_viewshed.dirty = true;
-
-        // Let them recover a bit
-        player_stats.hp = i32::max(player_stats.hp, player_stats.max_hp / 2);
-
-        // Notify the player
-        let mut log = self.world.resource_mut::<GameLog>();
-        log.entries
-            .push("You descend to the next level, and take a moment to heal.".to_string());
     }
 
-    fn game_over_cleanup(&mut self) {
-        self.world.clear_entities();
+    /// The answer to an open menu this frame, from the keyboard or the replay.
+    fn menu_input(&mut self, ctx: &Rltk, replayed: Option<replay::Input>) -> Option<MenuInput> {
+        let input = match replayed {
+            Some(replay::Input::Menu(input)) => Some(input),
+            _ => input::menu_input(ctx.key),
+        };
+        if let Some(input) = input {
+            self.record(replay::Input::Menu(input));
+        }
+        input
     }
 
     fn draw_to_screen(&mut self, ctx: &mut Rltk) {
         let mut things = self
+            .game
             .world
             .query_filtered::<(&Position, &Renderable), Without<Hidden>>();
 
-        let map = self.world.resource::<Map>();
-        map.draw(ctx);
+        let map = self.game.world.resource::<Map>();
+        map_render::draw_map(map, ctx);
 
         let mut priority = vec![100; MAPCOUNT];
-        for (pos, render) in things.iter(&self.world) {
+        for (pos, render) in things.iter(&self.game.world) {
             let idx = map.xy_idx(pos.x, pos.y);
-            if map.visible_tiles[idx] && priority[idx] > render.render_order {
+            if map.visible_tiles[idx] && map.is_lit(idx) && priority[idx] > render.render_order {
                 ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph);
                 priority[idx] = render.render_order
             }
         }
 
-        gui::draw_ui(&mut self.world, ctx);
+        gui::draw_ui(&mut self.game.world, ctx);
     }
 }
 
-pub type Key = Option<VirtualKeyCode>;
-pub type FrameTime = f32;
-
-#[derive(PartialEq, Copy, Clone, Resource)]
-pub enum RunState {
-    AwaitingInput,
-    PlayerTurn,
-    MonsterTurn,
-    ShowInventory,
-    ShowDropItem,
-    ShowTargeting {
-        range: i32,
-        item: Entity,
-    },
-    MainMenu {
-        menu_selection: gui::MainMenuSelection,
-    },
-    SaveGame,
-    NextLevel,
-    ShowRemoveItem,
-    GameOver,
-    MagicMapReveal {
-        row: i32,
-    },
-    MapGeneration,
-}
-
 impl GameState for State {
     fn tick(&mut self, ctx: &mut Rltk) {
         ctx.cls();
 
-        let state = *self.world.resource::<RunState>();
-        let mut new_state = match state {
-            RunState::PlayerTurn => RunState::MonsterTurn,
-            RunState::ShowInventory => state,
-            RunState::ShowDropItem => state,
-            RunState::ShowTargeting { range: _, item: _ } => state,
-            RunState::MainMenu { menu_selection: _ } => state,
-            RunState::SaveGame => RunState::MainMenu {
-                menu_selection: gui::MainMenuSelection::LoadGame,
-            },
-            RunState::ShowRemoveItem => state,
-            RunState::GameOver => state,
-            RunState::MapGeneration => state,
-            RunState::NextLevel => RunState::MapGeneration,
-            RunState::MagicMapReveal { row } => RunState::MagicMapReveal { row: row + 1 },
-            _ => RunState::AwaitingInput,
-        };
+        let state = *self.game.world.resource::<RunState>();
+
+        // Snapshot everything for a bug report, whatever the game happens to be doing
+        if ctx.key == Some(VirtualKeyCode::F12)
+            && !matches!(state, RunState::MainMenu { .. })
+            && self.game.world.contains_resource::<Map>()
+        {
+            let message = match saveload::dump_state(&self.game.world) {
+                Ok(file_name) => format!("Game state dumped to {}", file_name),
+                Err(e) => format!("Unable to dump game state: {}", e),
+            };
+            self.game
+                .world
+                .resource_mut::<GameLog>()
+                .entries
+                .push(message);
+        }
+
+        let mut replayed = None;
+        if let Some(playback) = &mut self.playback {
+            match playback.feed(ctx, state) {
+                replay::Fed::Nothing => {}
+                replay::Fed::Input(input) => replayed = Some(input),
+                replay::Fed::Finished(message) => {
+                    self.playback = None;
+                    self.game
+                        .world
+                        .resource_mut::<GameLog>()
+                        .entries
+                        .push(message.to_string());
+                }
+            }
+        }
+
+        let mut new_state = state.next();
 
         match state {
             RunState::MainMenu { menu_selection } => {
                 let result =
-                    gui::main_menu(menu_selection, ctx, self.world.resource::<RexAssets>());
+                    gui::main_menu(menu_selection, ctx, self.game.world.resource::<RexAssets>());
                 match result {
                     gui::MainMenuResult::NoSelection { selected } => {
                         new_state = RunState::MainMenu {
@@ -225,19 +155,14 @@ impl GameState for State {
                     }
                     gui::MainMenuResult::Selected { selected } => match selected {
                         gui::MainMenuSelection::NewGame => {
-                            self.world.insert_resource(GameLog {
-                                entries: vec!["Welcome to Rusty Roguelike".to_string()],
-                            });
-                            self.init_game();
-                            new_state = RunState::MapGeneration;
+                            new_state = self.open_slot_menu(gui::SlotPurpose::NewGame);
                         }
                         gui::MainMenuSelection::LoadGame => {
-                            saveload::load_game(&mut self.world);
-                            self.world.insert_resource(GameLog {
-                                entries: vec!["Welcome back to Rusty Roguelike".to_string()],
-                            });
-                            new_state = RunState::PlayerTurn;
-                            saveload::delete_save();
+                            new_state = self.open_slot_menu(gui::SlotPurpose::LoadGame);
+                        }
+                        gui::MainMenuSelection::HighScores => {
+                            self.game.world.insert_resource(morgue::HighScores::load());
+                            new_state = RunState::HighScores;
                         }
                         gui::MainMenuSelection::Quit => {
                             ::std::process::exit(0);
@@ -246,21 +171,116 @@ impl GameState for State {
                 }
             }
 
+            RunState::SaveSlots { purpose, selection } => {
+                let result = gui::save_slot_menu(
+                    ctx,
+                    purpose,
+                    selection,
+                    self.game.world.resource::<gui::SlotMenu>(),
+                );
+                match result {
+                    gui::SlotMenuResult::NoSelection { selected } => {
+                        new_state = RunState::SaveSlots {
+                            purpose,
+                            selection: selected,
+                        }
+                    }
+                    gui::SlotMenuResult::Cancel => {
+                        self.game.world.remove_resource::<gui::SlotMenu>();
+                        new_state = RunState::MainMenu {
+                            menu_selection: match purpose {
+                                gui::SlotPurpose::NewGame => gui::MainMenuSelection::NewGame,
+                                gui::SlotPurpose::LoadGame => gui::MainMenuSelection::LoadGame,
+                            },
+                        }
+                    }
+                    gui::SlotMenuResult::Selected { slot } => match purpose {
+                        gui::SlotPurpose::NewGame => {
+                            self.game.world.remove_resource::<gui::SlotMenu>();
+                            // The old game in this slot is gone the moment the new one starts
+                            saveload::delete_save(slot);
+                            self.game.world.insert_resource(saveload::SaveSlot(slot));
+                            let seed = RandomNumberGenerator::new().next_u64();
+                            self.game.start_run(seed);
+                            self.recorder = match replay::Recorder::start(seed) {
+                                Ok((recorder, file_name)) => {
+                                    rltk::console::log(format!("Recording to {}", file_name));
+                                    Some(recorder)
+                                }
+                                Err(e) => {
+                                    rltk::console::log(format!("Unable to record: {}", e));
+                                    None
+                                }
+                            };
+                            new_state = RunState::MapGeneration;
+                        }
+                        gui::SlotPurpose::LoadGame => {
+                            match saveload::load_game(&mut self.game.world, slot) {
+                                Ok(loaded) => {
+                                    self.game.world.remove_resource::<gui::SlotMenu>();
+                                    let mut entries =
+                                        vec!["Welcome back to Rusty Roguelike".to_string()];
+                                    if loaded.from_backup {
+                                        entries.push(
+                                            "The save was damaged, so the one before it was loaded."
+                                                .to_string(),
+                                        );
+                                    } else if loaded.autosave {
+                                        entries.push(
+                                            "Picking up from the start of the level.".to_string(),
+                                        );
+                                    }
+                                    self.game.world.insert_resource(GameLog { entries });
+                                    new_state = RunState::PlayerTurn;
+                                }
+                                Err(e) => {
+                                    self.game.world.resource_mut::<gui::SlotMenu>().message =
+                                        Some(format!("Unable to load slot {}: {}", slot + 1, e));
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+
+            RunState::SaveGame if self.playback.is_some() => {
+                // A replay only ever stands in for the player; it doesn't save over their games
+                self.playback = None;
+                self.game
+                    .world
+                    .resource_mut::<GameLog>()
+                    .entries
+                    .push("The player saved and quit here, so the replay ends.".to_string());
+                new_state = RunState::AwaitingInput;
+            }
+
             RunState::SaveGame => {
-                saveload::save_game(&mut self.world).unwrap();
-                self.world.clear_entities();
+                self.recorder = None;
+                if let Err(e) =
+                    saveload::save_game(&mut self.game.world, saveload::SaveKind::Manual)
+                {
+                    self.game
+                        .world
+                        .resource_mut::<GameLog>()
+                        .entries
+                        .push(format!("Unable to save the game: {}", e));
+                    new_state = RunState::AwaitingInput;
+                } else {
+                    self.game.world.clear_entities();
+                }
             }
 
             RunState::NextLevel => {
-                self.goto_next_level();
+                self.game.goto_next_level();
             }
 
             RunState::GameOver => {
-                let result = gui::game_over(ctx);
+                let result =
+                    gui::game_over(ctx, self.game.world.get_resource::<morgue::RunSummary>());
                 match result {
                     gui::GameOverResult::NoSelection => {}
                     gui::GameOverResult::QuitToMenu => {
-                        self.game_over_cleanup();
+                        self.game.game_over_cleanup();
                         new_state = RunState::MainMenu {
                             menu_selection: gui::MainMenuSelection::NewGame,
                         }
@@ -268,8 +288,33 @@ impl GameState for State {
                 }
             }
 
+            RunState::Victory => {
+                let result =
+                    gui::victory(ctx, self.game.world.get_resource::<morgue::RunSummary>());
+                match result {
+                    gui::GameOverResult::NoSelection => {}
+                    gui::GameOverResult::QuitToMenu => {
+                        self.game.game_over_cleanup();
+                        new_state = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        }
+                    }
+                }
+            }
+
+            RunState::HighScores => {
+                let result =
+                    gui::high_scores(ctx, self.game.world.resource::<morgue::HighScores>());
+                if result == gui::GameOverResult::QuitToMenu {
+                    self.game.world.remove_resource::<morgue::HighScores>();
+                    new_state = RunState::MainMenu {
+                        menu_selection: gui::MainMenuSelection::HighScores,
+                    }
+                }
+            }
+
             RunState::MagicMapReveal { row } => {
-                let mut map = self.world.resource_mut::<Map>();
+                let mut map = self.game.world.resource_mut::<Map>();
                 for x in 0..MAPWIDTH {
                     let idx = map.xy_idx(x as i32, row);
                     map.revealed_tiles[idx] = true;
@@ -287,13 +332,15 @@ impl GameState for State {
                     new_state = RunState::AwaitingInput;
                 } else {
                     ctx.cls();
-                    self.mapgen_history[self.mapgen_index].draw(ctx);
+                    map_render::draw_map(&self.game.mapgen_history[self.mapgen_index], ctx);
 
                     self.mapgen_timer += ctx.frame_time_ms;
                     if self.mapgen_timer > 150.0 {
                         self.mapgen_timer = 0.0;
                         self.mapgen_index += 1;
-                        if self.mapgen_index >= self.mapgen_history.len() {
+                        if self.mapgen_index >= self.game.mapgen_history.len() {
+                            // Ready for the next level's history
+                            self.mapgen_index = 0;
                             new_state = RunState::AwaitingInput;
                         }
                     }
@@ -301,10 +348,39 @@ impl GameState for State {
             }
 
             _ => {
-                self.world.insert_non_send_resource::<Key>(ctx.key);
-                self.world
+                if state == RunState::AwaitingInput {
+                    let bound = match replayed {
+                        Some(replay::Input::Action(bound)) => Some(bound),
+                        _ => self
+                            .game
+                            .world
+                            .resource::<KeyBindings>()
+                            .bound_action(ctx.key),
+                    };
+                    if let Some(bound) = bound {
+                        self.record(replay::Input::Action(bound));
+                        self.game.world.resource_mut::<PendingAction>().0 = Some(bound.action());
+                    }
+                }
+                self.game
+                    .world
                     .insert_non_send_resource::<FrameTime>(ctx.frame_time_ms);
-                self.schedule.run(&mut self.world);
+                self.game.schedule.run(&mut self.game.world);
+
+                // The run may have just ended, one way or the other. Either way there's no going
+                // back to its save.
+                let outcome = match *self.game.world.resource::<RunState>() {
+                    RunState::GameOver => Some(morgue::Outcome::Died),
+                    RunState::Victory => Some(morgue::Outcome::Won),
+                    _ => None,
+                };
+                if let Some(outcome) = outcome.filter(|_| self.playback.is_none()) {
+                    self.recorder = None;
+                    morgue::record_run(&mut self.game.world, outcome);
+                    if let Some(slot) = self.game.world.get_resource::<saveload::SaveSlot>() {
+                        saveload::delete_save(slot.0);
+                    }
+                }
 
                 self.draw_to_screen(ctx);
             }
@@ -312,149 +388,150 @@ impl GameState for State {
 
         match state {
             RunState::ShowInventory => {
-                let (result, item) = gui::show_inventory(&mut self.world, ctx);
+                let input = self.menu_input(ctx, replayed);
+                let (result, item) = gui::show_inventory(&mut self.game.world, ctx, input);
                 match result {
                     gui::ItemMenuResult::Cancel => new_state = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let player_entity = self
-                            .world
-                            .query_filtered::<Entity, With<Player>>()
-                            .single(&self.world);
-
                         let item = item.unwrap();
 
-                        if let Some(targeting) = self.world.get::<Ranged>(item) {
+                        if let Some(targeting) = self.game.world.get::<Ranged>(item) {
                             new_state = RunState::ShowTargeting {
                                 range: targeting.range,
                                 item,
                             }
                         } else {
-                            self.world
-                                .entity_mut(player_entity)
-                                .insert(WantsToUseItem { item, target: None });
-                            new_state = RunState::PlayerTurn;
+                            self.game.perform(PlayerAction::UseItem(item, None));
                         }
                     }
                 }
             }
             RunState::ShowDropItem => {
-                let (result, item) = gui::drop_menu_item(&mut self.world, ctx);
+                let input = self.menu_input(ctx, replayed);
+                let (result, item) = gui::drop_menu_item(&mut self.game.world, ctx, input);
                 match result {
                     gui::ItemMenuResult::Cancel => new_state = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let player_entity = self
-                            .world
-                            .query_filtered::<Entity, With<Player>>()
-                            .single(&self.world);
-
-                        self.world
-                            .entity_mut(player_entity)
-                            .insert(WantsToDropItem {
-                                item: item.unwrap(),
-                            });
-
-                        new_state = RunState::PlayerTurn;
+                        self.game.perform(PlayerAction::Drop(item.unwrap()));
                     }
                 }
             }
             RunState::ShowTargeting { range, item } => {
-                let (result, target) = gui::ranged_target(&mut self.world, ctx, range);
+                let (result, target) = match replayed {
+                    Some(replay::Input::Target(Some(target))) => {
+                        (gui::ItemMenuResult::Selected, Some(target))
+                    }
+                    Some(replay::Input::Target(None)) => (gui::ItemMenuResult::Cancel, None),
+                    _ => gui::ranged_target(&mut self.game.world, ctx, range),
+                };
+                if result != gui::ItemMenuResult::NoResponse {
+                    self.record(replay::Input::Target(target));
+                }
                 match result {
                     gui::ItemMenuResult::Cancel => new_state = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let player_entity = self
-                            .world
-                            .query_filtered::<Entity, With<Player>>()
-                            .single(&self.world);
-
-                        self.world
-                            .entity_mut(player_entity)
-                            .insert(WantsToUseItem { item, target });
-
-                        new_state = RunState::PlayerTurn;
+                        self.game.perform(PlayerAction::UseItem(item, target));
                     }
                 }
             }
             RunState::ShowRemoveItem => {
-                let (result, item) = gui::remove_item_menu(&mut self.world, ctx);
+                let input = self.menu_input(ctx, replayed);
+                let (result, item) = gui::remove_item_menu(&mut self.game.world, ctx, input);
                 match result {
                     gui::ItemMenuResult::Cancel => new_state = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let player_entity = self
-                            .world
-                            .query_filtered::<Entity, With<Player>>()
-                            .single(&self.world);
-
-                        self.world
-                            .entity_mut(player_entity)
-                            .insert(WantsToRemoveItem {
-                                item: item.unwrap(),
-                            });
-
-                        new_state = RunState::PlayerTurn;
+                        self.game.perform(PlayerAction::Remove(item.unwrap()));
                     }
                 }
             }
+            RunState::ShowHelp => {
+                let input = self.menu_input(ctx, replayed);
+                let result =
+                    gui::help_screen(ctx, self.game.world.resource::<KeyBindings>(), input);
+                if result == gui::ItemMenuResult::Cancel {
+                    new_state = RunState::AwaitingInput;
+                }
+            }
             _ => {}
         }
 
-        if *self.world.resource::<RunState>() == state {
-            self.world.insert_resource(new_state);
+        if let Some(playback) = &self.playback {
+            playback.draw(ctx);
+        }
+
+        if *self.game.world.resource::<RunState>() == state {
+            self.game.world.insert_resource(new_state);
         }
     }
 }
 
 fn main() -> BError {
+    // Play games with the bot for balance testing, without ever opening a window
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(games) = args
+        .iter()
+        .position(|arg| arg == "--simulate")
+        .and_then(|i| args.get(i + 1))
+    {
+        let games = games.parse().expect("--simulate takes a number of games");
+        let first_seed = args
+            .iter()
+            .position(|arg| arg == "--first-seed")
+            .and_then(|i| args.get(i + 1))
+            .map_or(0, |seed| seed.parse().expect("--first-seed takes a number"));
+        let file_name = simulate::run(first_seed, games).expect("Unable to write the results");
+        rltk::console::log(format!("Results written to {}", file_name));
+        return Ok(());
+    }
+
     let context = RltkBuilder::simple80x50()
         .with_title("Roguelike Tutorial")
         .build()?;
 
-    let mut world = World::new();
-
-    let rng = RandomNumberGenerator::new();
-    world.insert_non_send_resource(rng);
-
-    world.insert_resource(RunState::MainMenu {
-        menu_selection: gui::MainMenuSelection::NewGame,
-    });
-    world.insert_resource(particle::ParticleBuilder::new());
-    world.insert_resource(RexAssets::new());
-
-    world.insert_resource(Events::<DamageEvent>::default());
-
-    let mut state = State {
-        world,
-        schedule: Schedule::default(),
-        mapgen_index: 0,
-        mapgen_history: Vec::new(),
-        mapgen_timer: 0.0,
-    };
-
-    state.schedule.add_systems(
-        (
-            inventory::item_use_system,
-            inventory::item_drop_system,
-            inventory::item_remove_system,
-            hunger::hunger_system,
-            player::player_input_system,
-            inventory::item_collection_system,
-            visibility::visibility_system,
-            player::waiting_system,
-            ai::monster_ai_system,
-            triggers::trigger_system,
-            combat::melee_combat_system,
-            damage::damage_event_reader,
-            damage::damage_system,
-            map::map_indexing_system,
-            particle::cull_dead_particles_system,
-            particle::spawn_particles_system,
-        )
-            .chain(),
+    let mut state = State::new();
+    state.game.world.insert_resource(
+        KeyBindings::load().unwrap_or_else(|e| panic!("Unable to load key bindings: {}", e)),
     );
 
+    // Pick up exactly where a bug report left off
+    if let Some(file_name) = args
+        .iter()
+        .position(|arg| arg == "--load-dump")
+        .and_then(|i| args.get(i + 1))
+    {
+        saveload::load_state_dump(&mut state.game.world, file_name)
+            .expect("Unable to load state dump");
+    }
+    let mut save_settings = saveload::SaveSettings::default();
+    if let Some(name) = args
+        .iter()
+        .position(|arg| arg == "--save-format")
+        .and_then(|i| args.get(i + 1))
+    {
+        save_settings.format =
+            SaveFormat::from_name(name).expect("Save format must be json or binary");
+    }
+    if args.iter().any(|arg| arg == "--no-save-backup") {
+        save_settings.keep_backup = false;
+    }
+    state.game.world.insert_resource(save_settings);
+
+    // Play back a recorded run, from the very beginning
+    if let Some(file_name) = args
+        .iter()
+        .position(|arg| arg == "--replay")
+        .and_then(|i| args.get(i + 1))
+    {
+        let playback = replay::Playback::load(file_name)
+            .unwrap_or_else(|e| panic!("Unable to load replay {}: {}", file_name, e));
+        state.game.start_run(playback.seed);
+        state.playback = Some(playback);
+        state.game.world.insert_resource(RunState::MapGeneration);
+    }
+
     main_loop(context, state)
 }
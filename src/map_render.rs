@@ -0,0 +1,112 @@
+use game_core::map::{Map, TileType};
+use rltk::{to_cp437, FontCharType, Rltk, RGB};
+
+/// Tiles in sight are never drawn darker than this, so the shape of a dark room still shows.
+const MIN_GLOW: f32 = 0.25;
+
+fn is_revealed_and_wall(map: &Map, x: i32, y: i32) -> bool {
+    let idx = map.xy_idx(x, y);
+    map.tiles[idx] == TileType::Wall && map.revealed_tiles[idx]
+}
+
+fn wall_glyph(map: &Map, x: i32, y: i32) -> FontCharType {
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+        return 35;
+    }
+    let mut mask: u8 = 0;
+
+    if is_revealed_and_wall(map, x, y - 1) {
+        mask += 1;
+    }
+    if is_revealed_and_wall(map, x, y + 1) {
+        mask += 2;
+    }
+    if is_revealed_and_wall(map, x - 1, y) {
+        mask += 4;
+    }
+    if is_revealed_and_wall(map, x + 1, y) {
+        mask += 8;
+    }
+
+    match mask {
+        0 => 9,    // Pillar because we can't see neighbors
+        1 => 186,  // Wall only to the north
+        2 => 186,  // Wall only to the south
+        3 => 186,  // Wall to the north and south
+        4 => 205,  // Wall only to the west
+        5 => 188,  // Wall to the north and west
+        6 => 187,  // Wall to the south and west
+        7 => 185,  // Wall to the north, south and west
+        8 => 205,  // Wall only to the east
+        9 => 200,  // Wall to the north and east
+        10 => 201, // Wall to the south and east
+        11 => 204, // Wall to the north, south and east
+        12 => 205, // Wall to the east and west
+        13 => 202, // Wall to the east, west, and south
+        14 => 203, // Wall to the east, west, and north
+        15 => 206, // ╬ Wall on all sides
+        _ => 35,   // We missed one?
+    }
+}
+
+/// Draws every revealed tile of a map, lit as it's lit in the game.
+pub fn draw_map(map: &Map, ctx: &mut Rltk) {
+    let palette = map.theme.palette();
+    let mut y = 0;
+    let mut x = 0;
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        if map.revealed_tiles[idx] {
+            let glyph;
+            let mut fg;
+            let mut bg = RGB::from_f32(0., 0., 0.);
+            match tile {
+                TileType::Floor => {
+                    glyph = palette.floor_glyph;
+                    fg = palette.floor;
+                }
+                TileType::Wall => {
+                    glyph = palette.wall_glyph.unwrap_or_else(|| wall_glyph(map, x, y));
+                    fg = palette.wall;
+                }
+                TileType::DownStairs => {
+                    glyph = to_cp437('>');
+                    fg = palette.stairs;
+                }
+                TileType::Road => {
+                    glyph = to_cp437('≡');
+                    fg = RGB::from_f32(0.5, 0.5, 0.5);
+                }
+                TileType::Grass => {
+                    glyph = to_cp437('"');
+                    fg = RGB::from_f32(0., 0.6, 0.);
+                }
+                TileType::WoodFloor => {
+                    glyph = to_cp437('.');
+                    fg = RGB::from_f32(0.6, 0.4, 0.2);
+                }
+            }
+            if map.bloodstains.contains(&idx) {
+                bg = RGB::from_f32(0.75, 0., 0.);
+            }
+            if map.visible_tiles[idx] {
+                let light = map.light_at(idx);
+                let light = RGB::from_f32(
+                    light.r.max(MIN_GLOW),
+                    light.g.max(MIN_GLOW),
+                    light.b.max(MIN_GLOW),
+                );
+                fg = fg * light;
+                bg = bg * light;
+            } else {
+                fg = fg.to_greyscale()
+            }
+            ctx.set(x, y, fg, bg, glyph);
+        }
+        // Move the coordinates
+        x += 1;
+        if x >= map.width {
+            x = 0;
+            y += 1;
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rltk::{Point, Rltk, VirtualKeyCode, BLACK, YELLOW};
+use serde::{Deserialize, Serialize};
+
+use game_core::{actions::awaiting_player, RunState};
+
+use crate::input::{BoundAction, MenuInput};
+
+/// Bump this whenever a change to the game would make old recordings play out differently.
+const REPLAY_VERSION: u32 = 5;
+
+/// The first line of a recording: everything needed to set the run up again.
+#[derive(Serialize, Deserialize)]
+struct ReplayHeader {
+    version: u32,
+    seed: u64,
+}
+
+/// Something the player did that the game acted on. Keys are recorded by what they were bound
+/// to, so a recording still plays after the bindings change.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum Input {
+    /// A move, or any other bound key, while the game waited on the player.
+    Action(BoundAction),
+    /// An answer to one of the menus.
+    Menu(MenuInput),
+    /// Where the player aimed a ranged item, or `None` if they gave up. Recorded as the tile
+    /// chosen rather than the mouse click, since that depends on the window.
+    Target(Option<Point>),
+}
+
+/// Writes a run's inputs to a file as they happen, one JSON line each, so the recording
+/// survives even if the game doesn't.
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Starts a recording of a new run, returning it and the name of its file.
+    pub fn start(seed: u64) -> io::Result<(Recorder, String)> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_secs());
+        let file_name = format!("./replay-{}.jsonl", timestamp);
+
+        let mut recorder = Recorder {
+            writer: BufWriter::new(File::create(&file_name)?),
+        };
+        recorder.write_line(&ReplayHeader {
+            version: REPLAY_VERSION,
+            seed,
+        })?;
+        Ok((recorder, file_name))
+    }
+
+    pub fn record(&mut self, input: Input) -> io::Result<()> {
+        self.write_line(&input)
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, value)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum PlaybackSpeed {
+    /// Feed in the next input whenever the game is ready for it.
+    FastForward,
+    /// Wait for a key press before each input.
+    Step,
+}
+
+/// What playback did this frame.
+pub enum Fed {
+    /// Nothing for the game this frame.
+    Nothing,
+    /// The next recorded input, for the game to use in place of the keyboard or mouse.
+    Input(Input),
+    /// The recording has run out, or was stopped; the player has the controls now.
+    Finished(&'static str),
+}
+
+/// Plays a recording back into the game, in place of the keyboard.
+pub struct Playback {
+    pub seed: u64,
+    inputs: Vec<Input>,
+    next: usize,
+    speed: PlaybackSpeed,
+}
+
+impl Playback {
+    pub fn load(file_name: &str) -> Result<Playback, String> {
+        let file = File::open(file_name).map_err(|e| e.to_string())?;
+        let mut lines = BufReader::new(file).lines();
+
+        let header = lines.next().ok_or("the recording is empty")?;
+        let header: ReplayHeader = header
+            .map_err(|e| e.to_string())
+            .and_then(|line| serde_json::from_str(&line).map_err(|e| e.to_string()))?;
+        if header.version != REPLAY_VERSION {
+            return Err(format!(
+                "the recording is from format {}, but this build plays format {}",
+                header.version, REPLAY_VERSION
+            ));
+        }
+
+        let mut inputs = Vec::new();
+        for (number, line) in lines.enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            // A crash can leave half a line at the end; everything before it still plays
+            match serde_json::from_str(&line) {
+                Ok(input) => inputs.push(input),
+                Err(e) => {
+                    rltk::console::log(format!("Replay stops at line {}: {}", number + 2, e));
+                    break;
+                }
+            }
+        }
+
+        Ok(Playback {
+            seed: header.seed,
+            inputs,
+            next: 0,
+            speed: PlaybackSpeed::Step,
+        })
+    }
+
+    /// Takes over the frame's input. The real keyboard only works the playback controls: Tab
+    /// switches between stepping and fast-forwarding, Space steps, and Escape hands the game
+    /// back to the player. When the game is waiting on the player and an input is due, the
+    /// recorded one takes the place of whatever was really pressed.
+    pub fn feed(&mut self, ctx: &mut Rltk, state: RunState) -> Fed {
+        if matches!(state, RunState::GameOver | RunState::Victory) {
+            return Fed::Finished("That's the end of the replay.");
+        }
+
+        let pressed = ctx.key.take();
+        ctx.left_click = false;
+
+        let mut step = false;
+        match pressed {
+            Some(VirtualKeyCode::Escape) => return Fed::Finished("You take over from the replay."),
+            Some(VirtualKeyCode::Tab) => {
+                self.speed = match self.speed {
+                    PlaybackSpeed::Step => PlaybackSpeed::FastForward,
+                    PlaybackSpeed::FastForward => PlaybackSpeed::Step,
+                }
+            }
+            Some(VirtualKeyCode::Space) => step = true,
+            _ => {}
+        }
+
+        if !awaiting_player(state) || (self.speed == PlaybackSpeed::Step && !step) {
+            return Fed::Nothing;
+        }
+        let Some(input) = self.inputs.get(self.next).copied() else {
+            return Fed::Finished("The replay is over; the game is yours.");
+        };
+        self.next += 1;
+
+        let in_step = match input {
+            Input::Action(_) => state == RunState::AwaitingInput,
+            Input::Menu(_) => !matches!(
+                state,
+                RunState::AwaitingInput | RunState::ShowTargeting { .. }
+            ),
+            Input::Target(_) => matches!(state, RunState::ShowTargeting { .. }),
+        };
+        if in_step {
+            Fed::Input(input)
+        } else {
+            Fed::Finished("The replay has gone out of step with the game, so it stops here.")
+        }
+    }
+
+    /// Shows where playback is up to, and how to work it.
+    pub fn draw(&self, ctx: &mut Rltk) {
+        let mode = match self.speed {
+            PlaybackSpeed::Step => "stepping (Space: next)",
+            PlaybackSpeed::FastForward => "fast forward",
+        };
+        // The top line is where the targeting prompt goes
+        ctx.print_color(
+            1,
+            1,
+            YELLOW,
+            BLACK,
+            format!(
+                "Replay {}/{}, {} - Tab: switch, Esc: take over",
+                self.next,
+                self.inputs.len(),
+                mode
+            ),
+        );
+    }
+}